@@ -0,0 +1,11 @@
+// Generates the protobuf types for the binary API from proto/now.proto.
+fn main() {
+    println!("cargo:rerun-if-changed=proto/now.proto");
+    // a bundled protoc, so building doesn't need one installed
+    if std::env::var_os("PROTOC").is_none() {
+        if let Ok(protoc) = protoc_bin_vendored::protoc_bin_path() {
+            std::env::set_var("PROTOC", protoc);
+        }
+    }
+    prost_build::compile_protos(&["proto/now.proto"], &["proto"]).expect("proto/now.proto should compile");
+}
@@ -0,0 +1,986 @@
+use rocket::figment::Figment;
+use rocket::form::Form;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::outcome::try_outcome;
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::Redirect;
+use rocket::serde::json::{json, Json, Value};
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+use rocket_dyn_templates::{context, Template};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use crate::announcements::{Announcement, Announcements, Severity};
+use crate::custom;
+use crate::api::{ApiError, Validate, Validated};
+use crate::analytics::{ExperimentStats, VariantStats};
+use crate::audit::{AuditEntry, AuditLog, AuditQuery};
+use crate::outbox::{Delivery, Outbox};
+use crate::questions::{self, Bank, Question, QuestionBank, QuestionEdit, QuestionStore};
+use crate::export::Transcript;
+use crate::limits::{cap, BodyCap};
+use crate::ratelimit::{scope, ApiLimit};
+use crate::routes::AppState;
+use crate::sealed::{CookieKeys, Verified};
+use crate::snapshots::SnapshotStore;
+use crate::surprise;
+use crate::tenants::Tenants;
+use crate::retention::Retention;
+use crate::scheduler::{now_secs, JobStatus, Scheduler};
+use crate::sensitive;
+
+// Admin routes are guarded by a shared token from config (`admin_token`),
+// sent as `Authorization: Bearer <token>` or, for the HTML pages, as the
+// cookie set by /admin/login. No token configured = no admin. A tenant's own
+// `admin_token` signs in a tenant admin, limited to that tenant's questions
+// and rooms; shared questions are read-only to them.
+//
+// Both of those are owners. Further credentials with a narrower role
+// (moderator, support) are listed under `admins`; routes state the least
+// role they need with `RequireRole`.
+
+const TOKEN_COOKIE: &str = "admin_token";
+
+// --- Roles ---
+// Ordered: each role can do everything the ones before it can.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum Role {
+    // read-only: questions, audit trail, failed deliveries
+    Support,
+    // edits questions, closes rooms
+    Moderator,
+    // everything, including imports and jobs
+    Owner,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Credential {
+    // shows up in the audit trail as admin:<name>
+    pub name: String,
+    pub token: String,
+    pub role: Role,
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Credentials(Vec<Credential>);
+
+impl Credentials {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        if figment.find_value("admins").is_err() {
+            return Ok(Credentials::default());
+        }
+        let list: Vec<Credential> = figment
+            .extract_inner("admins")
+            .map_err(|e| format!("invalid `admins` config: {}", e))?;
+        let tenants = Tenants::load(figment).unwrap_or_default();
+        for (i, c) in list.iter().enumerate() {
+            if c.token.is_empty() || list[..i].iter().any(|o| o.token == c.token) {
+                return Err(format!("admin `{}` needs its own non-empty token", c.name));
+            }
+            if let Some(tenant) = c.tenant.as_deref().filter(|t| !tenants.contains(t)) {
+                return Err(format!("admin `{}` belongs to unknown tenant `{}`", c.name, tenant));
+            }
+        }
+        Ok(Credentials(list))
+    }
+
+    fn find(&self, token: &str) -> Option<&Credential> {
+        self.0.iter().find(|c| c.token == token)
+    }
+}
+
+pub fn fairing() -> rocket::fairing::AdHoc {
+    rocket::fairing::AdHoc::on_ignite("Admin Credentials", |rocket| async move {
+        let credentials = Credentials::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(credentials)
+    })
+}
+
+// --- Guard ---
+pub struct Admin {
+    // None = full admin
+    tenant: Option<String>,
+    role: Role,
+    // how this admin shows up in the audit trail
+    actor: String,
+}
+
+impl Admin {
+    fn require_full(&self) -> Result<(), Status> {
+        match self.tenant {
+            None => Ok(()),
+            Some(_) => Err(Status::Forbidden),
+        }
+    }
+
+    fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    // Exports blank player-written text unless `include_sensitive` is passed,
+    // which only owners may do.
+    fn include_sensitive(&self, flag: Option<bool>) -> Result<bool, Status> {
+        match flag {
+            Some(true) if self.role < Role::Owner => Err(Status::Forbidden),
+            Some(true) => Ok(true),
+            _ => Ok(false),
+        }
+    }
+
+    fn sees(&self, q: &Question) -> bool {
+        self.tenant.is_none() || q.visible_to(self.tenant.as_deref())
+    }
+
+    fn can_edit(&self, q: &Question) -> bool {
+        self.tenant.is_none() || q.tenant == self.tenant
+    }
+
+    // NotFound for what this admin can't see, Forbidden for shared questions.
+    fn check_edit(&self, bank: &QuestionBank, id: &str) -> Result<(), Status> {
+        match bank.get(id) {
+            Some(q) if self.can_edit(q) => Ok(()),
+            Some(q) if self.sees(q) => Err(Status::Forbidden),
+            _ => Err(Status::NotFound),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let tenants = req.rocket().state::<Tenants>();
+        let expected: Option<String> = req.rocket().figment().extract_inner("admin_token").ok();
+        let cookie = match (req.cookies().get(TOKEN_COOKIE), req.rocket().state::<CookieKeys>()) {
+            (Some(c), Some(keys)) => match keys.open(c.value()) {
+                Some(Verified::Current(token)) => Some(token),
+                Some(Verified::Previous(token)) => {
+                    // signed before a key rotation: move it onto the new key
+                    req.cookies().add(session_cookie(keys.seal(&token)));
+                    Some(token)
+                }
+                None => None,
+            },
+            _ => None,
+        };
+        let given = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(str::to_string)
+            .or(cookie);
+
+        let credentials = req.rocket().state::<Credentials>();
+        let expected = expected.filter(|e| !e.is_empty());
+        let admin = given.as_deref().and_then(|given| {
+            if expected.as_deref() == Some(given) {
+                return Some(Admin {
+                    tenant: None,
+                    role: Role::Owner,
+                    actor: "admin".to_string(),
+                });
+            }
+            if let Some(tenant) = tenants.and_then(|t| t.for_admin_token(given)) {
+                return Some(Admin {
+                    tenant: Some(tenant.to_string()),
+                    role: Role::Owner,
+                    actor: format!("admin:{}", tenant),
+                });
+            }
+            credentials?.find(given).map(|c| Admin {
+                tenant: c.tenant.clone(),
+                role: c.role,
+                actor: format!("admin:{}", c.name),
+            })
+        });
+        let configured = expected.is_some()
+            || tenants.is_some_and(Tenants::has_admins)
+            || credentials.is_some_and(|c| !c.0.is_empty());
+        match admin {
+            Some(admin) => request::Outcome::Success(admin),
+            None if !configured => request::Outcome::Error((Status::NotFound, ())),
+            None => request::Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+// An admin with at least `R`'s role; 403 otherwise.
+pub struct RequireRole<R>(Admin, PhantomData<R>);
+
+pub trait MinRole {
+    const ROLE: Role;
+}
+
+pub mod role {
+    use super::{MinRole, Role};
+
+    pub struct Support;
+    pub struct Moderator;
+    pub struct Owner;
+
+    impl MinRole for Support {
+        const ROLE: Role = Role::Support;
+    }
+    impl MinRole for Moderator {
+        const ROLE: Role = Role::Moderator;
+    }
+    impl MinRole for Owner {
+        const ROLE: Role = Role::Owner;
+    }
+}
+
+impl<R> Deref for RequireRole<R> {
+    type Target = Admin;
+
+    fn deref(&self) -> &Admin {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, R: MinRole + Send + Sync + 'static> FromRequest<'r> for RequireRole<R> {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let admin = try_outcome!(req.guard::<Admin>().await);
+        if admin.role >= R::ROLE {
+            request::Outcome::Success(RequireRole(admin, PhantomData))
+        } else {
+            request::Outcome::Error((Status::Forbidden, ()))
+        }
+    }
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![
+        dashboard,
+        room_detail,
+        room_close,
+        jobs,
+        dead_letters,
+        experiments,
+        referrals,
+        retention,
+        login_get,
+        login_post,
+        questions_get,
+        question_create,
+        question_get,
+        question_edit,
+        question_retire,
+        import_results,
+        audit_get,
+        announcement_get,
+        announcement_post,
+        announcement_clear
+    ]
+}
+
+// Mounted under /api/v1/admin.
+pub fn api_routes() -> Vec<rocket::Route> {
+    routes![rooms_batch_close, questions_batch_tag, audit_api, stats_api]
+}
+
+// --- Dashboard ---
+// Live numbers and every room; refreshes itself. Tenant admins see their
+// tenant's rooms, and the game counts of the whole site.
+#[get("/")]
+fn dashboard(admin: RequireRole<role::Support>, state: &State<AppState>, scheduler: &State<Scheduler>) -> Template {
+    let now = now_secs();
+    let (stats, rooms) = state.dashboard(admin.tenant.as_deref(), now);
+    // background jobs are site-wide, so only full owners see them (as /jobs)
+    let jobs = (admin.role >= Role::Owner && admin.require_full().is_ok()).then(|| scheduler.status());
+    Template::render(
+        "admin_dashboard",
+        context! {
+            stats,
+            rooms,
+            jobs,
+            now,
+            tenant: &admin.tenant,
+            can_close: admin.role >= Role::Moderator,
+        },
+    )
+}
+
+#[get("/rooms/<code>")]
+fn room_detail(admin: RequireRole<role::Support>, code: String, state: &State<AppState>) -> Option<Template> {
+    let now = now_secs();
+    let detail = state.room_detail(&code, admin.tenant.as_deref(), now)?;
+    Some(Template::render(
+        "admin_room",
+        context! {
+            detail,
+            now,
+            can_close: admin.role >= Role::Moderator,
+        },
+    ))
+}
+
+// Ends the game where it stands, as the batch API does for many.
+#[post("/rooms/<code>/close")]
+fn room_close(
+    admin: RequireRole<role::Moderator>,
+    code: String,
+    state: &State<AppState>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    let codes = vec![code.clone()];
+    match state.close_rooms(&codes, admin.tenant.as_deref()).first() {
+        Some((_, "not_found")) => return Err(Status::NotFound),
+        // already over; nothing to record
+        Some(_) => {}
+        None => audit.record(admin.actor(), "rooms_closed", None, json!({ "codes": codes })),
+    }
+    Ok(Redirect::to(uri!("/admin", room_detail(code = code))))
+}
+
+#[get("/stats")]
+fn stats_api(
+    _limit: ApiLimit<scope::Admin>,
+    admin: RequireRole<role::Support>,
+    state: &State<AppState>,
+) -> Json<Value> {
+    let (stats, rooms) = state.dashboard(admin.tenant.as_deref(), now_secs());
+    Json(json!({ "stats": stats, "rooms": rooms }))
+}
+
+// --- Routes ---
+#[get("/jobs")]
+fn jobs(admin: RequireRole<role::Owner>, scheduler: &State<Scheduler>) -> Result<Json<Vec<JobStatus>>, Status> {
+    admin.require_full()?;
+    Ok(Json(scheduler.status()))
+}
+
+// What the retention job would delete right now, plus what it has deleted
+// since launch. Owners only: the preview lists result tokens.
+#[get("/retention")]
+fn retention(admin: RequireRole<role::Owner>, retention: &State<Retention>) -> Result<Json<Value>, Status> {
+    admin.require_full()?;
+    Ok(Json(json!({
+        "policy": retention.policy(),
+        "would_purge": retention.run(true),
+        "purged": retention.totals(),
+    })))
+}
+
+#[get("/experiments")]
+fn experiments(admin: RequireRole<role::Support>, stats: &State<ExperimentStats>) -> Result<Json<Vec<VariantStats>>, Status> {
+    admin.require_full()?;
+    Ok(Json(stats.report()))
+}
+
+// Rooms and players per `?ref=` code; tenant admins see their tenant's.
+#[get("/referrals")]
+fn referrals(admin: RequireRole<role::Support>, state: &State<AppState>) -> Template {
+    Template::render(
+        "admin_referrals",
+        context! {
+            rows: state.referral_report(admin.tenant.as_deref()),
+            tenant: &admin.tenant,
+        },
+    )
+}
+
+#[get("/outbox/dead?<include_sensitive>")]
+fn dead_letters(
+    admin: RequireRole<role::Support>,
+    include_sensitive: Option<bool>,
+    outbox: &State<Outbox>,
+) -> Result<Json<Vec<Delivery>>, Status> {
+    admin.require_full()?;
+    let mut deliveries = outbox.dead_letters();
+    if !admin.include_sensitive(include_sensitive)? {
+        deliveries.iter_mut().for_each(|d| sensitive::redact(&mut d.payload));
+    }
+    Ok(Json(deliveries))
+}
+
+// --- Announcement ---
+#[derive(FromForm)]
+struct AnnouncementForm {
+    // markdown
+    #[field(validate = len(1..=1000))]
+    text: String,
+    severity: Severity,
+    // blank = until cleared
+    #[field(validate = range(1..=24 * 30))]
+    expires_in_hours: Option<u64>,
+}
+
+#[get("/announcement")]
+fn announcement_get(admin: RequireRole<role::Moderator>, announcements: &State<Announcements>) -> Result<Template, Status> {
+    admin.require_full()?;
+    let latest = announcements.latest();
+    let live = latest.as_ref().is_some_and(|a| a.is_live(now_secs()));
+    let preview = latest.as_ref().map(Announcement::html);
+    Ok(Template::render("admin_announcement", context! { latest, live, preview }))
+}
+
+// Replaces whatever was up.
+#[post("/announcement", data = "<form>")]
+fn announcement_post(
+    admin: RequireRole<role::Moderator>,
+    form: Form<AnnouncementForm>,
+    announcements: &State<Announcements>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    admin.require_full()?;
+    let now = now_secs();
+    let announcement = Announcement {
+        text: form.text.trim().to_string(),
+        severity: form.severity,
+        posted_at: now,
+        expires_at: form.expires_in_hours.map(|h| now + h * 60 * 60),
+    };
+    audit.record(
+        admin.actor(),
+        "announcement_posted",
+        None,
+        json!({ "text": announcement.text, "severity": announcement.severity, "expires_at": announcement.expires_at }),
+    );
+    announcements.post(announcement);
+    Ok(Redirect::to(uri!("/admin", announcement_get)))
+}
+
+#[post("/announcement/clear")]
+fn announcement_clear(
+    admin: RequireRole<role::Moderator>,
+    announcements: &State<Announcements>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    admin.require_full()?;
+    announcements.clear();
+    audit.record(admin.actor(), "announcement_cleared", None, json!({}));
+    Ok(Redirect::to(uri!("/admin", announcement_get)))
+}
+
+// --- Login ---
+#[derive(FromForm)]
+struct LoginForm {
+    token: String,
+}
+
+#[get("/login")]
+fn login_get() -> Template {
+    Template::render("admin_login", context! {})
+}
+
+// The token never sits in the browser in the clear; see `CookieKeys`.
+#[post("/login", data = "<form>")]
+fn login_post(form: Form<LoginForm>, cookies: &CookieJar<'_>, keys: &State<CookieKeys>) -> Redirect {
+    cookies.add(session_cookie(keys.seal(form.token.trim())));
+    Redirect::to(uri!("/admin", dashboard))
+}
+
+fn session_cookie(value: String) -> Cookie<'static> {
+    let mut cookie = Cookie::new(TOKEN_COOKIE, value);
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Strict);
+    cookie.set_path("/admin");
+    cookie
+}
+
+// --- Question editor ---
+#[derive(FromForm)]
+struct QuestionForm {
+    // only read when creating; ids never change
+    id: Option<String>,
+    #[field(validate = len(1..=300))]
+    text: String,
+    #[field(validate = len(1..=40))]
+    category: String,
+    #[field(validate = range(1..=5))]
+    blanks: usize,
+    // checkbox: 18+, dealt only with both players' consent
+    mature: bool,
+}
+
+#[derive(FromForm)]
+struct RetireForm {
+    // checkbox: unchecked brings it back
+    retired: bool,
+}
+
+impl QuestionForm {
+    fn to_edit(&self) -> QuestionEdit {
+        QuestionEdit {
+            text: self.text.trim().to_string(),
+            category: self.category.trim().to_lowercase(),
+            blanks: self.blanks,
+            mature: self.mature,
+        }
+    }
+}
+
+#[get("/questions")]
+fn questions_get(admin: RequireRole<role::Support>, bank: Bank) -> Template {
+    let questions: Vec<&Question> = bank.questions.iter().filter(|q| admin.sees(q)).collect();
+    Template::render(
+        "admin_questions",
+        context! { questions, tenant: &admin.tenant, role: admin.role },
+    )
+}
+
+#[post("/questions", data = "<form>")]
+fn question_create(
+    admin: RequireRole<role::Moderator>,
+    form: Form<QuestionForm>,
+    store: &State<QuestionStore>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    let id = form
+        .id
+        .as_deref()
+        .map(str::trim)
+        // `custom-<n>` belongs to hosts' own questions, see custom.rs
+        .filter(|id| !id.is_empty() && id.len() <= 40 && !custom::is_custom(id))
+        .ok_or(Status::UnprocessableEntity)?;
+    // tenant ids live in their own namespace so they can't collide across tenants
+    let id = match &admin.tenant {
+        Some(tenant) => format!("{}:{}", tenant, id),
+        None => id.to_string(),
+    };
+    store
+        .create(&id, form.to_edit(), admin.tenant.as_deref())
+        .map_err(|_| Status::Conflict)?;
+    audit.record(admin.actor(), "question_created", Some(&id), json!({ "text": form.text.trim() }));
+    Ok(Redirect::to(uri!("/admin", question_get(id))))
+}
+
+#[get("/questions/<id>")]
+fn question_get(admin: RequireRole<role::Support>, id: String, bank: Bank) -> Option<Template> {
+    let current = bank.get(&id).filter(|q| admin.sees(q))?.clone();
+    let editable = admin.role >= Role::Moderator && admin.can_edit(&current);
+    // newest first, each diffed against the one before it
+    let versions: Vec<_> = bank.versions(&id);
+    let history: Vec<_> = versions
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, q)| {
+            let diff = i.checked_sub(1).map(|prev| questions::text_diff(&versions[prev].text, &q.text));
+            context! {
+                version: q.version,
+                text: q.text.clone(),
+                category: q.category.clone(),
+                blanks: q.blanks,
+                diff,
+            }
+        })
+        .collect();
+    Some(Template::render("admin_question", context! { question: current, history, editable }))
+}
+
+#[post("/questions/<id>", data = "<form>")]
+fn question_edit(
+    admin: RequireRole<role::Moderator>,
+    id: String,
+    form: Form<QuestionForm>,
+    store: &State<QuestionStore>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    admin.check_edit(&store.snapshot(), &id)?;
+    store.edit(&id, form.to_edit()).map_err(|_| Status::NotFound)?;
+    audit.record(admin.actor(), "question_edited", Some(&id), json!({ "text": form.text.trim() }));
+    Ok(Redirect::to(uri!("/admin", question_get(id))))
+}
+
+#[post("/questions/<id>/retire", data = "<form>")]
+fn question_retire(
+    admin: RequireRole<role::Moderator>,
+    id: String,
+    form: Form<RetireForm>,
+    store: &State<QuestionStore>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    admin.check_edit(&store.snapshot(), &id)?;
+    store.set_retired(&id, form.retired).map_err(|_| Status::NotFound)?;
+    let action = if form.retired { "question_retired" } else { "question_restored" };
+    audit.record(admin.actor(), action, Some(&id), json!({}));
+    Ok(Redirect::to(uri!("/admin", question_get(id))))
+}
+
+// --- Bulk API ---
+// Batches report per id: one bad id never fails the rest, so cleanups can
+// be retried with just the `failed` list.
+const MAX_BATCH: usize = 500;
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct BatchIds {
+    ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct BatchTag {
+    ids: Vec<String>,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct BatchReport {
+    succeeded: Vec<String>,
+    failed: Vec<BatchFailure>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct BatchFailure {
+    id: String,
+    error: &'static str,
+}
+
+impl BatchReport {
+    fn new(ids: Vec<String>, failed: Vec<(String, &'static str)>) -> Self {
+        let succeeded = ids.into_iter().filter(|id| !failed.iter().any(|(f, _)| f == id)).collect();
+        let failed = failed.into_iter().map(|(id, error)| BatchFailure { id, error }).collect();
+        BatchReport { succeeded, failed }
+    }
+}
+
+// Trimmed and de-duplicated; `Validate` has already checked the count.
+fn batch_ids(ids: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for id in ids.iter().map(|id| id.trim()).filter(|id| !id.is_empty()) {
+        if !out.iter().any(|o| o == id) {
+            out.push(id.to_string());
+        }
+    }
+    out
+}
+
+// Lowercased and de-duplicated, like ids.
+fn batch_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim().to_lowercase()) {
+        if !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+fn check_ids(ids: &[String]) -> Result<(), ApiError> {
+    match batch_ids(ids).len() {
+        0 => Err(ApiError::invalid("ids", "required", "at least one non-blank id is required")),
+        n if n > MAX_BATCH => Err(ApiError::invalid(
+            "ids",
+            "too_many",
+            format!("at most {} ids per batch", MAX_BATCH),
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl Validate for BatchIds {
+    fn validate(&self) -> Result<(), ApiError> {
+        check_ids(&self.ids)
+    }
+}
+
+impl Validate for BatchTag {
+    fn validate(&self) -> Result<(), ApiError> {
+        check_ids(&self.ids)?;
+        if self.tags.is_empty() {
+            return Err(ApiError::invalid("tags", "required", "at least one tag is required"));
+        }
+        for (i, tag) in self.tags.iter().map(|t| t.trim()).enumerate() {
+            let field = format!("tags[{}]", i);
+            if tag.is_empty() {
+                return Err(ApiError::invalid(&field, "required", "tags can't be blank"));
+            }
+            if tag.len() > 30 {
+                return Err(ApiError::invalid(&field, "too_long", "tags are at most 30 characters"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[post("/rooms:batchClose", format = "json", data = "<body>")]
+fn rooms_batch_close(
+    _limit: ApiLimit<scope::Admin>,
+    admin: RequireRole<role::Moderator>,
+    _cap: BodyCap<cap::Batch>,
+    body: Validated<BatchIds>,
+    state: &State<AppState>,
+    audit: &State<AuditLog>,
+) -> Result<Json<BatchReport>, ApiError> {
+    let codes = batch_ids(&body.ids);
+    let failed = state.close_rooms(&codes, admin.tenant.as_deref());
+    let report = BatchReport::new(codes, failed);
+    audit.record(admin.actor(), "rooms_closed", None, json!({ "codes": report.succeeded }));
+    Ok(Json(report))
+}
+
+#[post("/questions:batchTag", format = "json", data = "<body>")]
+fn questions_batch_tag(
+    _limit: ApiLimit<scope::Admin>,
+    admin: RequireRole<role::Moderator>,
+    _cap: BodyCap<cap::Batch>,
+    body: Validated<BatchTag>,
+    store: &State<QuestionStore>,
+    audit: &State<AuditLog>,
+) -> Result<Json<BatchReport>, ApiError> {
+    let ids = batch_ids(&body.ids);
+    let tags = batch_tags(&body.tags);
+    let bank = store.snapshot();
+    let mut failed: Vec<(String, &'static str)> = Vec::new();
+    let allowed: Vec<String> = ids
+        .iter()
+        .filter(|id| match admin.check_edit(&bank, id) {
+            Ok(()) => true,
+            Err(status) => {
+                let why = if status == Status::Forbidden { "forbidden" } else { "not_found" };
+                failed.push((id.to_string(), why));
+                false
+            }
+        })
+        .cloned()
+        .collect();
+    failed.extend(store.tag(&allowed, &tags).into_iter().map(|id| (id, "not_found")));
+    let report = BatchReport::new(ids, failed);
+    audit.record(
+        admin.actor(),
+        "questions_tagged",
+        None,
+        json!({ "ids": report.succeeded, "tags": tags }),
+    );
+    Ok(Json(report))
+}
+
+// --- Import ---
+// One export (`/result/<code>/export?format=json`) or a list of them.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", untagged)]
+enum ImportBody {
+    Many(Vec<Transcript>),
+    One(Box<Transcript>),
+}
+
+// Each transcript is checked on import and reported on its own; here only
+// the size of the batch.
+impl Validate for ImportBody {
+    fn validate(&self) -> Result<(), ApiError> {
+        match self {
+            ImportBody::Many(all) if all.is_empty() => Err(ApiError::new(
+                Status::UnprocessableEntity,
+                "required",
+                None,
+                "at least one transcript is required",
+            )),
+            ImportBody::Many(all) if all.len() > MAX_BATCH => Err(ApiError::new(
+                Status::UnprocessableEntity,
+                "too_many",
+                None,
+                format!("at most {} transcripts per import", MAX_BATCH),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct ImportReport {
+    imported: Vec<Imported>,
+    failed: Vec<BatchFailure>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct Imported {
+    code: String,
+    token: String,
+}
+
+// `couple` is any snapshot token of theirs on this server; without it the
+// import starts a new history.
+#[post("/import/results?<couple>", format = "json", data = "<body>")]
+fn import_results(
+    admin: RequireRole<role::Owner>,
+    couple: Option<String>,
+    _cap: BodyCap<cap::Import>,
+    body: Validated<ImportBody>,
+    snapshots: &State<SnapshotStore>,
+    audit: &State<AuditLog>,
+) -> Result<Json<ImportReport>, ApiError> {
+    admin.require_full()?;
+    let transcripts = match body.into_inner() {
+        ImportBody::Many(all) => all,
+        ImportBody::One(one) => vec![*one],
+    };
+    let into = match couple {
+        Some(token) => Some(snapshots.get(&token).ok_or_else(|| {
+            ApiError::new(Status::NotFound, "not_found", Some("couple"), "no result with that token")
+        })?),
+        None => None,
+    };
+    let outcome = snapshots.import(transcripts, into.as_ref());
+    audit.record(
+        admin.actor(),
+        "results_imported",
+        into.as_ref().map(|s| s.token.as_str()),
+        json!({
+            "imported": outcome.imported.iter().map(|(code, _)| code).collect::<Vec<_>>(),
+            "failed": outcome.failed.len(),
+        }),
+    );
+    Ok(Json(ImportReport {
+        imported: outcome
+            .imported
+            .into_iter()
+            .map(|(code, token)| Imported { code, token })
+            .collect(),
+        failed: outcome
+            .failed
+            .into_iter()
+            .map(|(id, error)| BatchFailure { id, error })
+            .collect(),
+    }))
+}
+
+// --- Audit trail ---
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+// Dates are YYYY-MM-DD (UTC); `to` includes the whole day.
+fn audit_query(actor: Option<String>, action: Option<String>, from: Option<&str>, to: Option<&str>) -> Result<AuditQuery, Status> {
+    let day = |d: Option<&str>| -> Result<Option<u64>, Status> {
+        match d.map(str::trim).filter(|d| !d.is_empty()) {
+            Some(d) => surprise::parse_day(d).map(Some).ok_or(Status::UnprocessableEntity),
+            None => Ok(None),
+        }
+    };
+    Ok(AuditQuery {
+        actor: actor.filter(|a| !a.trim().is_empty()),
+        action: action.filter(|a| !a.trim().is_empty()),
+        from: day(from)?.map(|d| d * DAY_SECS),
+        to: day(to)?.map(|d| (d + 1) * DAY_SECS),
+    })
+}
+
+// Hosts' names and free-text reasons, unless asked for.
+fn redact_entries(entries: &mut [AuditEntry]) {
+    for entry in entries {
+        entry.actor = sensitive::redact_actor(&entry.actor);
+        sensitive::redact(&mut entry.detail);
+    }
+}
+
+#[get("/audit?<actor>&<action>&<from>&<to>&<include_sensitive>")]
+#[allow(clippy::too_many_arguments)]
+fn audit_get(
+    admin: RequireRole<role::Support>,
+    actor: Option<String>,
+    action: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    include_sensitive: Option<bool>,
+    audit: &State<AuditLog>,
+) -> Result<Template, Status> {
+    admin.require_full()?;
+    let query = audit_query(actor.clone(), action.clone(), from.as_deref(), to.as_deref())?;
+    let sensitive = admin.include_sensitive(include_sensitive)?;
+    let mut entries = audit.search(&query);
+    if !sensitive {
+        redact_entries(&mut entries);
+    }
+    Ok(Template::render(
+        "admin_audit",
+        context! {
+            entries,
+            sensitive,
+            actions: audit.actions(),
+            actor,
+            action,
+            from,
+            to,
+        },
+    ))
+}
+
+#[get("/audit?<actor>&<action>&<from>&<to>&<include_sensitive>")]
+#[allow(clippy::too_many_arguments)]
+fn audit_api(
+    _limit: ApiLimit<scope::Admin>,
+    admin: RequireRole<role::Support>,
+    actor: Option<String>,
+    action: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    include_sensitive: Option<bool>,
+    audit: &State<AuditLog>,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    admin.require_full()?;
+    let query = audit_query(actor, action, from.as_deref(), to.as_deref())?;
+    let mut entries = audit.search(&query);
+    if !admin.include_sensitive(include_sensitive)? {
+        redact_entries(&mut entries);
+    }
+    Ok(Json(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::serde::json;
+
+    // (field, code) of a 422 for `value` as a `T`.
+    fn refused<T: Validate + rocket::serde::de::DeserializeOwned>(value: Value) -> (String, String) {
+        let body: T = json::from_value(value).expect("the body should parse");
+        let e = body.validate().expect_err("the body should be refused");
+        assert_eq!(e.status, Status::UnprocessableEntity);
+        (e.field.unwrap_or_default(), e.code)
+    }
+
+    fn pair(field: &str, code: &str) -> (String, String) {
+        (field.to_string(), code.to_string())
+    }
+
+    #[test]
+    fn batches_need_an_id() {
+        assert_eq!(refused::<BatchIds>(json!({ "ids": [] })), pair("ids", "required"));
+        assert_eq!(refused::<BatchIds>(json!({ "ids": [" ", ""] })), pair("ids", "required"));
+    }
+
+    #[test]
+    fn batches_have_a_size_limit() {
+        let ids: Vec<String> = (0..=MAX_BATCH).map(|i| format!("ROOM{}", i)).collect();
+        assert_eq!(refused::<BatchIds>(json!({ "ids": ids })), pair("ids", "too_many"));
+    }
+
+    #[test]
+    fn tagging_needs_a_tag() {
+        assert_eq!(refused::<BatchTag>(json!({ "ids": ["q1"], "tags": [] })), pair("tags", "required"));
+    }
+
+    #[test]
+    fn each_tag_is_checked_where_it_sits() {
+        let blank = json!({ "ids": ["q1"], "tags": ["seasonal", "  "] });
+        assert_eq!(refused::<BatchTag>(blank), pair("tags[1]", "required"));
+        let long = json!({ "ids": ["q1"], "tags": ["x".repeat(31)] });
+        assert_eq!(refused::<BatchTag>(long), pair("tags[0]", "too_long"));
+    }
+
+    #[test]
+    fn tagging_checks_its_ids_too() {
+        assert_eq!(refused::<BatchTag>(json!({ "ids": [], "tags": ["seasonal"] })), pair("ids", "required"));
+    }
+
+    #[test]
+    fn imports_need_a_transcript_and_have_a_size_limit() {
+        assert_eq!(refused::<ImportBody>(json!([])), pair("", "required"));
+        let transcript = json!({ "code": "ROSE42", "score": 0, "players": [], "questions": [] });
+        let many = vec![transcript; MAX_BATCH + 1];
+        assert_eq!(refused::<ImportBody>(json!(many)), pair("", "too_many"));
+    }
+}
@@ -0,0 +1,160 @@
+use parking_lot::RwLock;
+use rocket::serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+// Aggregations over finished games for the result page, and per-variant
+// counts for experiments.
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PlayerLatency {
+    pub player_id: String,
+    pub player_name: String,
+    pub secs: u64,
+    // asked for extra time on this question
+    pub extended: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct QuestionTiming {
+    pub question_index: usize,
+    pub question: String,
+    pub latencies: Vec<PlayerLatency>,
+    // how heavily to shade the heatmap row, 0-100 of the slowest answer overall
+    pub heat: Vec<u32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PlayerTimingSummary {
+    pub player_name: String,
+    pub average_secs: u64,
+    pub fastest_secs: u64,
+    pub slowest_secs: u64,
+    // questions they asked for extra time on
+    pub extensions: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TimingReport {
+    pub questions: Vec<QuestionTiming>,
+    pub players: Vec<PlayerTimingSummary>,
+    // the question where the two of you differed the most
+    pub biggest_gap: Option<TimingGap>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TimingGap {
+    pub question: String,
+    pub fast: PlayerLatency,
+    pub slow: PlayerLatency,
+}
+
+pub fn timing_report(mut questions: Vec<QuestionTiming>) -> TimingReport {
+    let slowest = questions
+        .iter()
+        .flat_map(|q| q.latencies.iter().map(|l| l.secs))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for q in questions.iter_mut() {
+        q.heat = q
+            .latencies
+            .iter()
+            .map(|l| (l.secs * 100 / slowest) as u32)
+            .collect();
+    }
+
+    let mut players: Vec<PlayerTimingSummary> = Vec::new();
+    let mut names: Vec<(String, String)> = Vec::new();
+    for l in questions.iter().flat_map(|q| q.latencies.iter()) {
+        if !names.iter().any(|(id, _)| id == &l.player_id) {
+            names.push((l.player_id.clone(), l.player_name.clone()));
+        }
+    }
+    for (id, name) in names {
+        let own: Vec<&PlayerLatency> = questions
+            .iter()
+            .flat_map(|q| q.latencies.iter())
+            .filter(|l| l.player_id == id)
+            .collect();
+        let secs: Vec<u64> = own.iter().map(|l| l.secs).collect();
+        if secs.is_empty() {
+            continue;
+        }
+        players.push(PlayerTimingSummary {
+            player_name: name,
+            average_secs: secs.iter().sum::<u64>() / secs.len() as u64,
+            fastest_secs: *secs.iter().min().unwrap_or(&0),
+            slowest_secs: *secs.iter().max().unwrap_or(&0),
+            extensions: own.iter().filter(|l| l.extended).count(),
+        });
+    }
+
+    let biggest_gap = questions
+        .iter()
+        .filter_map(|q| {
+            let fast = q.latencies.iter().min_by_key(|l| l.secs)?;
+            let slow = q.latencies.iter().max_by_key(|l| l.secs)?;
+            (slow.secs > fast.secs).then(|| TimingGap {
+                question: q.question.clone(),
+                fast: fast.clone(),
+                slow: slow.clone(),
+            })
+        })
+        .max_by_key(|g| g.slow.secs - g.fast.secs);
+
+    TimingReport {
+        questions,
+        players,
+        biggest_gap,
+    }
+}
+
+// --- Experiments ---
+// Visitors assigned to each variant and how many of them converted, since
+// launch. Conversions are counted per event name (e.g. "room_created").
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct VariantStats {
+    pub experiment: String,
+    pub variant: String,
+    pub exposures: u64,
+    pub conversions: BTreeMap<String, u64>,
+}
+
+#[derive(Clone, Default)]
+pub struct ExperimentStats {
+    // (experiment, variant) -> stats
+    inner: Arc<RwLock<BTreeMap<(String, String), VariantStats>>>,
+}
+
+impl ExperimentStats {
+    fn with(&self, experiment: &str, variant: &str, f: impl FnOnce(&mut VariantStats)) {
+        let mut inner = self.inner.write();
+        let stats = inner
+            .entry((experiment.to_string(), variant.to_string()))
+            .or_insert_with(|| VariantStats {
+                experiment: experiment.to_string(),
+                variant: variant.to_string(),
+                ..VariantStats::default()
+            });
+        f(stats);
+    }
+
+    pub fn exposed(&self, experiment: &str, variant: &str) {
+        self.with(experiment, variant, |s| s.exposures += 1);
+    }
+
+    pub fn converted(&self, experiment: &str, variant: &str, event: &str) {
+        self.with(experiment, variant, |s| *s.conversions.entry(event.to_string()).or_default() += 1);
+    }
+
+    pub fn report(&self) -> Vec<VariantStats> {
+        self.inner.read().values().cloned().collect()
+    }
+}
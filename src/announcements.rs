@@ -0,0 +1,138 @@
+use parking_lot::RwLock;
+use pulldown_cmark::{html, Parser};
+use rocket::fairing::AdHoc;
+use rocket::serde::{Deserialize, Serialize};
+use rocket_dyn_templates::tera;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::jsonfile;
+use crate::scheduler::now_secs;
+
+// A notice from the admins ("maintenance at 22:00 UTC") shown on every page
+// until it expires or is cleared. Written in markdown at /admin/announcement
+// and saved to `announcement_path`. Templates read it through the
+// `announcement()` function registered on the template engine, so no route
+// has to pass it along; see the `announcement` partial. Apps get it from
+// /api/v1/capabilities.
+
+// --- Models ---
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromFormField)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Announcement {
+    // markdown, as the admin wrote it
+    pub text: String,
+    pub severity: Severity,
+    pub posted_at: u64,
+    // unix secs; None = until cleared
+    pub expires_at: Option<u64>,
+}
+
+impl Announcement {
+    pub fn is_live(&self, now: u64) -> bool {
+        self.expires_at.is_none_or(|at| at > now)
+    }
+
+    // The markdown as HTML. Raw HTML in the text is shown as typed.
+    pub fn html(&self) -> String {
+        let escaped = self.text.replace('&', "&amp;").replace('<', "&lt;");
+        let mut out = String::new();
+        html::push_html(&mut out, Parser::new(&escaped));
+        out
+    }
+}
+
+// What templates and the API see.
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AnnouncementView {
+    pub text: String,
+    pub html: String,
+    pub severity: Severity,
+    pub expires_at: Option<u64>,
+}
+
+// --- Store ---
+#[derive(Default)]
+struct Inner {
+    path: Option<PathBuf>,
+    current: Option<Announcement>,
+}
+
+#[derive(Clone, Default)]
+pub struct Announcements {
+    inner: Arc<RwLock<Inner>>,
+}
+
+impl Announcements {
+    // The live one, if any.
+    pub fn current(&self) -> Option<AnnouncementView> {
+        let inner = self.inner.read();
+        let a = inner.current.as_ref().filter(|a| a.is_live(now_secs()))?;
+        Some(AnnouncementView {
+            text: a.text.clone(),
+            html: a.html(),
+            severity: a.severity,
+            expires_at: a.expires_at,
+        })
+    }
+
+    // What the admin page shows, expired or not.
+    pub fn latest(&self) -> Option<Announcement> {
+        self.inner.read().current.clone()
+    }
+
+    pub fn post(&self, announcement: Announcement) {
+        let mut inner = self.inner.write();
+        inner.current = Some(announcement);
+        persist(&inner);
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.write();
+        inner.current = None;
+        persist(&inner);
+    }
+
+    // Loads `announcement_path`, and lets templates call `announcement()`.
+    pub fn fairing(&self) -> AdHoc {
+        let store = self.clone();
+        AdHoc::on_ignite("Announcements", |rocket| async move {
+            let path: Option<PathBuf> = rocket.figment().extract_inner("announcement_path").ok();
+            let mut inner = store.inner.write();
+            if let Some(path) = &path {
+                match jsonfile::read(path) {
+                    Ok(Some(current)) => inner.current = current,
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("announcements: ignoring {}", e),
+                }
+            }
+            inner.path = path;
+            drop(inner);
+            rocket
+        })
+    }
+
+    // For `Template::custom`: `{% set notice = announcement() %}`.
+    pub fn template_function(&self) -> impl tera::Function {
+        let store = self.clone();
+        move |_: &HashMap<String, tera::Value>| Ok(tera::to_value(store.current()).unwrap_or(tera::Value::Null))
+    }
+}
+
+fn persist(inner: &Inner) {
+    let Some(path) = &inner.path else { return };
+    if let Err(e) = jsonfile::write(path, &inner.current) {
+        tracing::error!("announcements: {}", e);
+    }
+}
@@ -0,0 +1,179 @@
+use rocket::data::{self, Data, FromData};
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::{self, serde_json::error::Category, Json};
+use rocket::serde::{Deserialize, Serialize};
+use rocket::Catcher;
+use std::ops::Deref;
+
+// Errors for JSON clients. Everything under /api fails with the same
+// envelope, `{ code, field, message }`: `code` is stable and meant for
+// machines, `field` names the offending part of the body when there is one
+// (`tags[2]`, `couple`), `message` is for people.
+
+// --- Envelope ---
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: Status,
+    pub code: String,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: Status, code: &str, field: Option<&str>, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code: code.to_string(),
+            field: field.map(str::to_string),
+            message: message.into(),
+        }
+    }
+
+    // 422: `field` is present but its value won't do.
+    pub fn invalid(field: &str, code: &str, message: impl Into<String>) -> Self {
+        ApiError::new(Status::UnprocessableEntity, code, Some(field), message)
+    }
+
+    // Remembers this error for the /api catcher, for guards that can only
+    // hand Rocket a status.
+    pub fn stash(self, req: &Request<'_>) -> Status {
+        let status = self.status;
+        req.local_cache(|| Some(self));
+        status
+    }
+}
+
+// Plain statuses (from guards, `?` on helpers) get a code from their reason,
+// e.g. 404 -> "not_found".
+impl From<Status> for ApiError {
+    fn from(status: Status) -> Self {
+        let reason = status.reason().unwrap_or("Error");
+        let code = reason.to_lowercase().replace([' ', '-'], "_");
+        ApiError::new(status, &code, None, reason)
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let status = self.status;
+        Response::build_from(Json(self).respond_to(req)?).status(status).ok()
+    }
+}
+
+// --- Validation ---
+pub trait Validate {
+    fn validate(&self) -> Result<(), ApiError>;
+}
+
+// A JSON body that parsed and passed `Validate`; any failure on the way
+// answers with the envelope instead of reaching the route.
+pub struct Validated<T>(pub T);
+
+impl<T> Validated<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r> + Validate + Send> FromData<'r> for Validated<T> {
+    type Error = ApiError;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let error = match Json::<T>::from_data(req, data).await {
+            data::Outcome::Success(Json(value)) => match value.validate() {
+                Ok(()) => return data::Outcome::Success(Validated(value)),
+                Err(e) => e,
+            },
+            data::Outcome::Forward(f) => return data::Outcome::Forward(f),
+            data::Outcome::Error((status, e)) => parse_error(status, &e),
+        };
+        data::Outcome::Error((error.clone().stash(req), error))
+    }
+}
+
+fn parse_error(status: Status, e: &json::Error<'_>) -> ApiError {
+    let json::Error::Parse(_, e) = e else {
+        // the only I/O failure Rocket reports is running past the limit
+        return ApiError::new(status, "payload_too_large", None, "request body is over the size limit");
+    };
+    let message = e.to_string();
+    // serde doesn't say which field in a structured way, only in the text
+    if let Some(field) = message.strip_prefix("missing field `").and_then(|m| m.split('`').next()) {
+        return ApiError::new(Status::UnprocessableEntity, "required", Some(field), message.clone());
+    }
+    match e.classify() {
+        Category::Data => ApiError::new(Status::UnprocessableEntity, "invalid_type", None, message),
+        _ => ApiError::new(Status::BadRequest, "malformed_json", None, message),
+    }
+}
+
+// --- Catchers ---
+#[catch(default)]
+fn envelope(status: Status, req: &Request<'_>) -> ApiError {
+    req.local_cache(|| None::<ApiError>)
+        .clone()
+        .filter(|e| e.status == status)
+        .unwrap_or_else(|| ApiError::from(status))
+}
+
+// Registered under /api.
+pub fn catchers() -> Vec<Catcher> {
+    catchers![envelope]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "rocket::serde")]
+    #[allow(dead_code)]
+    struct Body {
+        name: String,
+        count: u32,
+    }
+
+    fn parsed(raw: &str) -> ApiError {
+        let e = json::from_str::<Body>(raw).expect_err("the body should not parse");
+        parse_error(Status::BadRequest, &json::Error::Parse(raw, e))
+    }
+
+    #[test]
+    fn a_missing_field_is_named() {
+        let e = parsed(r#"{ "count": 1 }"#);
+        assert_eq!((e.status, e.code.as_str()), (Status::UnprocessableEntity, "required"));
+        assert_eq!(e.field.as_deref(), Some("name"));
+    }
+
+    #[test]
+    fn a_wrong_type_is_invalid() {
+        let e = parsed(r#"{ "name": "Ada", "count": "one" }"#);
+        assert_eq!((e.status, e.code.as_str()), (Status::UnprocessableEntity, "invalid_type"));
+    }
+
+    #[test]
+    fn broken_json_is_malformed() {
+        let e = parsed(r#"{ "name": "Ada", "#);
+        assert_eq!((e.status, e.code.as_str()), (Status::BadRequest, "malformed_json"));
+    }
+
+    #[test]
+    fn an_io_failure_is_an_oversized_body() {
+        let io = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "limit reached");
+        let e = parse_error(Status::PayloadTooLarge, &json::Error::Io(io));
+        assert_eq!((e.status, e.code.as_str()), (Status::PayloadTooLarge, "payload_too_large"));
+    }
+}
@@ -0,0 +1,81 @@
+use rocket::fs::NamedFile;
+use rocket::http::Header;
+use rocket::State;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+// Fingerprinted static assets. Every file under the public folder gets a
+// content-hashed URL (img/beach.svg -> /assets/img/beach.3f9c2a1b.svg) that
+// can be cached forever; a changed file simply gets a new URL.
+
+#[derive(Clone, Debug, Default)]
+pub struct AssetStore {
+    // logical path -> fingerprinted URL
+    urls: HashMap<String, String>,
+    // fingerprinted path (no /assets prefix) -> file on disk
+    files: HashMap<String, PathBuf>,
+}
+
+impl AssetStore {
+    pub fn scan(root: &str) -> Self {
+        let mut store = AssetStore::default();
+        store.walk(Path::new(root), Path::new(root));
+        store
+    }
+
+    fn walk(&mut self, root: &Path, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.walk(root, &path);
+                continue;
+            }
+            let Ok(bytes) = std::fs::read(&path) else { continue };
+            let Ok(rel) = path.strip_prefix(root) else { continue };
+            let logical = rel.to_string_lossy().replace('\\', "/");
+            if logical.starts_with('.') || logical.contains("/.") {
+                continue;
+            }
+
+            let mut hasher = DefaultHasher::new();
+            hasher.write(&bytes);
+            let hash = format!("{:08x}", hasher.finish() as u32);
+            let fingerprinted = match logical.rsplit_once('.') {
+                Some((stem, ext)) if !stem.is_empty() => format!("{}.{}.{}", stem, hash, ext),
+                _ => format!("{}.{}", logical, hash),
+            };
+
+            self.urls
+                .insert(logical, format!("/assets/{}", fingerprinted));
+            self.files.insert(fingerprinted, path);
+        }
+    }
+
+    pub fn url(&self, logical: &str) -> Option<String> {
+        self.urls.get(logical.trim_start_matches('/')).cloned()
+    }
+}
+
+// --- Routes ---
+#[derive(Responder)]
+pub struct Immutable {
+    file: NamedFile,
+    cache: Header<'static>,
+}
+
+#[get("/<path..>")]
+async fn asset(path: PathBuf, store: &State<AssetStore>) -> Option<Immutable> {
+    let key = path.to_string_lossy().replace('\\', "/");
+    let file = store.files.get(&key)?;
+    Some(Immutable {
+        file: NamedFile::open(file).await.ok()?,
+        cache: Header::new("Cache-Control", "public, max-age=31536000, immutable"),
+    })
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![asset]
+}
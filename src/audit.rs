@@ -0,0 +1,143 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::serde::{json::Value, Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::jsonfile;
+use crate::scheduler::now_secs;
+
+// Who did what, kept for `audit_retention_days` (see retention.rs): host
+// tools, admin edits, imports, passphrase lockouts. Stored like the outbox, one JSON file, and
+// searched from /admin/audit.
+
+// newest first, so a search never returns the whole history
+const MAX_RESULTS: usize = 500;
+
+// --- Models ---
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AuditEntry {
+    pub id: String,
+    pub at: u64,
+    // "admin", "admin:<tenant or name>", "host:<name>", "system"
+    pub actor: String,
+    // snake_case verb, e.g. "question_edited"
+    pub action: String,
+    // room code, question id, ... when there is one
+    pub target: Option<String>,
+    pub detail: Value,
+}
+
+// All parts optional; `from` and `to` are unix seconds, `to` exclusive.
+#[derive(Debug, Default)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl AuditQuery {
+    fn matches(&self, e: &AuditEntry) -> bool {
+        let contains = |hay: &str, needle: &Option<String>| {
+            needle
+                .as_deref()
+                .is_none_or(|n| hay.to_lowercase().contains(&n.trim().to_lowercase()))
+        };
+        contains(&e.actor, &self.actor)
+            && self.action.as_deref().is_none_or(|a| e.action == a.trim())
+            && self.from.is_none_or(|from| e.at >= from)
+            && self.to.is_none_or(|to| e.at < to)
+    }
+}
+
+#[derive(Default)]
+struct AuditInner {
+    path: Option<PathBuf>,
+    entries: Vec<AuditEntry>,
+}
+
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    inner: Arc<RwLock<AuditInner>>,
+}
+
+impl AuditLog {
+    pub fn record(&self, actor: &str, action: &str, target: Option<&str>, detail: Value) {
+        let entry = AuditEntry {
+            id: Uuid::new_v4().to_string(),
+            at: now_secs(),
+            actor: actor.to_string(),
+            action: action.to_string(),
+            target: target.map(str::to_string),
+            detail,
+        };
+        let mut inner = self.inner.write();
+        inner.entries.push(entry);
+        persist(&inner);
+    }
+
+    pub fn search(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        self.inner
+            .read()
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| query.matches(e))
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+
+    // Every action seen so far, for the search form.
+    pub fn actions(&self) -> Vec<String> {
+        let mut actions: Vec<String> = self.inner.read().entries.iter().map(|e| e.action.clone()).collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+
+    // Drops entries recorded before `cutoff` (unix secs); returns how many
+    // went, or would go on a dry run.
+    pub fn prune(&self, cutoff: u64, dry_run: bool) -> usize {
+        if dry_run {
+            return self.inner.read().entries.iter().filter(|e| e.at < cutoff).count();
+        }
+        let mut inner = self.inner.write();
+        let before = inner.entries.len();
+        inner.entries.retain(|e| e.at >= cutoff);
+        let pruned = before - inner.entries.len();
+        if pruned > 0 {
+            persist(&inner);
+        }
+        pruned
+    }
+
+    // Reads `audit_path`, then reloads the trail.
+    pub fn fairing(&self) -> AdHoc {
+        let log = self.clone();
+        AdHoc::on_ignite("Audit Log", |rocket| async move {
+            let path: Option<PathBuf> = rocket.figment().extract_inner("audit_path").ok();
+            let mut inner = log.inner.write();
+            if let Some(path) = &path {
+                match jsonfile::read(path) {
+                    Ok(Some(entries)) => inner.entries = entries,
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("audit: ignoring {}", e),
+                }
+            }
+            inner.path = path;
+            drop(inner);
+            rocket
+        })
+    }
+}
+
+fn persist(inner: &AuditInner) {
+    let Some(path) = &inner.path else { return };
+    if let Err(e) = jsonfile::write(path, &inner.entries) {
+        tracing::error!("audit: {}", e);
+    }
+}
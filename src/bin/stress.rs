@@ -0,0 +1,371 @@
+// Load smoke test: N couples playing whole games at once against a running
+// server, through the JSON API (/api/v1). Each couple creates a room, the
+// partner joins, both answer every question after a random think time, and
+// the result is fetched. Reports latency percentiles and errors per step.
+//
+//   cargo run --release --bin stress -- --url http://127.0.0.1:8000 --couples 50
+//
+// Every simulated player sends its own bearer token, so API rate limits
+// count them as separate callers, the way real phones would be. Form limits
+// don't apply; only the API is used. Exits 1 if the error rate is over
+// `--max-error-rate` (default 1%), so it can gate a release.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use reqwest::cookie::Jar;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::{Client, RequestBuilder};
+use rocket::futures::future::join_all;
+use rocket::tokio::time::sleep;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// between polls while waiting on the partner
+const POLL: Duration = Duration::from_millis(200);
+const WORDS: &[&str] = &["pizza", "beach", "dogs", "music", "sushi", "hiking", "movies", "coffee"];
+
+// --- Options ---
+struct Options {
+    url: String,
+    couples: usize,
+    // 10, 25 or 50, as the server allows
+    questions: usize,
+    // think time before each answer, up to this
+    jitter: Duration,
+    // per game, from create to result
+    timeout: Duration,
+    // confirms the age gate when the server has one
+    age: Option<u8>,
+    max_error_rate: f64,
+}
+
+impl Options {
+    fn parse() -> Result<Options, String> {
+        let mut options = Options {
+            url: "http://127.0.0.1:8000".to_string(),
+            couples: 10,
+            questions: 10,
+            jitter: Duration::from_millis(300),
+            timeout: Duration::from_secs(300),
+            age: None,
+            max_error_rate: 0.01,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", flag));
+            let number = |v: String| v.parse::<u64>().map_err(|_| format!("{} takes a whole number", flag));
+            match flag.as_str() {
+                "--url" => options.url = value()?.trim_end_matches('/').to_string(),
+                "--couples" => options.couples = number(value()?)? as usize,
+                "--questions" => options.questions = number(value()?)? as usize,
+                "--jitter-ms" => options.jitter = Duration::from_millis(number(value()?)?),
+                "--timeout-secs" => options.timeout = Duration::from_secs(number(value()?)?),
+                "--age" => options.age = Some(number(value()?)?.min(99) as u8),
+                "--max-error-rate" => {
+                    let rate = value()?;
+                    let invalid = |_| format!("--max-error-rate takes a fraction, not {}", rate);
+                    options.max_error_rate = rate.parse().map_err(invalid)?;
+                }
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                other => return Err(format!("unknown option {}\n{}", other, USAGE)),
+            }
+        }
+        if options.couples == 0 {
+            return Err("--couples must be at least 1".to_string());
+        }
+        Ok(options)
+    }
+}
+
+const USAGE: &str = "usage: stress [--url URL] [--couples N] [--questions 10|25|50] [--jitter-ms MS] \
+[--timeout-secs S] [--age YEARS] [--max-error-rate FRACTION]";
+
+// --- Measurements ---
+#[derive(Default)]
+struct Step {
+    latencies: Vec<Duration>,
+    // status (0 = no response) -> count, for anything that wasn't 2xx
+    errors: BTreeMap<u16, usize>,
+}
+
+#[derive(Default)]
+struct Stats {
+    steps: BTreeMap<&'static str, Step>,
+    games_finished: usize,
+    games_failed: usize,
+}
+
+impl Stats {
+    fn merge(&mut self, other: Stats) {
+        for (name, step) in other.steps {
+            let mine = self.steps.entry(name).or_default();
+            mine.latencies.extend(step.latencies);
+            for (status, n) in step.errors {
+                *mine.errors.entry(status).or_default() += n;
+            }
+        }
+        self.games_finished += other.games_finished;
+        self.games_failed += other.games_failed;
+    }
+
+    fn requests(&self) -> usize {
+        self.steps.values().map(|s| s.latencies.len()).sum()
+    }
+
+    fn errors(&self) -> usize {
+        self.steps.values().flat_map(|s| s.errors.values()).sum()
+    }
+
+    fn report(&mut self, elapsed: Duration) {
+        println!(
+            "{:<8} {:>7} {:>7} {:>8} {:>8} {:>8} {:>8}  errors by status",
+            "step", "calls", "errors", "p50 ms", "p90 ms", "p99 ms", "max ms"
+        );
+        for (name, step) in &mut self.steps {
+            step.latencies.sort();
+            let errors: usize = step.errors.values().sum();
+            let by_status: Vec<String> = step.errors.iter().map(|(s, n)| format!("{}×{}", s, n)).collect();
+            println!(
+                "{:<8} {:>7} {:>7} {:>8} {:>8} {:>8} {:>8}  {}",
+                name,
+                step.latencies.len(),
+                errors,
+                millis(percentile(&step.latencies, 0.50)),
+                millis(percentile(&step.latencies, 0.90)),
+                millis(percentile(&step.latencies, 0.99)),
+                millis(step.latencies.last().copied()),
+                by_status.join(" "),
+            );
+        }
+        let requests = self.requests();
+        println!(
+            "\n{} games finished, {} failed · {} requests in {:.1}s ({:.0}/s) · error rate {:.2}%",
+            self.games_finished,
+            self.games_failed,
+            requests,
+            elapsed.as_secs_f64(),
+            requests as f64 / elapsed.as_secs_f64().max(0.001),
+            self.error_rate() * 100.0,
+        );
+    }
+
+    fn error_rate(&self) -> f64 {
+        self.errors() as f64 / self.requests().max(1) as f64
+    }
+}
+
+// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    Some(sorted[rank - 1])
+}
+
+fn millis(d: Option<Duration>) -> String {
+    d.map(|d| format!("{:.1}", d.as_secs_f64() * 1000.0)).unwrap_or_else(|| "-".to_string())
+}
+
+// --- Players ---
+struct Player {
+    client: Client,
+    base: String,
+    stats: Stats,
+}
+
+impl Player {
+    fn new(options: &Options, token: String) -> Result<Player, String> {
+        let jar = Arc::new(Jar::default());
+        if let Some(age) = options.age {
+            let url = options.url.parse().map_err(|e| format!("bad --url: {}", e))?;
+            jar.add_cookie_str(&format!("age_ok={}; Path=/", age), &url);
+        }
+        let mut headers = HeaderMap::new();
+        let bearer = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|e| e.to_string())?;
+        headers.insert(AUTHORIZATION, bearer);
+        let client = Client::builder()
+            .cookie_provider(jar)
+            .default_headers(headers)
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Player {
+            client,
+            base: options.url.clone(),
+            stats: Stats::default(),
+        })
+    }
+
+    // Sends, times and records one call; the body on a 2xx.
+    async fn call(&mut self, step: &'static str, request: RequestBuilder) -> Option<Value> {
+        let started = Instant::now();
+        let outcome = request.send().await;
+        let entry = self.stats.steps.entry(step).or_default();
+        let response = match outcome {
+            Ok(response) => response,
+            Err(_) => {
+                entry.latencies.push(started.elapsed());
+                *entry.errors.entry(0).or_default() += 1;
+                return None;
+            }
+        };
+        let status = response.status();
+        let body = response.json::<Value>().await.ok();
+        entry.latencies.push(started.elapsed());
+        if !status.is_success() {
+            *entry.errors.entry(status.as_u16()).or_default() += 1;
+            return None;
+        }
+        body
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    // Answers whatever is asked until the game is over. False on timeout.
+    async fn play(&mut self, code: &str, options: &Options, deadline: Instant) -> bool {
+        let path = format!("/api/v1/rooms/{}", code);
+        while Instant::now() < deadline {
+            let request = self.client.get(self.url(&path));
+            let Some(room) = self.call("poll", request).await else {
+                sleep(POLL).await;
+                continue;
+            };
+            if room["finished"].as_bool() == Some(true) {
+                return true;
+            }
+            let question = &room["question"];
+            let flag = |key: &str| room[key].as_bool() == Some(true);
+            // the partner hasn't joined yet, or it's their turn to answer
+            if flag("waiting_for_partner") || flag("answered") || room["seat"] != "playing" || !question.is_object() {
+                sleep(POLL).await;
+                continue;
+            }
+            let think = rand::thread_rng().gen_range(Duration::ZERO..=options.jitter);
+            sleep(think).await;
+            let request = self.client.post(self.url(&format!("{}/answer", path))).json(&answer_for(question));
+            self.call("answer", request).await;
+        }
+        false
+    }
+}
+
+// A plausible answer of the right shape; picked at random, so some match.
+fn answer_for(question: &Value) -> Value {
+    let mut rng = rand::thread_rng();
+    let options = question["options"].as_array().cloned().unwrap_or_default();
+    let mut body = match question["kind"]["type"].as_str().unwrap_or("free_text") {
+        "ranking" => {
+            let mut order: Vec<usize> = (0..options.len()).collect();
+            order.shuffle(&mut rng);
+            json!({ "order": order })
+        }
+        "multiple_choice" => json!({ "option": options.choose(&mut rng) }),
+        "picture_choice" => json!({ "option": options.choose(&mut rng).map(|o| o["id"].clone()) }),
+        "scale" => {
+            let min = question["options"]["min"].as_i64().unwrap_or(1);
+            let max = question["options"]["max"].as_i64().unwrap_or(min).max(min);
+            json!({ "value": rng.gen_range(min..=max) })
+        }
+        _ => {
+            let blanks = question["blanks"].as_u64().unwrap_or(1).max(1) as usize;
+            let words: Vec<&str> = WORDS.choose_multiple(&mut rng, blanks.min(WORDS.len())).copied().collect();
+            json!({ "answer": words })
+        }
+    };
+    // a late answer for a question that has moved on comes back 409
+    body["question"] = question["index"].clone();
+    body
+}
+
+// --- Games ---
+async fn couple(options: Arc<Options>, run: u64, n: usize) -> Stats {
+    let players = (
+        Player::new(&options, format!("stress-{}-{}-host", run, n)),
+        Player::new(&options, format!("stress-{}-{}-guest", run, n)),
+    );
+    let (mut host, mut guest) = match players {
+        (Ok(host), Ok(guest)) => (host, guest),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("couple {}: {}", n, e);
+            return Stats {
+                games_failed: 1,
+                ..Stats::default()
+            };
+        }
+    };
+    let deadline = Instant::now() + options.timeout;
+    let create = host.client.post(host.url("/api/v1/rooms")).json(&json!({
+        "host_name": format!("Host {}", n),
+        "question_count": options.questions,
+    }));
+    let code = host.call("create", create).await.and_then(|room| room["code"].as_str().map(str::to_string));
+    let finished = match code {
+        Some(code) => {
+            let join = guest
+                .client
+                .post(guest.url(&format!("/api/v1/rooms/{}/join", code)))
+                .json(&json!({ "name": format!("Guest {}", n) }));
+            if guest.call("join", join).await.is_some() {
+                let (a, b) = rocket::futures::join!(
+                    host.play(&code, &options, deadline),
+                    guest.play(&code, &options, deadline)
+                );
+                let result = host.client.get(host.url(&format!("/api/v1/rooms/{}/result", code)));
+                a && b && host.call("result", result).await.is_some()
+            } else {
+                false
+            }
+        }
+        None => false,
+    };
+    let mut stats = Stats::default();
+    stats.merge(host.stats);
+    stats.merge(guest.stats);
+    if finished {
+        stats.games_finished = 1;
+    } else {
+        stats.games_failed = 1;
+    }
+    stats
+}
+
+#[rocket::main]
+async fn main() {
+    let options = match Options::parse() {
+        Ok(options) => Arc::new(options),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    println!(
+        "{} couples × {} questions against {} (think time up to {} ms)\n",
+        options.couples,
+        options.questions,
+        options.url,
+        options.jitter.as_millis()
+    );
+    // tells this run's tokens apart from the last one's
+    let run: u64 = rand::thread_rng().gen();
+    let started = Instant::now();
+    let games = (0..options.couples).map(|n| rocket::tokio::spawn(couple(options.clone(), run, n)));
+    let mut stats = Stats::default();
+    for game in join_all(games).await {
+        match game {
+            Ok(game) => stats.merge(game),
+            Err(e) => {
+                eprintln!("a game panicked: {}", e);
+                stats.games_failed += 1;
+            }
+        }
+    }
+    stats.report(started.elapsed());
+    if stats.error_rate() > options.max_error_rate || stats.games_finished == 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,136 @@
+use rand::seq::SliceRandom;
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::scoring;
+
+// Optional warm-up before the first question: three throwaway prompts, then
+// both players say whether their two answers count as the same. How close
+// the answers they accepted (and rejected) were sets how forgiving free-text
+// matching is for the rest of the game. Nothing here scores points.
+
+pub const PROMPTS_PER_ROUND: usize = 3;
+// calibration never loosens matching past this
+pub const MIN_THRESHOLD: f64 = 0.6;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Calibration {
+    pub prompts: Vec<String>,
+    // per prompt: player id -> answer
+    pub answers: Vec<HashMap<String, String>>,
+    // per prompt: player id -> "that's the same answer"
+    pub verdicts: Vec<HashMap<String, bool>>,
+    // skipped, or every prompt judged
+    pub done: bool,
+}
+
+// Where one player is in the round.
+pub enum Step<'a> {
+    Answer { index: usize, prompt: &'a str },
+    // partner hasn't answered, or hasn't judged yet
+    Wait,
+    Judge { index: usize, prompt: &'a str, answers: &'a HashMap<String, String> },
+    Done,
+}
+
+impl Calibration {
+    pub fn start() -> Self {
+        let mut prompts: Vec<String> = PROMPTS.iter().map(|p| p.to_string()).collect();
+        prompts.shuffle(&mut rand::thread_rng());
+        prompts.truncate(PROMPTS_PER_ROUND);
+        Calibration {
+            answers: vec![HashMap::new(); prompts.len()],
+            verdicts: vec![HashMap::new(); prompts.len()],
+            prompts,
+            done: false,
+        }
+    }
+
+    // First prompt not everyone has judged yet.
+    fn current(&self, players: &[&str]) -> Option<usize> {
+        (0..self.prompts.len()).find(|&i| !players.iter().all(|id| self.verdicts[i].contains_key(*id)))
+    }
+
+    pub fn step(&self, player: &str, players: &[&str]) -> Step<'_> {
+        let Some(i) = self.current(players).filter(|_| !self.done) else {
+            return Step::Done;
+        };
+        let prompt = self.prompts[i].as_str();
+        if !self.answers[i].contains_key(player) {
+            Step::Answer { index: i, prompt }
+        } else if !players.iter().all(|id| self.answers[i].contains_key(*id)) || self.verdicts[i].contains_key(player) {
+            Step::Wait
+        } else {
+            Step::Judge { index: i, prompt, answers: &self.answers[i] }
+        }
+    }
+
+    // `index` guards against a stale double-submit landing on the next prompt.
+    pub fn answer(&mut self, player: &str, index: usize, text: &str, players: &[&str]) -> bool {
+        let text = text.trim();
+        if self.done || text.is_empty() || self.current(players) != Some(index) {
+            return false;
+        }
+        self.answers[index].entry(player.to_string()).or_insert_with(|| text.to_string());
+        true
+    }
+
+    // Marks the round done once the last prompt is judged.
+    pub fn judge(&mut self, player: &str, index: usize, same: bool, players: &[&str]) -> bool {
+        if self.done || self.current(players) != Some(index) {
+            return false;
+        }
+        if !players.iter().all(|id| self.answers[index].contains_key(*id)) {
+            return false;
+        }
+        self.verdicts[index].entry(player.to_string()).or_insert(same);
+        self.done = self.current(players).is_none();
+        true
+    }
+
+    // Matching threshold for the couple: just forgiving enough for every
+    // pair they called the same, as long as that still tells apart every
+    // pair they called different. Falls back to `default` when they judged
+    // nothing the same, or judged inconsistently.
+    pub fn threshold(&self, players: &[&str], default: f64) -> f64 {
+        let mut loosest_same: Option<f64> = None;
+        let mut closest_different: Option<f64> = None;
+        for (answers, verdicts) in self.answers.iter().zip(&self.verdicts) {
+            let texts: Vec<&str> = players.iter().filter_map(|id| answers.get(*id).map(String::as_str)).collect();
+            if texts.len() < players.len() || !players.iter().all(|id| verdicts.contains_key(*id)) {
+                continue;
+            }
+            let similarity = texts
+                .iter()
+                .enumerate()
+                .flat_map(|(i, a)| texts[i + 1..].iter().map(move |b| scoring::similarity(a, b)))
+                .fold(1.0, f64::min);
+            if verdicts.values().all(|same| *same) {
+                loosest_same = Some(loosest_same.map_or(similarity, |s: f64| s.min(similarity)));
+            } else {
+                closest_different = Some(closest_different.map_or(similarity, |s: f64| s.max(similarity)));
+            }
+        }
+        let Some(loosest) = loosest_same else {
+            return default;
+        };
+        let threshold = loosest.clamp(MIN_THRESHOLD, default.max(MIN_THRESHOLD));
+        match closest_different {
+            Some(d) if d >= threshold => default,
+            _ => threshold,
+        }
+    }
+}
+
+// Short, open prompts that couples tend to answer almost-but-not-quite alike.
+const PROMPTS: &[&str] = &[
+    "Name a breakfast food.",
+    "Name a famous movie.",
+    "Name a city you'd love to visit.",
+    "Name a board game.",
+    "Name something you'd pack for the beach.",
+    "Name a pizza topping.",
+    "Name a sport.",
+    "Name a singer or band.",
+];
@@ -0,0 +1,212 @@
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::{Build, Rocket};
+use std::path::{Path, PathBuf};
+
+use crate::questions::QuestionStore;
+
+// Launch-time sanity checks. Anything missing here would otherwise only show
+// up as a 500 on the first request that needs it.
+
+pub const PUBLIC_DIR: &str = "public";
+
+const REQUIRED_KEYS: &[&str] = &["template_dir"];
+// JSON files the app reads at launch and writes as it runs
+const STORAGE_KEYS: &[&str] = &[
+    "outbox_path",
+    "question_store_path",
+    "snapshot_path",
+    "audit_path",
+    "room_store_path",
+    "shutdown_snapshot_path",
+];
+
+// Every problem found, in a human-readable form. Empty means good to go.
+pub fn run(figment: &Figment) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for key in REQUIRED_KEYS {
+        if figment.find_value(key).is_err() {
+            problems.push(format!("missing config key `{}` (set it in Rocket.toml)", key));
+        }
+    }
+
+    if let Ok(dir) = figment.extract_inner::<PathBuf>("template_dir") {
+        if !dir.is_dir() {
+            problems.push(format!("template directory `{}` does not exist", dir.display()));
+        }
+    }
+
+    if !PathBuf::from(PUBLIC_DIR).is_dir() {
+        problems.push(format!("static directory `{}` does not exist", PUBLIC_DIR));
+    }
+
+    if let Ok(path) = figment.extract_inner::<PathBuf>("questions_path") {
+        if let Err(e) = crate::questions::QuestionBank::from_file(&path) {
+            problems.push(e);
+        }
+    }
+
+    if let Err(e) = crate::verdicts::Verdicts::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::story::Story::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::sealed::MessageKey::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::sealed::CookieKeys::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::tenants::Tenants::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::admin::Credentials::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::site::Site::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::experiments::Experiments::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::content::ContentRules::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::retention::Policy::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::codes::CodeScheme::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::scoring::CategoryWeights::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::storage::StorageKind::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::ratelimit::RateLimitConfig::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::ratelimit::FormRateConfig::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::telemetry::Settings::load(figment) {
+        problems.push(e);
+    }
+
+    if let Err(e) = crate::features::Features::load(figment) {
+        problems.push(e);
+    }
+
+    problems
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Runtime Checks", |rocket| async move {
+        let problems = run(rocket.figment());
+        if problems.is_empty() {
+            return Ok(rocket);
+        }
+
+        eprintln!("Refusing to launch, startup checks failed:");
+        for p in &problems {
+            eprintln!("  - {}", p);
+        }
+        Err(rocket)
+    })
+}
+
+// --- Templates ---
+// Debug builds re-read templates whenever they change on disk. Release
+// builds compile them once at launch, so a syntax error refuses to start
+// rather than 500ing on the first page view, and serve them from memory.
+pub fn template_mode() -> &'static str {
+    if cfg!(debug_assertions) {
+        "reload"
+    } else {
+        "cached"
+    }
+}
+
+// --- Self-test ---
+// `cargo run -- check`: everything a launch does short of binding the port
+// (config, templates, the question bank, storage), reported line by line.
+// Deploy pipelines run it before switching traffic; true = all good.
+pub async fn self_test(rocket: Rocket<Build>) -> bool {
+    // keep Rocket's own launch chatter out of the report, errors aside
+    let figment = rocket.figment().clone().merge(("log_level", "critical"));
+    let rocket = rocket.configure(figment.clone());
+    let mut report: Vec<(String, Vec<String>)> = Vec::new();
+
+    report.push(("config".to_string(), run(&figment)));
+    for key in STORAGE_KEYS {
+        if let Ok(path) = figment.extract_inner::<PathBuf>(key) {
+            report.push((format!("storage {}", path.display()), storage_problems(&path)));
+        }
+    }
+    // ignition loads the templates and the stores, and runs every fairing
+    // that can refuse to launch
+    match rocket.ignite().await {
+        Ok(rocket) => {
+            report.push((format!("templates ({})", template_mode()), Vec::new()));
+            let bank = rocket.state::<QuestionStore>().map(|s| s.snapshot());
+            let problems = match &bank {
+                Some(bank) => bank.problems(),
+                None => vec!["question store is not installed".to_string()],
+            };
+            let count = bank.map(|b| b.questions.len()).unwrap_or(0);
+            report.push((format!("question bank ({} questions)", count), problems));
+        }
+        Err(e) => report.push(("boot".to_string(), vec![e.to_string()])),
+    }
+
+    let mut ok = true;
+    println!("Self-test:");
+    for (what, problems) in &report {
+        if problems.is_empty() {
+            println!("  ok    {}", what);
+        }
+        for p in problems {
+            ok = false;
+            println!("  FAIL  {}: {}", what, p);
+        }
+    }
+    println!("{}", if ok { "All checks passed." } else { "Some checks failed." });
+    ok
+}
+
+// Existing files must parse, and the folder must take writes.
+fn storage_problems(path: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Ok(raw) = std::fs::read_to_string(path) {
+        if let Err(e) = rocket::serde::json::from_str::<rocket::serde::json::Value>(&raw) {
+            problems.push(format!("unreadable, it would be ignored: {}", e));
+        }
+    }
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let probe = dir.join(".self-test");
+    let writable = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe));
+    if let Err(e) = writable {
+        problems.push(format!("cannot write to `{}`: {}", dir.display(), e));
+    }
+    problems
+}
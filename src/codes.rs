@@ -0,0 +1,158 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+
+use crate::i18n;
+
+// Room codes. The default is six letters and digits ("A9K4ZT"); with
+// `room_codes = "words"` new rooms get two words and a number instead
+// ("SUNNY-TIGER-42"), in the host's language, which is easier to read out
+// over the phone. Joining accepts either scheme however it's typed, so
+// changing the setting never strands an open room.
+
+const ALPHANUMERIC_LEN: usize = 6;
+// no 0/O or 1/I/L, which look alike on a small screen or a scrap of paper
+const UNAMBIGUOUS: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+// draws before giving up on finding a code nobody holds
+const MAX_ATTEMPTS: usize = 20;
+
+// --- Models ---
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CodeScheme {
+    #[default]
+    Alphanumeric,
+    Words,
+}
+
+impl CodeScheme {
+    // `room_codes = "alphanumeric"` (default) or `"words"`.
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        if figment.find_value("room_codes").is_err() {
+            return Ok(CodeScheme::default());
+        }
+        let choice: String = figment
+            .extract_inner("room_codes")
+            .map_err(|e| format!("invalid `room_codes` config: {}", e))?;
+        match choice.as_str() {
+            "alphanumeric" => Ok(CodeScheme::Alphanumeric),
+            "words" => Ok(CodeScheme::Words),
+            other => Err(format!("room_codes `{}` must be \"alphanumeric\" or \"words\"", other)),
+        }
+    }
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Room Codes", |rocket| async move {
+        let scheme = CodeScheme::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(scheme)
+    })
+}
+
+// A room code in its canonical form: uppercase, words joined by '-'.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RoomCode(String);
+
+impl RoomCode {
+    fn generate(scheme: CodeScheme, locale: Option<&str>) -> Self {
+        match scheme {
+            CodeScheme::Alphanumeric => RoomCode(alphanumeric()),
+            CodeScheme::Words => RoomCode(words(locale.unwrap_or(i18n::DEFAULT_LOCALE))),
+        }
+    }
+
+    // A fresh code `taken` says nobody holds; None if every draw was taken.
+    pub fn generate_unique(scheme: CodeScheme, locale: Option<&str>, taken: impl Fn(&str) -> bool) -> Option<Self> {
+        (0..MAX_ATTEMPTS)
+            .map(|_| RoomCode::generate(scheme, locale))
+            .find(|code| !taken(&code.0))
+    }
+
+    // What a player typed on the join form: any case, with spaces, dots or
+    // underscores between the parts. "sunny tiger 42" -> "SUNNY-TIGER-42",
+    // "a9k 4zt" -> "A9K4ZT".
+    pub fn parse(input: &str) -> Option<Self> {
+        let parts: Vec<String> = input
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_uppercase())
+            .collect();
+        let is_words = parts.len() == 3
+            && parts[..2].iter().all(|w| w.chars().all(char::is_alphabetic))
+            && parts[2].chars().all(|c| c.is_ascii_digit());
+        match parts.len() {
+            0 => None,
+            _ if is_words => Some(RoomCode(parts.join("-"))),
+            _ => Some(RoomCode(parts.concat())),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+// --- Generators ---
+fn alphanumeric() -> String {
+    let mut rng = rand::thread_rng();
+    (0..ALPHANUMERIC_LEN)
+        .map(|_| char::from(UNAMBIGUOUS[rng.gen_range(0..UNAMBIGUOUS.len())]))
+        .collect()
+}
+
+// locale, adjectives, animals, adjective first?
+// Plain ASCII only, so nobody has to find an accent key to join.
+const WORD_LISTS: &[(&str, &[&str], &[&str], bool)] = &[
+    (
+        "en",
+        &[
+            "SUNNY", "BRAVE", "CALM", "LUCKY", "QUICK", "GENTLE", "HAPPY", "CLEVER", "BRIGHT", "COSY", "WILD",
+            "JOLLY", "PROUD", "SWIFT", "MERRY", "BOLD",
+        ],
+        &[
+            "TIGER", "OTTER", "PANDA", "FOX", "EAGLE", "KOALA", "WHALE", "LION", "RABBIT", "ROBIN", "BEAR",
+            "DOLPHIN", "FALCON", "HERON", "MOOSE", "BADGER",
+        ],
+        true,
+    ),
+    (
+        "fr",
+        &[
+            "JOYEUX", "CALME", "RAPIDE", "MALIN", "BRAVE", "SAGE", "FIER", "DOUX", "VIF", "HEUREUX", "LIBRE",
+            "AGILE", "RIEUR", "TENDRE", "FORT", "JOLI",
+        ],
+        &[
+            "TIGRE", "RENARD", "LOUP", "OURS", "LION", "CHAT", "HIBOU", "CASTOR", "LAPIN", "DAUPHIN", "AIGLE",
+            "PANDA", "ZEBRE", "LEZARD", "MOUTON", "PINSON",
+        ],
+        false,
+    ),
+    (
+        "es",
+        &[
+            "ALEGRE", "VALIENTE", "TRANQUILO", "RAPIDO", "LISTO", "FELIZ", "BRILLANTE", "AMABLE", "LIBRE", "SABIO",
+            "FUERTE", "SERENO", "CURIOSO", "GENIAL", "TIERNO", "AUDAZ",
+        ],
+        &[
+            "TIGRE", "ZORRO", "LOBO", "OSO", "LEON", "GATO", "BUHO", "CASTOR", "CONEJO", "DELFIN", "AGUILA",
+            "PANDA", "CEBRA", "PINGUINO", "CABALLO", "HALCON",
+        ],
+        false,
+    ),
+];
+
+fn words(locale: &str) -> String {
+    let (_, adjectives, animals, adjective_first) = WORD_LISTS
+        .iter()
+        .find(|(l, ..)| *l == locale)
+        .unwrap_or(&WORD_LISTS[0]);
+    let mut rng = rand::thread_rng();
+    let adjective = adjectives.choose(&mut rng).copied().unwrap_or("SUNNY");
+    let animal = animals.choose(&mut rng).copied().unwrap_or("TIGER");
+    let number: u8 = rng.gen_range(10..100);
+    if *adjective_first {
+        format!("{}-{}-{}", adjective, animal, number)
+    } else {
+        format!("{}-{}-{}", animal, adjective, number)
+    }
+}
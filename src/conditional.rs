@@ -0,0 +1,183 @@
+use rocket::fairing::AdHoc;
+use rocket::http::{Header, Method, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::{self, Responder, Response};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::negotiate;
+use crate::surprise;
+
+// Conditional GETs for the pages clients poll (/play, /result). A page's
+// ETag hashes the room version plus whatever else it depends on (viewer,
+// language, representation); Last-Modified is when the room version was
+// first seen. Polls that change nothing get a 304, and HEAD (link previews,
+// uptime checks) gets the validators without a render.
+
+// --- Validators ---
+pub struct Validator {
+    etag: String,
+    modified: u64,
+}
+
+impl Validator {
+    fn headers(&self, response: &mut Response<'_>) {
+        response.set_header(Header::new("ETag", self.etag.clone()));
+        response.set_header(Header::new("Last-Modified", http_date(self.modified)));
+        // cache, but always ask first
+        response.set_header(Header::new("Cache-Control", "no-cache"));
+    }
+}
+
+// When a room version was first seen, for Last-Modified. Lives on the room
+// but isn't persisted; after a restart pages start "modified" again.
+#[derive(Debug, Default)]
+pub struct Stamp {
+    version: AtomicU64,
+    since: AtomicU64,
+}
+
+impl Clone for Stamp {
+    fn clone(&self) -> Self {
+        Stamp::default()
+    }
+}
+
+impl Stamp {
+    pub fn since(&self, version: u64, now: u64) -> u64 {
+        if self.version.swap(version, Ordering::Relaxed) != version {
+            self.since.store(now, Ordering::Relaxed);
+            return now;
+        }
+        self.since.load(Ordering::Relaxed)
+    }
+}
+
+// --- Guard ---
+// Rocket answers HEAD by routing it again as GET, so the method is gone by
+// the time guards run; this remembers it.
+struct WasHead(bool);
+
+pub fn fairing() -> AdHoc {
+    AdHoc::on_request("HEAD Requests", |req, _| {
+        Box::pin(async move {
+            req.local_cache(|| WasHead(req.method() == Method::Head));
+        })
+    })
+}
+
+pub struct Conditional {
+    head: bool,
+    // the page and its JSON are different representations
+    json: bool,
+    if_none_match: Option<String>,
+    if_modified_since: Option<u64>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Conditional {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        request::Outcome::Success(Conditional {
+            head: req.local_cache(|| WasHead(false)).0,
+            json: negotiate::wants_json(req),
+            if_none_match: req.headers().get_one("If-None-Match").map(str::to_string),
+            if_modified_since: req.headers().get_one("If-Modified-Since").and_then(parse_http_date),
+        })
+    }
+}
+
+impl Conditional {
+    // `parts` is everything the page depends on; `modified` when that last
+    // changed, in unix secs.
+    pub fn validator(&self, parts: impl Hash, modified: u64) -> Validator {
+        let mut hasher = DefaultHasher::new();
+        (parts, self.json).hash(&mut hasher);
+        Validator {
+            etag: format!("\"{:016x}\"", hasher.finish()),
+            modified,
+        }
+    }
+
+    // Err with the response to send when there's nothing to render: a 304,
+    // or the validators alone for HEAD.
+    pub fn check<R>(&self, validator: Validator) -> Result<Validator, Fresh<R>> {
+        // If-None-Match wins over If-Modified-Since when both are sent
+        let unchanged = match (&self.if_none_match, self.if_modified_since) {
+            (Some(tags), _) => tags.split(',').any(|t| {
+                let t = t.trim();
+                t == "*" || t.trim_start_matches("W/") == validator.etag
+            }),
+            (None, Some(since)) => validator.modified <= since,
+            (None, None) => false,
+        };
+        if unchanged {
+            Err(Fresh::Unchanged(Status::NotModified, validator))
+        } else if self.head {
+            Err(Fresh::Unchanged(Status::Ok, validator))
+        } else {
+            Ok(validator)
+        }
+    }
+}
+
+// --- Responder ---
+pub enum Fresh<R> {
+    // 304, or a bodiless 200 for HEAD
+    Unchanged(Status, Validator),
+    Render(R, Validator),
+}
+
+impl<R> Fresh<R> {
+    pub fn into_validator(self) -> Validator {
+        match self {
+            Fresh::Unchanged(_, validator) | Fresh::Render(_, validator) => validator,
+        }
+    }
+}
+
+impl<'r, R: Responder<'r, 'static>> Responder<'r, 'static> for Fresh<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let (mut response, validator) = match self {
+            Fresh::Unchanged(status, validator) => (Response::build().status(status).finalize(), validator),
+            Fresh::Render(inner, validator) => (inner.respond_to(req)?, validator),
+        };
+        validator.headers(&mut response);
+        Ok(response)
+    }
+}
+
+// --- HTTP dates ---
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// IMF-fixdate, e.g. "Sun, 06 Nov 1994 08:49:37 GMT".
+fn http_date(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (year, month, day) = surprise::civil_from_days(days);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+fn parse_http_date(raw: &str) -> Option<u64> {
+    let parts: Vec<&str> = raw.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts[..] else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| *m == month)? + 1;
+    let midnight = surprise::parse_day(&format!("{}-{}-{}", year, month, day))?;
+    let mut hms = time.split(':').map(|n| n.parse::<u64>().ok());
+    let (h, m, s) = (hms.next()??, hms.next()??, hms.next()??);
+    Some(midnight * 86_400 + h * 3600 + m * 60 + s)
+}
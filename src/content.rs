@@ -0,0 +1,227 @@
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::form::{Form, FromForm};
+use rocket::http::{Cookie, CookieJar, SameSite};
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::Redirect;
+use rocket::serde::Deserialize;
+use rocket::time::Duration;
+use rocket::State;
+use rocket_dyn_templates::{context, Template};
+use std::collections::HashMap;
+
+use crate::questions::Question;
+
+// Who may see what. `min_age` puts an "are you old enough" page in front of
+// creating and joining rooms; `regions` hide question categories (and mature
+// questions) from visitors in some countries. The country comes from a header
+// set by the CDN or GeoIP proxy (`region_header`), else `default_region`.
+// Nothing configured = everyone sees everything, as before.
+
+const AGE_COOKIE: &str = "age_ok";
+const AGE_COOKIE_DAYS: i64 = 365;
+
+// --- Models ---
+#[derive(Clone, Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RegionRule {
+    // categories never offered or dealt in this region
+    #[serde(default)]
+    pub blocked_categories: Vec<String>,
+    // false = no mature questions, and rooms can't opt in
+    #[serde(default = "allowed")]
+    pub mature: bool,
+}
+
+fn allowed() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ContentRules {
+    min_age: Option<u8>,
+    region_header: Option<String>,
+    default_region: Option<String>,
+    // ISO country code (uppercase) -> rule
+    regions: HashMap<String, RegionRule>,
+}
+
+impl ContentRules {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let min_age = match figment.find_value("min_age") {
+            Ok(_) => {
+                let age: u8 = figment
+                    .extract_inner("min_age")
+                    .map_err(|e| format!("invalid `min_age` config: {}", e))?;
+                if !(1..=99).contains(&age) {
+                    return Err(format!("min_age `{}` must be between 1 and 99", age));
+                }
+                Some(age)
+            }
+            Err(_) => None,
+        };
+        let regions: HashMap<String, RegionRule> = match figment.find_value("regions") {
+            Ok(_) => figment
+                .extract_inner("regions")
+                .map_err(|e| format!("invalid `regions` config: {}", e))?,
+            Err(_) => HashMap::new(),
+        };
+        let mut by_code = HashMap::new();
+        for (code, mut rule) in regions {
+            if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(format!("region `{}` must be a two-letter country code", code));
+            }
+            for category in rule.blocked_categories.iter_mut() {
+                *category = category.trim().to_lowercase();
+                if category.is_empty() {
+                    return Err(format!("region `{}` blocks an empty category", code));
+                }
+            }
+            by_code.insert(code.to_ascii_uppercase(), rule);
+        }
+        let default_region = figment
+            .extract_inner::<String>("default_region")
+            .ok()
+            .map(|r| r.trim().to_ascii_uppercase());
+        Ok(ContentRules {
+            min_age,
+            region_header: figment.extract_inner("region_header").ok(),
+            default_region,
+            regions: by_code,
+        })
+    }
+
+    pub fn min_age(&self) -> Option<u8> {
+        self.min_age
+    }
+
+    fn region_of(&self, req: &Request<'_>) -> Option<String> {
+        self.region_header
+            .as_deref()
+            .and_then(|h| req.headers().get_one(h))
+            .map(|r| r.trim().to_ascii_uppercase())
+            .filter(|r| r.len() == 2)
+            .or_else(|| self.default_region.clone())
+    }
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Content Rules", |rocket| async move {
+        let rules = ContentRules::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(rules)
+    })
+}
+
+// --- Guards ---
+// What this visitor's region allows.
+#[derive(Clone, Debug)]
+pub struct Restrictions {
+    pub blocked: Vec<String>,
+    pub mature: bool,
+}
+
+impl Restrictions {
+    pub fn allows(&self, q: &Question) -> bool {
+        (self.mature || !q.mature) && !self.blocked.contains(&q.category)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Restrictions {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let rules = req.rocket().state::<ContentRules>();
+        let region = rules.and_then(|r| r.region_of(req));
+        let rule = rules.zip(region.as_deref()).and_then(|(r, code)| r.regions.get(code));
+        request::Outcome::Success(Restrictions {
+            blocked: rule.map(|r| r.blocked_categories.clone()).unwrap_or_default(),
+            mature: rule.map(|r| r.mature).unwrap_or(true),
+        })
+    }
+}
+
+// Whether this browser has been through the age gate (or there is none).
+pub struct AgeGate {
+    passed: bool,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AgeGate {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let min_age = req.rocket().state::<ContentRules>().and_then(|r| r.min_age);
+        // the cookie remembers the age confirmed, so raising min_age asks again
+        let confirmed = req
+            .cookies()
+            .get(AGE_COOKIE)
+            .and_then(|c| c.value().parse::<u8>().ok());
+        let passed = match min_age {
+            Some(min) => confirmed.is_some_and(|age| age >= min),
+            None => true,
+        };
+        request::Outcome::Success(AgeGate { passed })
+    }
+}
+
+impl AgeGate {
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+
+    // Err sends the visitor through the gate and back to `next`.
+    #[allow(clippy::result_large_err)]
+    pub fn check(&self, next: &str) -> Result<(), Redirect> {
+        if self.passed {
+            return Ok(());
+        }
+        Err(Redirect::to(uri!(age_get(next = Some(next)))))
+    }
+}
+
+// --- Routes ---
+pub fn routes() -> Vec<rocket::Route> {
+    routes![age_get, age_post]
+}
+
+#[derive(FromForm)]
+struct AgeForm {
+    next: Option<String>,
+    // false = "I'm not old enough"
+    confirm: bool,
+}
+
+// Only local paths, so the gate can't be used as an open redirect.
+fn local_path(next: Option<&str>) -> String {
+    next.filter(|n| n.starts_with('/') && !n.starts_with("//") && !n.starts_with("/\\"))
+        .unwrap_or("/")
+        .to_string()
+}
+
+#[get("/age?<next>")]
+fn age_get(next: Option<&str>, rules: &State<ContentRules>) -> Template {
+    Template::render(
+        "age_gate",
+        context! { min_age: rules.min_age, next: local_path(next), declined: false },
+    )
+}
+
+#[post("/age", data = "<form>")]
+fn age_post(form: Form<AgeForm>, rules: &State<ContentRules>, cookies: &CookieJar<'_>) -> Result<Redirect, Template> {
+    let next = local_path(form.next.as_deref());
+    if !form.confirm {
+        return Err(Template::render(
+            "age_gate",
+            context! { min_age: rules.min_age, next, declined: true },
+        ));
+    }
+    if let Some(min_age) = rules.min_age {
+        let mut cookie = Cookie::new(AGE_COOKIE, min_age.to_string());
+        cookie.set_path("/");
+        cookie.set_same_site(SameSite::Lax);
+        cookie.set_max_age(Duration::days(AGE_COOKIE_DAYS));
+        cookies.add(cookie);
+    }
+    Ok(Redirect::to(next))
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use crate::questions::{Question, QuestionKind};
+
+// Questions the host writes for their own room, added from the lobby before
+// play begins (`POST /create/questions`). They live on that room only: dealt
+// alongside the bank's, copied into its deck like any other, and never
+// shared with other rooms, the word cloud or the recommender, which all go
+// by bank ids.
+
+pub const MAX_PER_ROOM: usize = 5;
+pub const MAX_CHARS: usize = 200;
+pub const CATEGORY: &str = "custom";
+
+// Normalizes one submitted question: control and invisible formatting
+// characters go, whitespace runs become one space, and angle brackets are
+// dropped so nothing reads as markup wherever the text ends up (emails,
+// exports). Ok(None) for a blank field.
+pub fn clean(raw: &str) -> Result<Option<String>, &'static str> {
+    let kept: String = raw
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .filter(|c| !c.is_control() && !is_invisible(*c) && !matches!(c, '<' | '>'))
+        .collect();
+    let text = kept.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if text.chars().count() > MAX_CHARS {
+        return Err("Keep each question to 200 characters or fewer.");
+    }
+    if !text.chars().any(char::is_alphanumeric) {
+        return Err("Each question needs some words in it.");
+    }
+    Ok(Some(text))
+}
+
+// Zero-width and bidi override characters, which can hide or flip text.
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2069}' | '\u{FEFF}')
+}
+
+// The room's `n`th (1-based) custom question, as a plain free-text one.
+pub fn question(n: usize, text: String) -> Question {
+    Question {
+        id: format!("{}-{}", CATEGORY, n),
+        text,
+        category: CATEGORY.to_string(),
+        blanks: 1,
+        kind: QuestionKind::FreeText,
+        follow_up: None,
+        translations: HashMap::new(),
+        version: 1,
+        retired: false,
+        tags: Vec::new(),
+        tenant: None,
+        mature: false,
+    }
+}
+
+pub fn is_custom(id: &str) -> bool {
+    id.strip_prefix(CATEGORY).is_some_and(|n| n.starts_with('-'))
+}
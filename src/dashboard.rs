@@ -0,0 +1,149 @@
+use parking_lot::Mutex;
+use rocket::serde::Serialize;
+use std::sync::Arc;
+
+// What /admin shows at a glance: rooms open right now and who's in them,
+// from the rooms themselves, plus finished games counted as they end. The
+// counts live in `AppState` rather than being recomputed from rooms, since
+// finished rooms expire and get purged long before the day is over.
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+// --- Counters ---
+#[derive(Clone, Default)]
+pub struct GameTally {
+    inner: Arc<Mutex<Tally>>,
+}
+
+#[derive(Default)]
+struct Tally {
+    // UTC day (unix secs / DAY_SECS) the daily counts are for
+    day: u64,
+    games_today: u64,
+    score_sum_today: u64,
+    games_since_launch: u64,
+}
+
+impl GameTally {
+    pub fn finished(&self, score: u32, at: u64) {
+        let mut tally = self.inner.lock();
+        tally.roll(at / DAY_SECS);
+        tally.games_today += 1;
+        tally.score_sum_today += u64::from(score);
+        tally.games_since_launch += 1;
+    }
+
+    // (games finished today, their average score), as of `now`.
+    pub fn today(&self, now: u64) -> (u64, Option<u32>) {
+        let mut tally = self.inner.lock();
+        tally.roll(now / DAY_SECS);
+        let average = (tally.games_today > 0)
+            .then(|| (tally.score_sum_today as f64 / tally.games_today as f64).round() as u32);
+        (tally.games_today, average)
+    }
+
+    pub fn since_launch(&self) -> u64 {
+        self.inner.lock().games_since_launch
+    }
+}
+
+impl Tally {
+    fn roll(&mut self, day: u64) {
+        if day != self.day {
+            self.day = day;
+            self.games_today = 0;
+            self.score_sum_today = 0;
+        }
+    }
+}
+
+// --- Views ---
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DashboardStats {
+    // not finished yet
+    pub active_rooms: usize,
+    pub players_online: usize,
+    pub games_today: u64,
+    pub average_score_today: Option<u32>,
+    pub games_since_launch: u64,
+    pub memory: MemoryUsage,
+}
+
+// Estimated room memory (see footprint.rs), for capacity planning.
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MemoryUsage {
+    pub total_bytes: usize,
+    pub average_bytes: usize,
+    pub largest_bytes: usize,
+    // the per-room cap, and rooms past 80% of it
+    pub room_max_bytes: usize,
+    pub rooms_near_cap: usize,
+}
+
+impl MemoryUsage {
+    pub fn of(rooms: impl Iterator<Item = usize>, room_max_bytes: usize) -> MemoryUsage {
+        let (mut total, mut largest, mut count, mut near) = (0, 0, 0, 0);
+        for bytes in rooms {
+            total += bytes;
+            largest = largest.max(bytes);
+            count += 1;
+            near += usize::from(bytes * 5 >= room_max_bytes * 4);
+        }
+        MemoryUsage {
+            total_bytes: total,
+            average_bytes: if count == 0 { 0 } else { total / count },
+            largest_bytes: largest,
+            room_max_bytes,
+            rooms_near_cap: near,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RoomRow {
+    pub code: String,
+    // couple, friends or party
+    pub game_mode: String,
+    // classic, guess, story, coop or endless
+    pub mode: &'static str,
+    pub capacity: usize,
+    pub players: usize,
+    pub online: usize,
+    // 1-based question being played; the last one once finished
+    pub question: usize,
+    // None for endless rooms
+    pub total: Option<usize>,
+    pub finished: bool,
+    pub score: u32,
+    // estimated, see footprint.rs
+    pub bytes: usize,
+    pub created_at: u64,
+    // latest heartbeat or action from anyone
+    pub last_active: u64,
+    pub tenant: Option<String>,
+}
+
+// One seat, by position; names stay out of the dashboard.
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SeatRow {
+    pub seat: usize,
+    pub player_id: String,
+    pub score: u32,
+    pub online: bool,
+    pub last_seen: u64,
+    pub is_host: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RoomDetail {
+    pub room: RoomRow,
+    pub seats: Vec<SeatRow>,
+    pub events: usize,
+    // the newest events' types, newest first
+    pub recent: Vec<String>,
+}
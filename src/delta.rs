@@ -0,0 +1,171 @@
+use parking_lot::RwLock;
+use rocket::http::{ContentType, Header};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::{json, Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::features;
+
+// Room views handed out by GET /api/v1/rooms/<code>, remembered by version
+// so a client polling with `?since=<version>` gets only what changed, as a
+// JSON Merge Patch (RFC 7386). A field that became null comes as null, which
+// the patch format reads as "remove"; clients treat a missing field as null
+// anyway. Kept in memory, a few versions per room and viewer; a version we
+// no longer have just gets the full view again. How many of each are kept
+// comes from `Features` (view_versions, view_viewers).
+
+#[derive(Clone, Default)]
+pub struct ViewHistory {
+    // "<code>/<viewer>/<locale>" -> recent views, newest last
+    views: Arc<RwLock<HashMap<String, VecDeque<(String, Value)>>>>,
+}
+
+impl ViewHistory {
+    // Stamps `view` (an object) with its `version` and remembers it. The
+    // patch from `since` to it, if we still have that version.
+    pub fn record(&self, key: &str, view: &mut Value, since: Option<&str>) -> Option<Value> {
+        let version = version_of(view);
+        view["version"] = json!(version);
+        let features = features::current();
+        let mut views = self.views.write();
+        // rare; everyone's next poll is simply a full one
+        if views.len() >= features.view_viewers && !views.contains_key(key) {
+            views.clear();
+        }
+        let history = views.entry(key.to_string()).or_default();
+        let patch = since
+            .and_then(|since| history.iter().find(|(v, _)| v == since))
+            .map(|(_, old)| merge_patch(old, view));
+        if history.back().is_none_or(|(v, _)| *v != version) {
+            history.push_back((version, view.clone()));
+            if history.len() > features.view_versions {
+                history.pop_front();
+            }
+        }
+        patch
+    }
+}
+
+fn version_of(view: &Value) -> String {
+    let mut hasher = DefaultHasher::new();
+    view.to_string().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// The RFC 7386 patch that turns `old` into `new`: changed and added fields,
+// removed ones as null, unchanged ones left out. Arrays are replaced whole.
+pub fn merge_patch(old: &Value, new: &Value) -> Value {
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return new.clone();
+    };
+    let mut patch = Map::new();
+    for (key, value) in new {
+        match old.get(key) {
+            Some(before) if before == value => {}
+            Some(before) => {
+                patch.insert(key.clone(), merge_patch(before, value));
+            }
+            None => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    for key in old.keys().filter(|k| !new.contains_key(*k)) {
+        patch.insert(key.clone(), Value::Null);
+    }
+    Value::Object(patch)
+}
+
+pub struct MergePatch(pub Value);
+
+impl<'r> Responder<'r, 'static> for MergePatch {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let content_type = ContentType::new("application", "merge-patch+json");
+        Response::build_from((content_type, self.0.to_string()).respond_to(req)?)
+            .header(Header::new("Vary", "Accept"))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // What a client does with a patch (RFC 7386, section 2).
+    fn apply(target: &Value, patch: &Value) -> Value {
+        let Value::Object(patch) = patch else {
+            return patch.clone();
+        };
+        let mut target = match target {
+            Value::Object(target) => target.clone(),
+            _ => Map::new(),
+        };
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                let merged = apply(target.get(key).unwrap_or(&Value::Null), value);
+                target.insert(key.clone(), merged);
+            }
+        }
+        Value::Object(target)
+    }
+
+    #[test]
+    fn every_rfc_example_comes_out_the_same() {
+        // (target, result) of each example in RFC 7386, appendix A
+        let examples = [
+            (json!({ "a": "b" }), json!({ "a": "c" })),
+            (json!({ "a": "b" }), json!({ "a": "b", "b": "c" })),
+            (json!({ "a": "b" }), json!({})),
+            (json!({ "a": "b", "b": "c" }), json!({ "b": "c" })),
+            (json!({ "a": ["b"] }), json!({ "a": "c" })),
+            (json!({ "a": "c" }), json!({ "a": ["b"] })),
+            (json!({ "a": { "b": "c" } }), json!({ "a": { "b": "d" } })),
+            (json!({ "a": [{ "b": "c" }] }), json!({ "a": [1] })),
+            (json!(["a", "b"]), json!(["c", "d"])),
+            (json!({ "a": "b" }), json!(["c"])),
+            (json!({ "a": "foo" }), json!(null)),
+            (json!({ "a": "foo" }), json!("bar")),
+            (json!({ "e": null }), json!({ "e": null, "a": 1 })),
+            (json!([1, 2]), json!({ "a": "b" })),
+            (json!({}), json!({ "a": { "bb": {} } })),
+        ];
+        for (old, new) in examples {
+            assert_eq!(apply(&old, &merge_patch(&old, &new)), new, "from {}", old);
+        }
+    }
+
+    #[test]
+    fn removed_fields_come_as_null() {
+        let old = json!({ "a": "b", "b": "c" });
+        assert_eq!(merge_patch(&old, &json!({ "b": "c" })), json!({ "a": null }));
+        assert_eq!(merge_patch(&old, &json!({})), json!({ "a": null, "b": null }));
+    }
+
+    #[test]
+    fn nested_objects_carry_only_what_changed() {
+        let old = json!({ "room": { "round": 1, "names": ["Ada", "Ben"], "timer": 30 }, "finished": false });
+        let new = json!({ "room": { "round": 2, "names": ["Ada", "Ben"] }, "finished": false });
+        let patch = merge_patch(&old, &new);
+        assert_eq!(patch, json!({ "room": { "round": 2, "timer": null } }));
+        assert_eq!(apply(&old, &patch), new);
+    }
+
+    #[test]
+    fn arrays_are_replaced_whole() {
+        let old = json!({ "names": ["Ada", "Ben"] });
+        let new = json!({ "names": ["Ada", "Ben", "Cy"] });
+        assert_eq!(merge_patch(&old, &new), new);
+    }
+
+    #[test]
+    fn the_same_view_is_an_empty_patch() {
+        let view = json!({ "room": { "round": 1 }, "finished": false });
+        assert_eq!(merge_patch(&view, &view), json!({}));
+    }
+}
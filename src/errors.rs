@@ -0,0 +1,67 @@
+use rocket::http::Status;
+use rocket::{Catcher, Request};
+use rocket_dyn_templates::{context, Template};
+
+// Error pages for browsers. Routes that fail with a bare `Status` land in a
+// catcher here; routes with something specific to say return their own
+// error type that renders `page` (see JoinError and CreateError in
+// routes.rs). The API has its own JSON envelope, see api.rs.
+
+// --- Pages ---
+pub fn page(status: Status, message: impl Into<String>) -> (Status, Template) {
+    let template = Template::render(
+        "error",
+        context! {
+            code: status.code,
+            title: title(status),
+            message: message.into(),
+        },
+    );
+    (status, template)
+}
+
+fn title(status: Status) -> &'static str {
+    match status.code {
+        400 | 422 => "That didn't look right",
+        403 => "That's not for you",
+        404 | 410 => "We couldn't find that",
+        429 => "Slow down a little",
+        507 => "This room is full",
+        500..=599 => "Something broke on our side",
+        _ => "Something went wrong",
+    }
+}
+
+// --- Catchers ---
+#[catch(400)]
+fn bad_request() -> (Status, Template) {
+    page(Status::BadRequest, "Something in that request was missing or wrong. Go back and give it another go.")
+}
+
+#[catch(404)]
+fn not_found(req: &Request<'_>) -> (Status, Template) {
+    page(Status::NotFound, format!("There's nothing at {}. The link may be old or mistyped.", req.uri().path()))
+}
+
+#[catch(500)]
+fn server_error() -> (Status, Template) {
+    page(Status::InternalServerError, "We hit a problem we didn't expect. Nothing you did — try again in a moment.")
+}
+
+#[catch(default)]
+fn other(status: Status, _req: &Request<'_>) -> (Status, Template) {
+    let message = match status.code {
+        403 => "You can't do that here. If you were invited, open the link you were sent.",
+        410 => "This is gone for good.",
+        422 => "Some of what you entered wasn't accepted. Go back and check the form.",
+        429 => "That's a lot of tries in a row. Wait a minute, then try again.",
+        507 => "This game can't hold any more answers. See how you did, or start a fresh room.",
+        _ => "Go back and try again, or start over from home.",
+    };
+    page(status, message)
+}
+
+// Registered under /; /api keeps its JSON catcher.
+pub fn catchers() -> Vec<Catcher> {
+    catchers![bad_request, not_found, server_error, other]
+}
@@ -0,0 +1,220 @@
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::scheduler::now_secs;
+
+// Per-room event log. Everything the result page itemizes, and everything
+// streamed live to open pages (GET /events/<code>), goes through here.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", tag = "type", rename_all = "snake_case")]
+pub enum RoomEvent {
+    PlayerJoined {
+        player_id: String,
+        player_name: String,
+    },
+    // who answered, never what; the answers come with the reveal
+    AnswerSubmitted {
+        player_id: String,
+        player_name: String,
+        question_index: usize,
+    },
+    PointsAdjusted {
+        player_id: String,
+        player_name: String,
+        delta: i32,
+        reason: String,
+    },
+    QuestionRevealed {
+        question_index: usize,
+        question_id: String,
+        answers: Vec<RevealedAnswer>,
+        matched: bool,
+        // partial credit for multi-blank questions, 100 = full match
+        #[serde(default)]
+        credit_pct: u32,
+        #[serde(default)]
+        wagers: Vec<WagerOutcome>,
+        #[serde(default)]
+        cue: Option<Cue>,
+    },
+    // partner went quiet mid-question; "waiting for them to reconnect"
+    TimerPaused {
+        player_id: String,
+        player_name: String,
+        remaining_secs: u64,
+    },
+    TimerResumed {
+        remaining_secs: u64,
+    },
+    // a player asked for more time on the current question
+    TimeExtended {
+        question_index: usize,
+        player_id: String,
+        player_name: String,
+        added_secs: u64,
+        remaining_secs: u64,
+    },
+    // the clock ran out; anyone who hadn't answered is marked timed out
+    // and the reveal follows
+    QuestionTimedOut {
+        question_index: usize,
+    },
+    LightningFinished {
+        matches: u32,
+        // paid to every player
+        bonus: u32,
+        #[serde(default)]
+        cue: Option<Cue>,
+    },
+    GameFinished {
+        score: u32,
+        cue: Option<Cue>,
+    },
+    // the host's surprise was unsealed on the result page
+    SurpriseRevealed {
+        has_text: bool,
+        has_photo: bool,
+    },
+    // too many wrong passphrases; nobody can join until `until`
+    JoinLocked {
+        failed_attempts: u32,
+        until: u64,
+    },
+    // approval rooms: someone is waiting for the host to let them in
+    JoinRequested {
+        player_id: String,
+        player_name: String,
+    },
+    JoinApproved {
+        player_id: String,
+        player_name: String,
+    },
+    JoinRejected {
+        player_id: String,
+        player_name: String,
+    },
+    // room was full; they're `position` in line (1 = next)
+    JoinQueued {
+        player_id: String,
+        player_name: String,
+        position: usize,
+    },
+    // first in line got the seat someone left
+    QueuePromoted {
+        player_id: String,
+        player_name: String,
+    },
+    PlayerLeft {
+        player_id: String,
+        player_name: String,
+    },
+    // the host took them out of the room or the line
+    PlayerKicked {
+        player_id: String,
+        player_name: String,
+    },
+    // the host started play with this many seated
+    GameStarted {
+        players: usize,
+    },
+    // the host ended the game early; GameFinished follows
+    RoomClosed,
+    // moved to another device; the old one is signed out
+    DeviceChanged {
+        player_id: String,
+        player_name: String,
+    },
+    // an emoji for the others, sent while `question_index` was current
+    // (so usually about the reveal before it)
+    Reacted {
+        player_id: String,
+        player_name: String,
+        question_index: usize,
+        emoji: String,
+    },
+}
+
+// Celebration hint for clients. Decided here so the web page, bots and
+// embeds all react to the same moments.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum Cue {
+    Confetti,
+    Heartbreak,
+    Drumroll,
+}
+
+// Streaks are counted in full matches, this reveal included.
+const STREAK_WORTH_MOURNING: u32 = 3;
+
+impl Cue {
+    pub fn for_reveal(credit: f64, streak_before: u32, wagers: &[WagerOutcome]) -> Option<Cue> {
+        let lost_bet = wagers.iter().any(|w| w.delta < 0);
+        if credit >= 1.0 {
+            Some(Cue::Confetti)
+        } else if lost_bet || (credit == 0.0 && streak_before >= STREAK_WORTH_MOURNING) {
+            Some(Cue::Heartbreak)
+        } else if credit >= 0.5 {
+            // so close
+            Some(Cue::Drumroll)
+        } else {
+            None
+        }
+    }
+
+    pub fn for_lightning(matches: u32) -> Option<Cue> {
+        match matches {
+            0 => Some(Cue::Heartbreak),
+            m if m >= 5 => Some(Cue::Confetti),
+            _ => None,
+        }
+    }
+
+    pub fn for_finish(score: u32) -> Option<Cue> {
+        match score {
+            85.. => Some(Cue::Confetti),
+            0..=30 => Some(Cue::Heartbreak),
+            _ => Some(Cue::Drumroll),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RevealedAnswer {
+    pub player_id: String,
+    pub player_name: String,
+    pub answer: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WagerOutcome {
+    pub player_id: String,
+    pub player_name: String,
+    pub stake: u32,
+    // +stake when the guess matched, -stake otherwise
+    pub delta: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LoggedEvent {
+    pub at: u64,
+    #[serde(flatten)]
+    pub event: RoomEvent,
+    // the play-page form that caused it, so the tab that sent it can tell
+    // its own echo from a change made elsewhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submission: Option<String>,
+}
+
+impl LoggedEvent {
+    pub fn now(event: RoomEvent) -> Self {
+        LoggedEvent {
+            at: now_secs(),
+            event,
+            submission: None,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use rand::seq::SliceRandom;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::http::{Cookie, Method, SameSite};
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::Deserialize;
+use rocket::time::Duration;
+use std::collections::BTreeMap;
+
+use crate::analytics::ExperimentStats;
+use crate::negotiate;
+
+// Simple A/B experiments. Each is a name and its variants in config; a
+// browser's first page view draws a variant per experiment and keeps it in
+// a cookie, so it sees the same one on every visit. Handlers read the
+// assignment with the `Variants` guard and report conversions to
+// `ExperimentStats`.
+
+const COOKIE_PREFIX: &str = "exp_";
+const COOKIE_DAYS: i64 = 90;
+
+// --- Models ---
+#[derive(Clone, Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Experiment {
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Experiments(Vec<Experiment>);
+
+impl Experiments {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        if figment.find_value("experiments").is_err() {
+            return Ok(Experiments::default());
+        }
+        let list: Vec<Experiment> = figment
+            .extract_inner("experiments")
+            .map_err(|e| format!("invalid `experiments` config: {}", e))?;
+        for (i, e) in list.iter().enumerate() {
+            if e.name.is_empty() || !e.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(format!("experiment name `{}` must be letters, digits and dashes", e.name));
+            }
+            if list[..i].iter().any(|o| o.name == e.name) {
+                return Err(format!("experiment `{}` is defined twice", e.name));
+            }
+            let mut variants = e.variants.clone();
+            variants.sort();
+            variants.dedup();
+            if variants.len() < 2 || variants.len() != e.variants.len() {
+                return Err(format!("experiment `{}` needs at least two distinct variants", e.name));
+            }
+        }
+        Ok(Experiments(list))
+    }
+
+    fn cookie_name(experiment: &Experiment) -> String {
+        format!("{}{}", COOKIE_PREFIX, experiment.name)
+    }
+}
+
+// Installs the experiments and assigns variants on page views.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Experiments", |rocket| async move {
+        let experiments = Experiments::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(experiments).attach(AdHoc::on_request("Experiment Assignment", |req, _| {
+            Box::pin(async move { assign(req) })
+        }))
+    })
+}
+
+// Only browsers loading a page get a variant; assets, APIs, health checks,
+// live event streams and bots asking for JSON are neither assigned nor
+// counted.
+fn assign(req: &mut Request<'_>) {
+    if req.method() != Method::Get || negotiate::wants_json(req) {
+        return;
+    }
+    let path = req.uri().path();
+    if ["/assets", "/public", "/api", "/admin", "/healthz", "/events"].iter().any(|p| path.starts_with(p)) {
+        return;
+    }
+    let (Some(experiments), Some(stats)) = (req.rocket().state::<Experiments>(), req.rocket().state::<ExperimentStats>())
+    else {
+        return;
+    };
+    for experiment in &experiments.0 {
+        let name = Experiments::cookie_name(experiment);
+        let current = req.cookies().get(&name).map(|c| c.value().to_string());
+        if current.is_some_and(|v| experiment.variants.contains(&v)) {
+            continue;
+        }
+        let Some(variant) = experiment.variants.choose(&mut rand::thread_rng()) else { continue };
+        let mut cookie = Cookie::new(name, variant.clone());
+        cookie.set_path("/");
+        cookie.set_same_site(SameSite::Lax);
+        cookie.set_max_age(Duration::days(COOKIE_DAYS));
+        req.cookies().add(cookie);
+        stats.exposed(&experiment.name, variant);
+    }
+}
+
+// --- Guard ---
+pub struct Variants {
+    // experiment -> variant for this browser; experiments it isn't in are absent
+    pub assigned: BTreeMap<String, String>,
+    stats: ExperimentStats,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Variants {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let mut assigned = BTreeMap::new();
+        if let Some(experiments) = req.rocket().state::<Experiments>() {
+            for experiment in &experiments.0 {
+                // pending: assigned by the fairing on this very request
+                let variant = req
+                    .cookies()
+                    .get_pending(&Experiments::cookie_name(experiment))
+                    .map(|c| c.value().to_string())
+                    .filter(|v| experiment.variants.contains(v));
+                if let Some(variant) = variant {
+                    assigned.insert(experiment.name.clone(), variant);
+                }
+            }
+        }
+        let stats = req.rocket().state::<ExperimentStats>().cloned().unwrap_or_default();
+        request::Outcome::Success(Variants { assigned, stats })
+    }
+}
+
+impl Variants {
+    // Counts `event` once for every experiment this browser is in.
+    pub fn convert(&self, event: &str) {
+        for (experiment, variant) in &self.assigned {
+            self.stats.converted(experiment, variant, event);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use rocket::http::{ContentType, Header};
+use rocket::serde::{Deserialize, Serialize};
+
+// Full game transcript for download: every question, every answer, scores
+// and timings. Built by the result route, rendered here as JSON or CSV.
+
+// --- Models ---
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Transcript {
+    pub code: String,
+    pub score: u32,
+    // unix seconds; absent while playing and in exports from older versions
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    // classic, guess, story, coop or endless; absent in older exports
+    #[serde(default)]
+    pub mode: Option<String>,
+    pub players: Vec<TranscriptPlayer>,
+    pub questions: Vec<TranscriptQuestion>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TranscriptPlayer {
+    pub id: String,
+    pub name: String,
+    pub score: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TranscriptQuestion {
+    pub index: usize,
+    pub question_id: String,
+    pub question: String,
+    pub category: String,
+    // None until both players have answered
+    pub credit_pct: Option<u32>,
+    pub answers: Vec<TranscriptAnswer>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TranscriptAnswer {
+    pub player_id: String,
+    pub player_name: String,
+    pub answer: String,
+    pub wager: u32,
+    // seconds from the question being asked to this answer
+    pub secs: Option<u64>,
+}
+
+// --- Formats ---
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Option<Format> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+const CSV_HEADER: &str = "index,question_id,category,question,player,answer,wager,seconds,credit_pct";
+
+impl Transcript {
+    // One row per question and player.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(CSV_HEADER);
+        out.push('\n');
+        for q in &self.questions {
+            for a in &q.answers {
+                let row = [
+                    q.index.to_string(),
+                    csv_field(&q.question_id),
+                    csv_field(&q.category),
+                    csv_field(&q.question),
+                    csv_field(&a.player_name),
+                    csv_field(&a.answer),
+                    a.wager.to_string(),
+                    a.secs.map(|s| s.to_string()).unwrap_or_default(),
+                    q.credit_pct.map(|c| c.to_string()).unwrap_or_default(),
+                ];
+                out.push_str(&row.join(","));
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    pub fn download(&self, format: Format) -> Download {
+        let (content_type, ext, body) = match format {
+            Format::Json => (
+                ContentType::JSON,
+                "json",
+                rocket::serde::json::to_pretty_string(self).unwrap_or_default(),
+            ),
+            Format::Csv => (ContentType::CSV, "csv", self.to_csv()),
+        };
+        Download {
+            body: (content_type, body),
+            disposition: Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"now-{}.{}\"", self.code, ext),
+            ),
+        }
+    }
+}
+
+// Quote only when needed (RFC 4180): commas, quotes, newlines.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// --- Responder ---
+#[derive(Responder)]
+pub struct Download {
+    body: (ContentType, String),
+    disposition: Header<'static>,
+}
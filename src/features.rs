@@ -0,0 +1,127 @@
+use parking_lot::RwLock;
+use rocket::data::ByteUnit;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::serde::Serialize;
+
+// What this deployment turns on and how much it keeps around, decided in one
+// place. `low_memory = true` is the profile for a tiny VPS: no QR images, no
+// surprise photos (each one sits in memory with its room), shorter buffers
+// and caches, and a cap on open rooms. (The QR images are the only thing we
+// render server-side; exports are plain JSON and CSV.) Any one setting can
+// still be given on its own (`max_rooms = 50`, `qr_codes = true`) on top of
+// either profile.
+//
+// Routes read the managed copy; room internals that have no request to hand
+// (event buffers, the view history) read `current()`.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Features {
+    pub low_memory: bool,
+    // PNG QR codes on the invite page; the link is always there
+    pub qr_codes: bool,
+    pub surprise_photos: bool,
+    // events a slow /events listener may fall behind by before skipping ahead
+    pub live_buffer: usize,
+    // room views kept per viewer, and viewers kept, for `?since=` polling
+    pub view_versions: usize,
+    pub view_viewers: usize,
+    // open rooms at once; None = no cap
+    pub max_rooms: Option<usize>,
+    // what one room may hold (see footprint.rs) before it takes no more answers
+    pub room_max_bytes: usize,
+}
+
+impl Features {
+    pub const STANDARD: Features = Features {
+        low_memory: false,
+        qr_codes: true,
+        surprise_photos: true,
+        live_buffer: 64,
+        view_versions: 8,
+        view_viewers: 10_000,
+        max_rooms: None,
+        // a surprise photo (2 MiB) and a long endless game
+        room_max_bytes: 8 << 20,
+    };
+
+    pub const LOW_MEMORY: Features = Features {
+        low_memory: true,
+        qr_codes: false,
+        surprise_photos: false,
+        live_buffer: 16,
+        view_versions: 2,
+        view_viewers: 500,
+        max_rooms: Some(100),
+        room_max_bytes: 512 << 10,
+    };
+
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let low_memory = optional::<bool>(figment, "low_memory")?.unwrap_or(false);
+        let mut features = if low_memory { Features::LOW_MEMORY } else { Features::STANDARD };
+        if let Some(on) = optional(figment, "qr_codes")? {
+            features.qr_codes = on;
+        }
+        if let Some(on) = optional(figment, "surprise_photos")? {
+            features.surprise_photos = on;
+        }
+        if let Some(n) = optional::<usize>(figment, "live_buffer")? {
+            if n == 0 {
+                return Err("live_buffer must be at least 1".to_string());
+            }
+            features.live_buffer = n;
+        }
+        if let Some(n) = optional::<usize>(figment, "max_rooms")? {
+            // 0 lifts the cap, e.g. to undo the low-memory default
+            features.max_rooms = (n > 0).then_some(n);
+        }
+        if let Some(cap) = optional::<ByteUnit>(figment, "room_max_bytes")? {
+            features.room_max_bytes = cap.as_u64() as usize;
+        }
+        Ok(features)
+    }
+
+    // Whether another room fits next to `open` ones.
+    pub fn room_fits(&self, open: usize) -> bool {
+        self.max_rooms.is_none_or(|max| open < max)
+    }
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Features::STANDARD
+    }
+}
+
+fn optional<T: rocket::serde::de::DeserializeOwned>(figment: &Figment, key: &str) -> Result<Option<T>, String> {
+    if figment.find_value(key).is_err() {
+        return Ok(None);
+    }
+    figment
+        .extract_inner(key)
+        .map(Some)
+        .map_err(|e| format!("invalid `{}` config: {}", key, e))
+}
+
+static CURRENT: RwLock<Features> = RwLock::new(Features::STANDARD);
+
+pub fn current() -> Features {
+    *CURRENT.read()
+}
+
+// Loads the settings at ignite, manages them and makes them `current()`.
+// A bad value is reported by the startup checks; this keeps the defaults.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Features", |rocket| async {
+        let features = Features::load(rocket.figment()).unwrap_or_default();
+        *CURRENT.write() = features;
+        if features.low_memory {
+            match features.max_rooms {
+                Some(max) => tracing::info!("features: low-memory mode, up to {} rooms", max),
+                None => tracing::info!("features: low-memory mode"),
+            }
+        }
+        rocket.manage(features)
+    })
+}
@@ -0,0 +1,45 @@
+use rocket::serde::json;
+use rocket::serde::Serialize;
+
+// Roughly how many bytes a room keeps in memory, for the admin dashboard and
+// the per-room cap (`room_max_bytes`, see features.rs). Not exact: a fixed
+// cost per player, answer and event, plus the text and media they carry,
+// which is what actually grows. Rooms recount on every event they log.
+
+// a String or Vec header
+pub const HEADER: usize = 24;
+// a player, answer or map entry before its text
+pub const ENTRY: usize = 64;
+
+pub fn text(s: &str) -> usize {
+    HEADER + s.len()
+}
+
+pub fn texts<'a>(items: impl IntoIterator<Item = &'a String>) -> usize {
+    items.into_iter().map(|s| text(s)).sum()
+}
+
+// For nested values with text all over (events, dealt questions): their
+// size as JSON, which tracks what they hold closely enough.
+pub fn json_len<T: Serialize>(value: &T) -> usize {
+    ENTRY + json::to_string(value).map(|s| s.len()).unwrap_or_default()
+}
+
+// Running total over an append-only list, so each item is measured once.
+#[derive(Clone, Debug, Default)]
+pub struct ListSize {
+    counted: usize,
+    bytes: usize,
+}
+
+impl ListSize {
+    pub fn update<T: Serialize>(&mut self, items: &[T]) -> usize {
+        // shrunk (trimmed or replaced): start over
+        if items.len() < self.counted {
+            *self = ListSize::default();
+        }
+        self.bytes += items[self.counted..].iter().map(json_len).sum::<usize>();
+        self.counted = items.len();
+        self.bytes
+    }
+}
@@ -0,0 +1,143 @@
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
+use async_graphql::{Context, EmptyMutation, Json, Object, Result, Schema, SimpleObject, Subscription};
+use rocket::futures::Stream;
+use rocket::response::content::RawHtml;
+use rocket::serde::json::{self, Value};
+use rocket::tokio::sync::broadcast::{self, error::RecvError};
+use std::sync::Arc;
+
+use crate::events::LoggedEvent;
+
+// Read-only GraphQL over the rooms, for dashboards that want to pick their
+// own fields: POST /graphql for queries, POST /graphql/stream for
+// subscriptions (as server-sent events), GET /graphql for the playground.
+// Rooms are looked up by code, as everywhere else; only results their
+// hosts made public can be listed.
+
+pub type GameSchema = Schema<Query, EmptyMutation, Subscription>;
+
+pub fn schema() -> GameSchema {
+    Schema::build(Query, EmptyMutation, Subscription).finish()
+}
+
+// What the schema reads from, handed in with each request; see
+// routes::GameService.
+pub trait Games: Send + Sync {
+    fn room(&self, code: &str) -> Option<RoomView>;
+    fn public_results(&self) -> Vec<RoomView>;
+    fn subscribe(&self, code: &str) -> Option<broadcast::Receiver<LoggedEvent>>;
+}
+
+pub type GamesData = Arc<dyn Games>;
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct RoomView {
+    pub code: String,
+    // couple, friends or party
+    pub game_mode: String,
+    pub capacity: u32,
+    pub players: Vec<PlayerView>,
+    pub finished: bool,
+    // None for endless rooms
+    pub total: Option<u32>,
+    // what's being asked now; None once the game is over
+    pub question: Option<QuestionView>,
+    // None until the game is over
+    pub result: Option<ResultView>,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct PlayerView {
+    pub name: String,
+    pub score: u32,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct QuestionView {
+    pub number: u32,
+    pub text: String,
+    pub category: String,
+    // free_text, ranking, picture_choice, multiple_choice or scale
+    pub kind: String,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ResultView {
+    pub score: u32,
+    // low, medium, high or perfect
+    pub level: String,
+    pub verdict: String,
+    pub answered: u32,
+    pub questions: Vec<QuestionResultView>,
+}
+
+#[derive(SimpleObject, Clone, Debug)]
+pub struct QuestionResultView {
+    pub number: u32,
+    // None if it has since left the bank
+    pub question: Option<String>,
+    pub category: Option<String>,
+    pub credit_pct: u32,
+    pub level: String,
+    pub matched: bool,
+}
+
+// One entry of the room's event log, as GET /events streams it.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct EventView {
+    // the event's `type`, e.g. question_revealed
+    pub kind: String,
+    pub at: u64,
+    // the whole event, fields as in the JSON stream
+    pub data: Json<Value>,
+}
+
+impl From<LoggedEvent> for EventView {
+    fn from(event: LoggedEvent) -> Self {
+        let data = json::to_value(&event).unwrap_or(Value::Null);
+        EventView {
+            kind: data["type"].as_str().unwrap_or_default().to_string(),
+            at: event.at,
+            data: Json(data),
+        }
+    }
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    async fn room(&self, ctx: &Context<'_>, code: String) -> Result<Option<RoomView>> {
+        Ok(ctx.data::<GamesData>()?.room(&code))
+    }
+
+    // Finished games their hosts chose to make public.
+    async fn public_results(&self, ctx: &Context<'_>) -> Result<Vec<RoomView>> {
+        Ok(ctx.data::<GamesData>()?.public_results())
+    }
+}
+
+pub struct Subscription;
+
+#[Subscription]
+impl Subscription {
+    // Everything the room logs from now on. Ends when the room goes away.
+    async fn room_events(&self, ctx: &Context<'_>, code: String) -> Result<impl Stream<Item = EventView>> {
+        let rx = ctx.data::<GamesData>()?.subscribe(&code).ok_or("no room with that code")?;
+        Ok(rocket::futures::stream::unfold(rx, |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => return Some((EventView::from(event), rx)),
+                    // fell behind; skip ahead like the event stream does
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }))
+    }
+}
+
+#[get("/graphql")]
+pub fn playground() -> RawHtml<String> {
+    RawHtml(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
+}
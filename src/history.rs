@@ -0,0 +1,120 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::http::CookieJar;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+use rocket_dyn_templates::{context, Template};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::identity;
+use crate::jsonfile;
+use crate::sealed::CookieKeys;
+use crate::surprise;
+
+// One line per finished game per player, kept apart from the rooms so it
+// outlives them: /history lists the games this browser played (see
+// identity::played). Written by the "summaries" job when a game ends and
+// persisted like the snapshots, to `history_path`.
+
+// --- Models ---
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GameSummary {
+    pub code: String,
+    pub finished_at: u64,
+    pub score: u32,
+    // everyone else in the room, by name
+    pub partners: Vec<String>,
+    // as in transcripts: classic, coop, guess, endless or story
+    pub mode: String,
+}
+
+// --- Store ---
+#[derive(Default)]
+struct HistoryInner {
+    path: Option<PathBuf>,
+    // player id -> their games, oldest first
+    games: HashMap<String, Vec<GameSummary>>,
+}
+
+#[derive(Clone, Default)]
+pub struct GameHistory {
+    inner: Arc<RwLock<HistoryInner>>,
+}
+
+impl GameHistory {
+    pub fn record(&self, player_id: &str, summary: GameSummary) {
+        let mut inner = self.inner.write();
+        let games = inner.games.entry(player_id.to_string()).or_default();
+        // a game is summed up once, but a restart may replay the job
+        if games.iter().any(|g| g.code == summary.code) {
+            return;
+        }
+        games.push(summary);
+        persist(&inner);
+    }
+
+    // The games of any of these players, newest first.
+    pub fn for_players(&self, ids: &[String]) -> Vec<GameSummary> {
+        let inner = self.inner.read();
+        let mut games: Vec<GameSummary> = ids
+            .iter()
+            .filter_map(|id| inner.games.get(id))
+            .flatten()
+            .cloned()
+            .collect();
+        games.sort_by(|a, b| b.finished_at.cmp(&a.finished_at));
+        games.dedup_by(|a, b| a.code == b.code);
+        games
+    }
+
+    // Reads `history_path` and reloads what was saved there.
+    pub fn fairing(&self) -> AdHoc {
+        let store = self.clone();
+        AdHoc::on_ignite("Game History", |rocket| async move {
+            let path: Option<PathBuf> = rocket.figment().extract_inner("history_path").ok();
+            let mut inner = store.inner.write();
+            if let Some(path) = &path {
+                match jsonfile::read(path) {
+                    Ok(Some(games)) => inner.games = games,
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("history: ignoring {}", e),
+                }
+            }
+            inner.path = path;
+            drop(inner);
+            rocket
+        })
+    }
+}
+
+fn persist(inner: &HistoryInner) {
+    let Some(path) = &inner.path else { return };
+    if let Err(e) = jsonfile::write(path, &inner.games) {
+        tracing::error!("history: {}", e);
+    }
+}
+
+// --- Routes ---
+pub fn routes() -> Vec<rocket::Route> {
+    routes![history_get]
+}
+
+#[get("/history")]
+fn history_get(cookies: &CookieJar<'_>, keys: &State<CookieKeys>, history: &State<GameHistory>) -> Template {
+    let games: Vec<_> = history
+        .for_players(&identity::played(cookies, keys))
+        .into_iter()
+        .map(|g| {
+            context! {
+                date: surprise::format_day(g.finished_at / 86_400),
+                partners: g.partners.join(", "),
+                score: g.score,
+                code: g.code,
+            }
+        })
+        .collect();
+    Template::render("history", context! { games })
+}
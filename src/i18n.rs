@@ -0,0 +1,57 @@
+use rocket::request::{self, FromRequest, Request};
+
+// Locale resolution. Each player can pick their own language, so this runs
+// per request for whoever is viewing, never once per room: the player's own
+// choice first, then the browser's Accept-Language, then English. Question
+// ids stay canonical; only the text shown is localized.
+
+pub const DEFAULT_LOCALE: &str = "en";
+pub const SUPPORTED: &[(&str, &str)] = &[("en", "English"), ("fr", "Français"), ("es", "Español")];
+
+pub fn is_supported(locale: &str) -> bool {
+    SUPPORTED.iter().any(|(code, _)| *code == locale)
+}
+
+// Normalizes a user-supplied choice ("FR", "fr-CA") to a supported locale.
+pub fn normalize(locale: &str) -> Option<String> {
+    let primary = locale.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    is_supported(&primary).then_some(primary)
+}
+
+// --- Guard ---
+// Supported languages from Accept-Language, most preferred first.
+pub struct AcceptLanguage(Vec<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AcceptLanguage {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let mut ranked: Vec<(f32, String)> = req
+            .headers()
+            .get_one("Accept-Language")
+            .unwrap_or("")
+            .split(',')
+            .filter_map(|part| {
+                let mut bits = part.split(';');
+                let locale = normalize(bits.next()?)?;
+                let q = bits
+                    .find_map(|b| b.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                Some((q, locale))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        request::Outcome::Success(AcceptLanguage(ranked.into_iter().map(|(_, l)| l).collect()))
+    }
+}
+
+impl AcceptLanguage {
+    pub fn resolve(&self, preferred: Option<&str>) -> String {
+        preferred
+            .and_then(normalize)
+            .or_else(|| self.0.first().cloned())
+            .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+    }
+}
@@ -0,0 +1,158 @@
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::time::Duration;
+use uuid::Uuid;
+
+use crate::sealed::{CookieKeys, Verified};
+
+// Which player this browser is, per room. Creating or joining a room leaves
+// a sealed cookie with the player's id, so the play page knows who's looking
+// and answers can only come from the browser that joined. The cookie names
+// its room and the sealed value repeats the code, so it can't be carried
+// over to another room. It also carries the player's session, which a
+// device handoff replaces, signing the old device out.
+
+const COOKIE_PREFIX: &str = "player_";
+const COOKIE_DAYS: i64 = 30;
+// transfer codes: long enough not to guess within their lifetime
+const HANDOFF_CODE_LEN: usize = 8;
+pub const HANDOFF_TTL_SECS: u64 = 10 * 60;
+
+fn cookie_name(code: &str) -> String {
+    format!("{}{}", COOKIE_PREFIX, code)
+}
+
+fn player_cookie(code: &str, value: String) -> Cookie<'static> {
+    let mut cookie = Cookie::new(cookie_name(code), value);
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_path("/");
+    cookie.set_max_age(Duration::days(COOKIE_DAYS));
+    cookie
+}
+
+pub fn new_session() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+// One-off code for "continue on my phone".
+pub fn handoff_code() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(HANDOFF_CODE_LEN)
+        .map(char::from)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+// Signs this browser in as `player_id` for room `code`. `session` is the
+// player's current one; None for players no device has claimed yet.
+pub fn remember(cookies: &CookieJar<'_>, keys: &CookieKeys, code: &str, player_id: &str, session: Option<&str>) {
+    let sealed = keys.seal(&format!("{}:{}:{}", code, player_id, session.unwrap_or_default()));
+    cookies.add(player_cookie(code, sealed));
+    note_played(cookies, keys, player_id);
+}
+
+// --- Game history ---
+// Room cookies go after COOKIE_DAYS, so the players this browser has been
+// are also kept in one long-lived `played` cookie, newest first, for
+// /history to look their finished games up by.
+
+const PLAYED_COOKIE: &str = "played";
+const PLAYED_DAYS: i64 = 365;
+// uuids, sealed and hex-encoded, still under browsers' 4 KB
+const PLAYED_MAX: usize = 40;
+
+fn played_cookie(value: String) -> Cookie<'static> {
+    let mut cookie = Cookie::new(PLAYED_COOKIE, value);
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_path("/");
+    cookie.set_max_age(Duration::days(PLAYED_DAYS));
+    cookie
+}
+
+fn note_played(cookies: &CookieJar<'_>, keys: &CookieKeys, player_id: &str) {
+    let mut ids = played(cookies, keys);
+    if ids.first().is_some_and(|id| id == player_id) {
+        return;
+    }
+    ids.retain(|id| id != player_id);
+    ids.insert(0, player_id.to_string());
+    ids.truncate(PLAYED_MAX);
+    cookies.add(played_cookie(keys.seal(&ids.join(","))));
+}
+
+// Every player id this browser has played as, newest first: the `played`
+// list plus any room cookie from before it existed.
+pub fn played(cookies: &CookieJar<'_>, keys: &CookieKeys) -> Vec<String> {
+    let mut ids: Vec<String> = match cookies.get(PLAYED_COOKIE).and_then(|c| keys.open(c.value())) {
+        Some(Verified::Current(value) | Verified::Previous(value)) => {
+            value.split(',').filter(|id| !id.is_empty()).map(str::to_string).collect()
+        }
+        None => Vec::new(),
+    };
+    let rooms: Vec<String> = cookies
+        .iter()
+        .filter_map(|c| c.name().strip_prefix(COOKIE_PREFIX).map(str::to_string))
+        .collect();
+    for code in rooms {
+        if let Some(me) = recall(cookies, keys, &code).filter(|me| !ids.contains(&me.id)) {
+            ids.push(me.id);
+        }
+    }
+    ids
+}
+
+// The player this browser joined `code` as, if any. Whether the session is
+// still theirs is up to the room (see Room::bound).
+pub fn recall(cookies: &CookieJar<'_>, keys: &CookieKeys, code: &str) -> Option<CurrentPlayer> {
+    let cookie = cookies.get(&cookie_name(code))?;
+    let value = match keys.open(cookie.value())? {
+        Verified::Current(value) => value,
+        Verified::Previous(value) => {
+            // sealed before a key rotation: move it onto the new key
+            cookies.add(player_cookie(code, keys.seal(&value)));
+            value
+        }
+    };
+    let (room, rest) = value.split_once(':')?;
+    if room != code {
+        return None;
+    }
+    // cookies from before sessions carry just the id
+    let (id, session) = rest.split_once(':').unwrap_or((rest, ""));
+    Some(CurrentPlayer {
+        id: id.to_string(),
+        session: session.to_string(),
+    })
+}
+
+// --- Guard ---
+// The player this browser joined the room in the path (`/<any>/<code>/..`)
+// as. Forbidden without a valid cookie for that room; membership itself is
+// checked by the route, which has the room.
+pub struct CurrentPlayer {
+    pub id: String,
+    // empty for unclaimed players
+    pub session: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CurrentPlayer {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let keys = req.rocket().state::<CookieKeys>();
+        let current = req
+            .routed_segment(1)
+            .zip(keys)
+            .and_then(|(code, keys)| recall(req.cookies(), keys, code));
+        match current {
+            Some(current) => request::Outcome::Success(current),
+            None => request::Outcome::Error((Status::Forbidden, ())),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use image::{ImageFormat, Luma};
+use qrcode::QrCode;
+use rocket::http::uri::Host;
+use std::io::Cursor;
+
+use crate::site::Site;
+
+// Invites a partner can scan off the host's screen instead of typing the
+// code: /invite/<code> shows the link and its QR code, /invite/<code>/qr.png
+// is the image on its own.
+
+// smallest side of the QR image, in pixels
+const QR_SIZE: u32 = 240;
+
+// Where a scan should land. `public_url` when the deployment has one,
+// otherwise whichever host this request came in on.
+pub fn join_url(site: &Site, host: Option<&Host<'_>>, code: &str) -> Option<String> {
+    let path = format!("/join?code={}", code);
+    site.absolute(&path).or_else(|| {
+        let host = host?.to_string();
+        let local = host.starts_with("localhost") || host.starts_with("127.0.0.1");
+        let scheme = if local { "http" } else { "https" };
+        Some(format!("{}://{}{}", scheme, host, path))
+    })
+}
+
+pub fn qr_png(url: &str) -> Option<Vec<u8>> {
+    let image = QrCode::new(url.as_bytes())
+        .ok()?
+        .render::<Luma<u8>>()
+        .min_dimensions(QR_SIZE, QR_SIZE)
+        .build();
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).ok()?;
+    Some(png)
+}
@@ -0,0 +1,31 @@
+use rocket::serde::de::DeserializeOwned;
+use rocket::serde::Serialize;
+use std::path::Path;
+
+// The JSON files the stores keep their state in (`*_path` settings): read
+// once at launch, rewritten whole after every change.
+
+// What's saved at `path`; None when nothing was saved there yet.
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let raw = match std::fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("can't read {}: {}", path.display(), e)),
+    };
+    rocket::serde::json::from_str(&raw)
+        .map(Some)
+        .map_err(|e| format!("{} is malformed: {}", path.display(), e))
+}
+
+// Replaces what's saved at `path`, making its folder if needed.
+pub fn write<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let raw = rocket::serde::json::to_string(value).map_err(|e| format!("can't serialize {}: {}", path.display(), e))?;
+    // write-then-rename so a crash never leaves half a file
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, raw)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| format!("can't write {}: {}", path.display(), e))
+}
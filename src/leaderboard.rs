@@ -0,0 +1,137 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::scheduler::now_secs;
+use crate::scoring::{self, Normalizer};
+use crate::snapshots::{self, Snapshot, SnapshotStore};
+
+// Best public games, ranked by rating rather than raw score so a short
+// classic game and a long guess-mode one can share a table; the method is
+// in scoring.rs and repeated in every response. Each couple appears once,
+// with their best game. Rebuilt by a batch job like the word cloud.
+
+const MAX_ENTRIES: usize = 50;
+
+// --- Models ---
+#[derive(Debug, Default, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Leaderboard {
+    pub entries: Vec<Entry>,
+    // public games looked at
+    pub games: usize,
+    pub method: Method,
+    pub built_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Entry {
+    pub rank: usize,
+    pub code: String,
+    pub players: Vec<String>,
+    pub mode: String,
+    pub questions: usize,
+    pub score: u32,
+    // 0-100, one decimal
+    pub rating: f64,
+    // what the game was measured against, e.g. "guess/short", or "all"
+    pub group: String,
+    pub finished_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Method {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub min_group_games: usize,
+}
+
+impl Default for Method {
+    fn default() -> Self {
+        Method {
+            name: scoring::NORMALIZATION_METHOD,
+            description: scoring::NORMALIZATION_DETAIL,
+            min_group_games: scoring::MIN_GROUP_GAMES,
+        }
+    }
+}
+
+// --- Building ---
+pub fn build(games: &[Snapshot]) -> Leaderboard {
+    let normalizer = Normalizer::fit(games.iter().map(|s| (mode(s), s.transcript.questions.len(), s.transcript.score)));
+
+    let mut best: HashMap<Vec<&str>, Entry> = HashMap::new();
+    for game in games {
+        let t = &game.transcript;
+        let (group, rating) = normalizer.rate(mode(game), t.questions.len(), t.score);
+        let entry = Entry {
+            rank: 0,
+            code: t.code.clone(),
+            players: t.players.iter().map(|p| p.name.clone()).collect(),
+            mode: mode(game).to_string(),
+            questions: t.questions.len(),
+            score: t.score,
+            rating: (rating * 10.0).round() / 10.0,
+            group,
+            finished_at: t.finished_at,
+        };
+        let couple = snapshots::couple(t);
+        if best.get(&couple).is_none_or(|b| b.rating < entry.rating) {
+            best.insert(couple, entry);
+        }
+    }
+
+    let mut entries: Vec<Entry> = best.into_values().collect();
+    entries.sort_by(|a, b| b.rating.total_cmp(&a.rating).then_with(|| a.code.cmp(&b.code)));
+    entries.truncate(MAX_ENTRIES);
+    for (i, e) in entries.iter_mut().enumerate() {
+        e.rank = i + 1;
+    }
+    Leaderboard {
+        entries,
+        games: games.len(),
+        method: Method::default(),
+        built_at: Some(now_secs()),
+    }
+}
+
+// Games from before modes were recorded were all played the classic way.
+fn mode(s: &Snapshot) -> &str {
+    s.transcript.mode.as_deref().unwrap_or("classic")
+}
+
+// --- Service ---
+#[derive(Clone)]
+pub struct LeaderboardService {
+    snapshots: SnapshotStore,
+    current: Arc<RwLock<Arc<Leaderboard>>>,
+}
+
+impl LeaderboardService {
+    pub fn new(snapshots: SnapshotStore) -> Self {
+        LeaderboardService {
+            snapshots,
+            current: Arc::default(),
+        }
+    }
+
+    pub fn current(&self) -> Arc<Leaderboard> {
+        self.current.read().clone()
+    }
+
+    // Re-rates every public game. Run by the scheduler.
+    pub fn refresh(&self) {
+        *self.current.write() = Arc::new(build(&self.snapshots.public()));
+    }
+
+    pub fn fairing(&self) -> AdHoc {
+        let service = self.clone();
+        AdHoc::on_liftoff("Leaderboard", move |_| {
+            Box::pin(async move { service.refresh() })
+        })
+    }
+}
@@ -0,0 +1,83 @@
+#[macro_use] extern crate rocket;
+
+mod admin;
+mod analytics;
+mod announcements;
+mod api;
+mod assets;
+mod audit;
+mod calibration;
+mod checks;
+mod codes;
+mod conditional;
+mod content;
+mod custom;
+mod dashboard;
+mod delta;
+mod errors;
+mod events;
+mod experiments;
+mod export;
+mod features;
+mod footprint;
+mod graphql;
+mod history;
+mod i18n;
+mod identity;
+mod invite;
+mod jsonfile;
+mod leaderboard;
+mod lightning;
+mod limits;
+mod negotiate;
+mod notify;
+mod outbox;
+mod presets;
+mod proto;
+mod questions;
+mod ratelimit;
+mod recommend;
+mod referrals;
+mod retention;
+mod review;
+mod routes;
+mod scheduler;
+mod sealed;
+mod sensitive;
+mod series;
+mod site;
+mod snapshots;
+mod storage;
+mod scoring;
+mod story;
+mod surprise;
+mod telemetry;
+mod tenants;
+mod timer;
+mod translate;
+mod verdicts;
+mod wordcloud;
+
+use rocket::fs::{FileServer, Options};
+use rocket::{Build, Rocket};
+use routes::build_rocket;
+
+pub use checks::self_test;
+pub use telemetry::init as init_telemetry;
+
+pub fn rocket() -> Rocket<Build> {
+    // Attach templates, mount routes, serve static if needed later.
+    // A missing folder is reported by the startup checks rather than panicking here.
+    build_rocket().mount(
+        "/public",
+        FileServer::new(checks::PUBLIC_DIR, Options::Index | Options::Missing),
+    )
+}
+
+// Room `code` as JSON with what the game's moves changed (players by seat,
+// answers, logged events) and none of the ids, or None if there's no such
+// room. Only built for tests/contract.rs.
+#[cfg(any(test, feature = "contract"))]
+pub fn transitions(rocket: &Rocket<rocket::Orbit>, code: &str) -> Option<rocket::serde::json::Value> {
+    rocket.state::<routes::AppState>()?.transitions(code)
+}
@@ -0,0 +1,106 @@
+use rand::seq::SliceRandom;
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// 60-second this-or-that round played after the last regular question.
+// Each player races through the same list of pairs at their own pace; every
+// pair both picked the same side of earns bonus points.
+
+pub const DURATION_SECS: u64 = 60;
+pub const BONUS_PER_MATCH: u32 = 5;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LightningRound {
+    pub pairs: Vec<(String, String)>,
+    pub ends_at: u64,
+    // player id -> picks in pair order (0 = this, 1 = that)
+    pub picks: HashMap<String, Vec<u8>>,
+    pub finished: bool,
+}
+
+impl LightningRound {
+    pub fn start(now: u64) -> Self {
+        let mut pairs: Vec<(String, String)> = PAIRS
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect();
+        pairs.shuffle(&mut rand::thread_rng());
+        LightningRound {
+            pairs,
+            ends_at: now + DURATION_SECS,
+            picks: HashMap::new(),
+            finished: false,
+        }
+    }
+
+    pub fn is_over(&self, now: u64) -> bool {
+        self.finished || now >= self.ends_at
+    }
+
+    pub fn remaining(&self, now: u64) -> u64 {
+        self.ends_at.saturating_sub(now)
+    }
+
+    // The pair this player is on, None once they've run out.
+    pub fn next_pair(&self, player_id: &str) -> Option<(usize, &(String, String))> {
+        let done = self.picks.get(player_id).map(Vec::len).unwrap_or(0);
+        self.pairs.get(done).map(|p| (done, p))
+    }
+
+    // Records a pick for the player's current pair. `index` guards against a
+    // stale double-submit landing on the next pair.
+    pub fn pick(&mut self, player_id: &str, index: usize, choice: u8, now: u64) -> bool {
+        if self.is_over(now) || choice > 1 {
+            return false;
+        }
+        let picks = self.picks.entry(player_id.to_string()).or_default();
+        if picks.len() != index || index >= self.pairs.len() {
+            return false;
+        }
+        picks.push(choice);
+        true
+    }
+
+    // Pairs where every player picked the same side.
+    pub fn matches(&self, player_ids: &[&str]) -> u32 {
+        (0..self.pairs.len())
+            .filter(|&i| {
+                let mut choices = player_ids
+                    .iter()
+                    .map(|id| self.picks.get(*id).and_then(|p| p.get(i)));
+                match choices.next() {
+                    Some(Some(first)) => choices.all(|c| c == Some(first)),
+                    _ => false,
+                }
+            })
+            .count() as u32
+    }
+}
+
+const PAIRS: &[(&str, &str)] = &[
+    ("Coffee", "Tea"),
+    ("Beach", "Mountains"),
+    ("Sunrise", "Sunset"),
+    ("Pizza", "Burgers"),
+    ("Netflix", "Night out"),
+    ("Text", "Call"),
+    ("Cats", "Dogs"),
+    ("Sweet", "Savory"),
+    ("Summer", "Winter"),
+    ("Early bird", "Night owl"),
+    ("Road trip", "Flight"),
+    ("Books", "Movies"),
+    ("Cook", "Order in"),
+    ("Big party", "Quiet dinner"),
+    ("Hugs", "Kisses"),
+    ("Rain", "Sunshine"),
+    ("City", "Countryside"),
+    ("Chocolate", "Vanilla"),
+    ("Plan ahead", "Go with the flow"),
+    ("Dance", "Sing"),
+    ("Breakfast", "Dinner"),
+    ("Spicy", "Mild"),
+    ("Museum", "Theme park"),
+    ("Handwritten note", "Surprise gift"),
+];
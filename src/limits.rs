@@ -0,0 +1,84 @@
+use rocket::data::ByteUnit;
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+use rocket::Catcher;
+use rocket_dyn_templates::{context, Template};
+use std::marker::PhantomData;
+
+use crate::api::ApiError;
+use crate::surprise;
+
+// Body size caps. `[default.limits]` in Rocket.toml sets the ceiling per kind
+// of body ("form", "data-form", "file", "json"); Rocket stops reading there.
+// Routes that should take far less than their kind's ceiling add a
+// `BodyCap<..>` guard, which reads `limits.<kind>/<name>` and turns away a
+// declared Content-Length over it before the body is touched. Either way the
+// client gets a 413: a page for browsers, the API envelope under /api.
+
+// --- Guard ---
+pub trait Cap {
+    const KIND: &'static str;
+    const NAME: &'static str;
+}
+
+pub struct BodyCap<C>(PhantomData<C>);
+
+pub mod cap {
+    use super::Cap;
+
+    // admin bulk calls: a list of ids and tags
+    pub struct Batch;
+    // admin result import: whole transcripts
+    pub struct Import;
+
+    impl Cap for Batch {
+        const KIND: &'static str = "json";
+        const NAME: &'static str = "batch";
+    }
+    impl Cap for Import {
+        const KIND: &'static str = "json";
+        const NAME: &'static str = "import";
+    }
+}
+
+// `limits.<kind>/<name>`, falling back to the kind's own limit.
+fn limit_for(req: &Request<'_>, kind: &str, name: &str) -> Option<ByteUnit> {
+    req.limits().find([kind, name])
+}
+
+#[rocket::async_trait]
+impl<'r, C: Cap + Send + Sync + 'static> FromRequest<'r> for BodyCap<C> {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let declared = req
+            .headers()
+            .get_one("Content-Length")
+            .and_then(|len| len.parse::<u64>().ok());
+        match (declared, limit_for(req, C::KIND, C::NAME)) {
+            (Some(len), Some(limit)) if len > limit.as_u64() => {
+                let error = ApiError::new(
+                    Status::PayloadTooLarge,
+                    "payload_too_large",
+                    None,
+                    format!("request body is over the {} limit for this endpoint", limit),
+                );
+                request::Outcome::Error((error.stash(req), ()))
+            }
+            _ => request::Outcome::Success(BodyCap(PhantomData)),
+        }
+    }
+}
+
+// --- Catchers ---
+#[catch(413)]
+fn too_large_page() -> Template {
+    Template::render(
+        "too_large",
+        context! { max_photo_mb: surprise::MAX_PHOTO_BYTES / (1024 * 1024) },
+    )
+}
+
+pub fn catchers() -> Vec<Catcher> {
+    catchers![too_large_page]
+}
@@ -1,13 +1,17 @@
-#[macro_use] extern crate rocket;
+use moyosola_gift_app::{init_telemetry, self_test};
 
-mod routes;
-
-use rocket::fs::FileServer;
-use routes::build_rocket;
-
-#[launch]
-fn rocket() -> _ {
-    // Attach templates, mount routes, serve static if needed later.
-    build_rocket()
-        .mount("/public", FileServer::from("public")) // optional folder for css later
+// `check` runs the startup self-test and exits instead of serving. The page
+// snapshots and the forms-vs-API comparison are integration tests
+// (tests/golden.rs, tests/contract.rs).
+#[rocket::main]
+async fn main() {
+    init_telemetry(&rocket::Config::figment());
+    if std::env::args().nth(1).as_deref() == Some("check") {
+        let ok = self_test(moyosola_gift_app::rocket()).await;
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+    if let Err(e) = moyosola_gift_app::rocket().launch().await {
+        eprintln!("Launch failed: {}", e);
+        std::process::exit(1);
+    }
 }
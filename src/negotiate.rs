@@ -0,0 +1,103 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::{self, Json, Value};
+use rocket::serde::Serialize;
+use rocket_dyn_templates::Template;
+
+use crate::proto::{self, Schema};
+
+// One handler for both audiences: a page for browsers, and the very same
+// context as JSON for clients whose Accept prefers `application/json`.
+
+pub struct Negotiated {
+    template: &'static str,
+    context: Value,
+}
+
+impl Negotiated {
+    // Serialized up front, like `Template::render`, so contexts may borrow
+    // from the room lock.
+    pub fn render(template: &'static str, context: impl Serialize) -> Self {
+        Negotiated {
+            template,
+            context: json::to_value(context).unwrap_or(Value::Null),
+        }
+    }
+}
+
+pub fn wants_json(req: &Request<'_>) -> bool {
+    req.accept().is_some_and(|accept| accept.preferred().is_json())
+}
+
+impl<'r> Responder<'r, 'static> for Negotiated {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let inner = if wants_json(req) {
+            Json(self.context).respond_to(req)?
+        } else {
+            Template::render(self.template, self.context).respond_to(req)?
+        };
+        // caches must not hand a page to a JSON client or the other way round
+        Response::build_from(inner).header(Header::new("Vary", "Accept")).ok()
+    }
+}
+
+// An /api/v1 body: JSON unless the client's Accept prefers one of the
+// binary encodings, which carry the same fields in fewer bytes.
+pub struct ApiBody {
+    schema: Schema,
+    value: Value,
+}
+
+impl ApiBody {
+    pub fn room(value: Value) -> Self {
+        ApiBody { schema: Schema::Room, value }
+    }
+
+    pub fn result(value: Value) -> Self {
+        ApiBody { schema: Schema::Result, value }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    Json,
+    // proto/now.proto
+    Protobuf,
+    // the JSON body's maps, keys and all, in binary
+    MessagePack,
+    Cbor,
+}
+
+impl Encoding {
+    pub fn preferred(req: &Request<'_>) -> Encoding {
+        let Some(accept) = req.accept() else { return Encoding::Json };
+        let preferred = accept.preferred().media_type();
+        match (preferred.top().as_str(), preferred.sub().as_str()) {
+            proto::MEDIA_TYPE => Encoding::Protobuf,
+            ("application", "msgpack" | "x-msgpack" | "vnd.msgpack") => Encoding::MessagePack,
+            ("application", "cbor") => Encoding::Cbor,
+            _ => Encoding::Json,
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiBody {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let binary = |sub: &'static str, bytes: Option<Vec<u8>>| match bytes {
+            Some(bytes) => (ContentType::new("application", sub), bytes).respond_to(req),
+            None => Err(Status::InternalServerError),
+        };
+        let inner = match Encoding::preferred(req) {
+            Encoding::Json => Json(self.value).respond_to(req)?,
+            Encoding::Protobuf => binary(proto::MEDIA_TYPE.1, Some(proto::encode(self.schema, &self.value)))?,
+            Encoding::MessagePack => binary("msgpack", rmp_serde::to_vec_named(&self.value).ok())?,
+            Encoding::Cbor => {
+                let mut bytes = Vec::new();
+                binary("cbor", ciborium::into_writer(&self.value, &mut bytes).ok().map(|_| bytes))?
+            }
+        };
+        // caches must keep the encodings apart
+        Response::build_from(inner).header(Header::new("Vary", "Accept")).ok()
+    }
+}
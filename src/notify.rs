@@ -0,0 +1,103 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::serde::json::json;
+use rocket::serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::outbox::Outbox;
+use crate::sensitive::Sensitive;
+
+// Player notifications. Each player sets their own preferences per room;
+// deliveries go through the outbox to the push / email relays from config
+// (`push_relay_url`, `email_relay_url`). No relay configured = nothing sent.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct NotifyPrefs {
+    // push when the partner answers and it's your turn
+    pub partner_answered: bool,
+    // email a summary once the game is over
+    pub email_summary: bool,
+    pub email: Option<Sensitive<String>>,
+}
+
+impl Default for NotifyPrefs {
+    fn default() -> Self {
+        NotifyPrefs {
+            partner_answered: true,
+            email_summary: false,
+            email: None,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Relays {
+    push: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Notifier {
+    outbox: Outbox,
+    relays: Arc<RwLock<Relays>>,
+}
+
+impl Notifier {
+    pub fn new(outbox: Outbox) -> Self {
+        Notifier {
+            outbox,
+            relays: Arc::default(),
+        }
+    }
+
+    pub fn partner_answered(&self, code: &str, to: &str, prefs: &NotifyPrefs, partner_name: &str) {
+        let Some(url) = self.relays.read().push.clone() else { return };
+        if !prefs.partner_answered {
+            return;
+        }
+        self.outbox.enqueue(
+            "webhook",
+            &url,
+            json!({
+                "kind": "push",
+                "player_id": to,
+                "room": code,
+                "message": format!("{} answered — your turn! 💌", partner_name),
+            }),
+        );
+    }
+
+    // Returns whether anything was queued.
+    pub fn game_summary(&self, code: &str, prefs: &NotifyPrefs, score: u32, answered: usize) -> bool {
+        let Some(url) = self.relays.read().email.clone() else { return false };
+        let Some(email) = prefs.email.as_ref().map(|e| e.expose()).filter(|_| prefs.email_summary) else {
+            return false;
+        };
+        self.outbox.enqueue(
+            "webhook",
+            &url,
+            json!({
+                "kind": "email_summary",
+                "to": email,
+                "room": code,
+                "score": score,
+                "answered": answered,
+            }),
+        );
+        true
+    }
+
+    pub fn fairing(&self) -> AdHoc {
+        let notifier = self.clone();
+        AdHoc::on_ignite("Notifications", |rocket| async move {
+            let figment = rocket.figment();
+            let relay = |key: &str| figment.extract_inner::<String>(key).ok().filter(|u| !u.is_empty());
+            *notifier.relays.write() = Relays {
+                push: relay("push_relay_url"),
+                email: relay("email_relay_url"),
+            };
+            rocket
+        })
+    }
+}
@@ -0,0 +1,249 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::serde::{json::Value, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::jsonfile;
+use crate::scheduler::now_secs;
+
+// Durable queue for outbound calls (webhooks, emails, push). Deliveries are
+// written to a JSON file so retries survive restarts; a scheduler job drains
+// whatever is due.
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+const BACKOFF_BASE_SECS: u64 = 30;
+const BACKOFF_MAX_SECS: u64 = 60 * 60;
+
+// --- Models ---
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    DeadLettered,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Delivery {
+    pub id: String,
+    // which sender handles it: "webhook", "email", "push", ...
+    pub channel: String,
+    // url / address / player id, depending on channel
+    pub target: String,
+    pub payload: Value,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+    pub status: DeliveryStatus,
+    pub created_at: u64,
+}
+
+#[rocket::async_trait]
+pub trait Sender: Send + Sync {
+    async fn send(&self, delivery: &Delivery) -> Result<(), String>;
+}
+
+#[derive(Default)]
+struct OutboxInner {
+    path: Option<PathBuf>,
+    webhook_url: Option<String>,
+    max_attempts: u32,
+    deliveries: Vec<Delivery>,
+    senders: HashMap<String, Arc<dyn Sender>>,
+}
+
+#[derive(Clone, Default)]
+pub struct Outbox {
+    inner: Arc<RwLock<OutboxInner>>,
+}
+
+fn backoff_secs(attempts: u32) -> u64 {
+    BACKOFF_BASE_SECS
+        .saturating_mul(1u64 << attempts.min(16))
+        .min(BACKOFF_MAX_SECS)
+}
+
+impl Outbox {
+    pub fn register_sender<S: Sender + 'static>(&self, channel: &str, sender: S) {
+        self.inner
+            .write()
+            .senders
+            .insert(channel.to_string(), Arc::new(sender));
+    }
+
+    pub fn enqueue(&self, channel: &str, target: &str, payload: Value) -> String {
+        let now = now_secs();
+        let delivery = Delivery {
+            id: Uuid::new_v4().to_string(),
+            channel: channel.to_string(),
+            target: target.to_string(),
+            payload,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            status: DeliveryStatus::Pending,
+            created_at: now,
+        };
+        let id = delivery.id.clone();
+        let mut inner = self.inner.write();
+        inner.deliveries.push(delivery);
+        persist(&inner);
+        id
+    }
+
+    // Queues an event for the deployment's `webhook_url`, if one is set.
+    pub fn notify_webhook(&self, event: Value) {
+        let url = self.inner.read().webhook_url.clone();
+        if let Some(url) = url {
+            self.enqueue("webhook", &url, event);
+        }
+    }
+
+    pub fn has_webhook(&self) -> bool {
+        self.inner.read().webhook_url.is_some()
+    }
+
+    pub fn dead_letters(&self) -> Vec<Delivery> {
+        self.inner
+            .read()
+            .deliveries
+            .iter()
+            .filter(|d| d.status == DeliveryStatus::DeadLettered)
+            .cloned()
+            .collect()
+    }
+
+    // Sends everything that's due once. Called from the scheduler.
+    pub async fn drain(&self) -> Result<(), String> {
+        let now = now_secs();
+        let due: Vec<(Delivery, Option<Arc<dyn Sender>>)> = {
+            let inner = self.inner.read();
+            inner
+                .deliveries
+                .iter()
+                .filter(|d| d.status == DeliveryStatus::Pending && d.next_attempt_at <= now)
+                .map(|d| (d.clone(), inner.senders.get(&d.channel).cloned()))
+                .collect()
+        };
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let mut results = Vec::with_capacity(due.len());
+        for (delivery, sender) in due {
+            let result = match sender {
+                Some(sender) => sender.send(&delivery).await,
+                None => Err(format!("no sender for channel '{}'", delivery.channel)),
+            };
+            results.push((delivery.id, result));
+        }
+
+        let mut inner = self.inner.write();
+        let max_attempts = inner.max_attempts;
+        let now = now_secs();
+        for (id, result) in results {
+            if let Some(d) = inner.deliveries.iter_mut().find(|d| d.id == id) {
+                d.attempts += 1;
+                match result {
+                    Ok(()) => {
+                        d.status = DeliveryStatus::Delivered;
+                        d.last_error = None;
+                    }
+                    Err(e) => {
+                        d.last_error = Some(e);
+                        if d.attempts >= max_attempts {
+                            d.status = DeliveryStatus::DeadLettered;
+                        } else {
+                            d.next_attempt_at = now + backoff_secs(d.attempts);
+                        }
+                    }
+                }
+            }
+        }
+        // delivered rows don't need to stick around
+        inner
+            .deliveries
+            .retain(|d| d.status != DeliveryStatus::Delivered);
+        persist(&inner);
+        Ok(())
+    }
+
+    // Reads `outbox_path`, `outbox_max_attempts` and `webhook_url`, then
+    // reloads whatever was still queued.
+    pub fn fairing(&self) -> AdHoc {
+        let outbox = self.clone();
+        AdHoc::on_ignite("Outbox", |rocket| async move {
+            let path: Option<PathBuf> = rocket.figment().extract_inner("outbox_path").ok();
+            let max_attempts = rocket
+                .figment()
+                .extract_inner("outbox_max_attempts")
+                .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+            let webhook_url: Option<String> = rocket.figment().extract_inner("webhook_url").ok();
+
+            let mut inner = outbox.inner.write();
+            inner.max_attempts = max_attempts.max(1);
+            inner.webhook_url = webhook_url.filter(|u| !u.is_empty());
+            if let Some(path) = &path {
+                match jsonfile::read(path) {
+                    Ok(Some(deliveries)) => inner.deliveries = deliveries,
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("outbox: ignoring {}", e),
+                }
+            }
+            inner.path = path;
+            drop(inner);
+            rocket
+        })
+    }
+}
+
+fn persist(inner: &OutboxInner) {
+    let Some(path) = &inner.path else { return };
+    if let Err(e) = jsonfile::write(path, &inner.deliveries) {
+        tracing::error!("outbox: {}", e);
+    }
+}
+
+// --- Senders ---
+
+// JSON POST to the delivery's target, http or https. Anything but a 2xx,
+// or no answer within WEBHOOK_TIMEOUT, counts as a failed attempt.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct WebhookSender {
+    client: reqwest::Client,
+}
+
+impl Default for WebhookSender {
+    fn default() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        WebhookSender { client }
+    }
+}
+
+#[rocket::async_trait]
+impl Sender for WebhookSender {
+    async fn send(&self, delivery: &Delivery) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&delivery.target)
+            .header("X-Delivery-Id", delivery.id.as_str())
+            .json(&delivery.payload)
+            .send()
+            .await
+            .map_err(|e| if e.is_timeout() { "webhook timed out".to_string() } else { e.to_string() })?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(format!("webhook answered {}", status.as_u16())),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use rocket::form::Form;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::response::Redirect;
+use rocket::serde::json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::time::Duration;
+use rocket::State;
+use rocket_dyn_templates::{context, Template};
+
+use crate::content::Restrictions;
+use crate::errors;
+use crate::questions::Bank;
+use crate::routes::QUESTION_COUNTS;
+use crate::scoring::GameMode;
+use crate::sealed::{CookieKeys, Verified};
+use crate::tenants::Tenant;
+
+// Room settings a host reuses: mode, seats, categories, timer and length
+// under a name, picked from the create form. There are no accounts, so they
+// live in a sealed cookie on the host's browser, one of them marked as the
+// default the create form starts from. Managed at /presets.
+
+const COOKIE_NAME: &str = "presets";
+const COOKIE_DAYS: i64 = 365;
+// keeps the cookie well under browsers' 4 KB
+const MAX_PRESETS: usize = 10;
+const MAX_NAME_LEN: usize = 40;
+
+// --- Models ---
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Preset {
+    pub name: String,
+    #[serde(default)]
+    pub game_mode: GameMode,
+    // None = the mode's default
+    pub capacity: Option<usize>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    // seconds per question, None = untimed
+    pub time_limit: Option<u64>,
+    pub question_count: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Presets {
+    pub items: Vec<Preset>,
+    // name of the one the create form starts from
+    pub default: Option<String>,
+}
+
+impl Presets {
+    // This browser's presets; none if the cookie is missing or won't open.
+    pub fn load(cookies: &CookieJar<'_>, keys: &CookieKeys) -> Presets {
+        let Some(cookie) = cookies.get(COOKIE_NAME) else {
+            return Presets::default();
+        };
+        let value = match keys.open(cookie.value()) {
+            Some(Verified::Current(value)) => value,
+            Some(Verified::Previous(value)) => {
+                // sealed before a key rotation: move it onto the new key
+                cookies.add(presets_cookie(keys.seal(&value)));
+                value
+            }
+            None => return Presets::default(),
+        };
+        json::from_str(&value).unwrap_or_default()
+    }
+
+    fn store(&self, cookies: &CookieJar<'_>, keys: &CookieKeys) {
+        let value = json::to_string(self).unwrap_or_default();
+        cookies.add(presets_cookie(keys.seal(&value)));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.items.iter().find(|p| p.name == name)
+    }
+
+    // `name`, else the default; what the create form should start from.
+    pub fn pick(&self, name: Option<&str>) -> Option<&Preset> {
+        name.or(self.default.as_deref()).and_then(|n| self.get(n))
+    }
+}
+
+fn presets_cookie(value: String) -> Cookie<'static> {
+    let mut cookie = Cookie::new(COOKIE_NAME, value);
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_path("/");
+    cookie.set_max_age(Duration::days(COOKIE_DAYS));
+    cookie
+}
+
+// --- Routes ---
+pub fn routes() -> Vec<rocket::Route> {
+    routes![presets_get, presets_post, preset_delete_post, preset_default_post]
+}
+
+#[derive(FromForm)]
+struct PresetForm {
+    name: String,
+    game_mode: Option<GameMode>,
+    // blank = the mode's default
+    capacity: Option<usize>,
+    categories: Vec<String>,
+    time_limit: Option<u64>,
+    question_count: Option<usize>,
+    // checkbox: start the create form from this one
+    make_default: bool,
+}
+
+impl PresetForm {
+    fn into_preset(self) -> Result<Preset, &'static str> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err("Give the preset a name of up to 40 characters.");
+        }
+        let game_mode = self.game_mode.unwrap_or_default();
+        if self.capacity.is_some_and(|n| !game_mode.capacities().contains(&n)) {
+            return Err("Couples play as two; groups seat 2 to 8 players.");
+        }
+        if self.question_count.is_some_and(|n| !QUESTION_COUNTS.contains(&n)) {
+            return Err("Pick 10, 25 or 50 questions.");
+        }
+        let mut categories: Vec<String> = self
+            .categories
+            .iter()
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect();
+        categories.sort();
+        categories.dedup();
+        Ok(Preset {
+            name,
+            game_mode,
+            capacity: self.capacity.filter(|_| game_mode.is_group()),
+            categories,
+            time_limit: self.time_limit.filter(|t| *t > 0),
+            question_count: self.question_count,
+        })
+    }
+}
+
+#[get("/presets")]
+fn presets_get(
+    cookies: &CookieJar<'_>,
+    keys: &State<CookieKeys>,
+    bank: Bank,
+    tenant: Tenant,
+    restrictions: Restrictions,
+) -> Template {
+    let presets = Presets::load(cookies, keys);
+    // as the create form offers them
+    let mut categories: Vec<&str> = bank
+        .drawable(tenant.0.as_deref())
+        .filter(|q| restrictions.allows(q) && !q.mature)
+        .map(|q| q.category.as_str())
+        .collect();
+    categories.sort();
+    categories.dedup();
+    Template::render(
+        "presets",
+        context! {
+            presets: presets.items,
+            default: presets.default,
+            categories,
+            question_counts: QUESTION_COUNTS,
+            max_presets: MAX_PRESETS,
+        },
+    )
+}
+
+// Adds a preset, or replaces the one with the same name.
+#[post("/presets", data = "<form>")]
+fn presets_post(
+    form: Form<PresetForm>,
+    cookies: &CookieJar<'_>,
+    keys: &State<CookieKeys>,
+) -> Result<Redirect, (Status, Template)> {
+    let form = form.into_inner();
+    let make_default = form.make_default;
+    let preset = form
+        .into_preset()
+        .map_err(|message| errors::page(Status::UnprocessableEntity, message))?;
+    let mut presets = Presets::load(cookies, keys);
+    match presets.items.iter_mut().find(|p| p.name == preset.name) {
+        Some(existing) => *existing = preset.clone(),
+        None if presets.items.len() >= MAX_PRESETS => {
+            let message = "That's as many presets as we can keep. Delete one to make room.";
+            return Err(errors::page(Status::UnprocessableEntity, message));
+        }
+        None => presets.items.push(preset.clone()),
+    }
+    if make_default {
+        presets.default = Some(preset.name);
+    }
+    presets.store(cookies, keys);
+    Ok(Redirect::to(uri!(presets_get)))
+}
+
+#[post("/presets/<name>/delete")]
+fn preset_delete_post(name: &str, cookies: &CookieJar<'_>, keys: &State<CookieKeys>) -> Redirect {
+    let mut presets = Presets::load(cookies, keys);
+    presets.items.retain(|p| p.name != name);
+    if presets.default.as_deref() == Some(name) {
+        presets.default = None;
+    }
+    presets.store(cookies, keys);
+    Redirect::to(uri!(presets_get))
+}
+
+// Makes `name` the default, or clears the default if it's already that.
+#[post("/presets/<name>/default")]
+fn preset_default_post(name: &str, cookies: &CookieJar<'_>, keys: &State<CookieKeys>) -> Result<Redirect, Status> {
+    let mut presets = Presets::load(cookies, keys);
+    presets.get(name).ok_or(Status::NotFound)?;
+    presets.default = match presets.default.as_deref() {
+        Some(current) if current == name => None,
+        _ => Some(name.to_string()),
+    };
+    presets.store(cookies, keys);
+    Ok(Redirect::to(uri!(presets_get)))
+}
@@ -0,0 +1,125 @@
+use prost::Message;
+use rocket::serde::json::Value;
+
+// The /api/v1 bodies in protobuf, schema in proto/now.proto. Routes build
+// their JSON as usual and `negotiate::ApiBody` converts it here when the
+// client asked for protobuf, so the two forms can't drift apart.
+
+pub mod pb {
+    include!(concat!(env!("OUT_DIR"), "/now.v1.rs"));
+}
+
+pub const MEDIA_TYPE: (&str, &str) = ("application", "x-protobuf");
+
+// Which message a JSON body becomes.
+#[derive(Clone, Copy, Debug)]
+pub enum Schema {
+    Room,
+    Result,
+}
+
+pub fn encode(schema: Schema, value: &Value) -> Vec<u8> {
+    match schema {
+        Schema::Room => room(value).encode_to_vec(),
+        Schema::Result => result(value).encode_to_vec(),
+    }
+}
+
+fn room(v: &Value) -> pb::Room {
+    pb::Room {
+        code: text(v, "code"),
+        player_id: maybe_text(v, "player_id"),
+        seat: maybe_text(v, "seat"),
+        is_host: flag(v, "is_host"),
+        players: list(v, "players").map(player).collect(),
+        waiting_for_partner: flag(v, "waiting_for_partner"),
+        finished: flag(v, "finished"),
+        total: v["total"].as_u64().map(|n| n as u32),
+        question: v["question"].is_object().then(|| question(&v["question"])),
+        answered: flag(v, "answered"),
+        seconds_left: v["seconds_left"].as_u64(),
+        running_score: v["running_score"].as_u64().map(|n| n as u32),
+        game_mode: text(v, "game_mode"),
+        capacity: number(v, "capacity"),
+    }
+}
+
+fn question(v: &Value) -> pb::Question {
+    let kind = text(&v["kind"], "type");
+    let options = || list(v, "options");
+    let texts = |of: &str| -> Vec<String> {
+        if kind != of {
+            return Vec::new();
+        }
+        options().filter_map(Value::as_str).map(str::to_string).collect()
+    };
+    let scale = |end: &str| v["options"][end].as_i64().map(|n| n as i32);
+    pb::Question {
+        index: number(v, "index"),
+        number: number(v, "number"),
+        text: text(v, "text"),
+        blanks: number(v, "blanks"),
+        ranking_options: texts("ranking"),
+        choice_options: texts("multiple_choice"),
+        scale_min: scale("min"),
+        scale_max: scale("max"),
+        kind,
+        picture_options: options()
+            .filter(|o| o.is_object())
+            .map(|o| pb::PictureOption {
+                id: text(o, "id"),
+                label: text(o, "label"),
+                url: text(o, "url"),
+            })
+            .collect(),
+    }
+}
+
+fn player(v: &Value) -> pb::Player {
+    pb::Player {
+        name: text(v, "name"),
+        score: number(v, "score"),
+    }
+}
+
+fn result(v: &Value) -> pb::Result {
+    pb::Result {
+        code: text(v, "code"),
+        finished: flag(v, "finished"),
+        score: number(v, "score"),
+        verdict: text(v, "verdict"),
+        answered: number(v, "answered"),
+        players: list(v, "players").map(player).collect(),
+        questions: list(v, "questions")
+            .map(|q| pb::QuestionResult {
+                number: number(q, "number"),
+                question: maybe_text(q, "question"),
+                category: maybe_text(q, "category"),
+                credit_pct: number(q, "credit_pct"),
+                matched: flag(q, "matched"),
+                weight: q["weight"].as_f64().unwrap_or(1.0),
+            })
+            .collect(),
+        snapshot: maybe_text(v, "snapshot"),
+    }
+}
+
+fn text(v: &Value, key: &str) -> String {
+    maybe_text(v, key).unwrap_or_default()
+}
+
+fn maybe_text(v: &Value, key: &str) -> Option<String> {
+    v[key].as_str().map(str::to_string)
+}
+
+fn number(v: &Value, key: &str) -> u32 {
+    v[key].as_u64().unwrap_or_default() as u32
+}
+
+fn flag(v: &Value, key: &str) -> bool {
+    v[key].as_bool().unwrap_or_default()
+}
+
+fn list<'a>(v: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    v[key].as_array().into_iter().flatten()
+}
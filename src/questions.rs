@@ -0,0 +1,724 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::{Format, Toml};
+use rocket::figment::Figment;
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::custom;
+use crate::jsonfile;
+
+// --- Models ---
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Question {
+    pub id: String,
+    pub text: String,
+    pub category: String,
+    // number of answers expected ("name 3 things..."), 1 for plain questions
+    #[serde(default = "one")]
+    pub blanks: usize,
+    #[serde(default)]
+    pub kind: QuestionKind,
+    // only asked straight after another question, when its answers qualify
+    #[serde(default)]
+    pub follow_up: Option<FollowUp>,
+    // locale -> text; `text` is the canonical (English) wording
+    #[serde(default)]
+    pub translations: HashMap<String, String>,
+    // bumped on every admin edit; older versions live in the bank's history
+    #[serde(default = "first_version")]
+    pub version: u32,
+    // retired questions are no longer dealt but still resolve for old games
+    #[serde(default)]
+    pub retired: bool,
+    // free-form admin labels ("seasonal", "needs-review"); never shown to players
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // owning tenant; None = the shared pack everyone can draw
+    #[serde(default)]
+    pub tenant: Option<String>,
+    // 18+; only dealt in rooms where both players consented
+    #[serde(default)]
+    pub mature: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct FollowUp {
+    // id of the question this one follows
+    pub after: String,
+    pub when: Condition,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    // everyone gave this answer (case-insensitive)
+    AllSaid { answer: String },
+    // at least one player gave it
+    AnySaid { answer: String },
+    // the pair fully matched, whatever they said
+    Matched,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", tag = "type", rename_all = "snake_case")]
+pub enum QuestionKind {
+    #[default]
+    FreeText,
+    // players order the options from most to least
+    Ranking { options: Vec<String> },
+    // pick one image; answers store the option id
+    PictureChoice { options: Vec<PictureOption> },
+    // pick one of a few answers; stored as written
+    MultipleChoice { options: Vec<String> },
+    // a whole number from min to max ("how spicy, 1-10?")
+    Scale { min: i32, max: i32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PictureOption {
+    pub id: String,
+    pub label: String,
+    // path under the public folder, resolved through the asset store
+    pub image: String,
+}
+
+impl Condition {
+    pub fn holds(&self, answers: &[&[String]], credit: f64) -> bool {
+        let said = |answer: &str, a: &[String]| a.iter().any(|i| i.trim().eq_ignore_ascii_case(answer.trim()));
+        match self {
+            Condition::AllSaid { answer } => !answers.is_empty() && answers.iter().all(|a| said(answer, a)),
+            Condition::AnySaid { answer } => answers.iter().any(|a| said(answer, a)),
+            Condition::Matched => credit >= 1.0,
+        }
+    }
+}
+
+fn one() -> usize {
+    1
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct QuestionBank {
+    pub questions: Vec<Question>,
+    // id -> superseded versions, oldest first
+    #[serde(default)]
+    pub history: HashMap<String, Vec<Question>>,
+}
+
+impl QuestionBank {
+    pub fn builtin() -> Self {
+        let mut questions = BUILTIN
+            .iter()
+            .map(|(id, category, text, blanks)| Question {
+                id: id.to_string(),
+                text: text.to_string(),
+                category: category.to_string(),
+                blanks: *blanks,
+                kind: QuestionKind::FreeText,
+                follow_up: None,
+                translations: HashMap::new(),
+                version: 1,
+                retired: false,
+                tags: Vec::new(),
+                tenant: None,
+                mature: false,
+            })
+            .chain(RANKINGS.iter().map(|(id, category, text, options)| Question {
+                id: id.to_string(),
+                text: text.to_string(),
+                category: category.to_string(),
+                blanks: 1,
+                kind: QuestionKind::Ranking {
+                    options: options.iter().map(|o| o.to_string()).collect(),
+                },
+                follow_up: None,
+                translations: HashMap::new(),
+                version: 1,
+                retired: false,
+                tags: Vec::new(),
+                tenant: None,
+                mature: false,
+            }))
+            .chain(PICTURES.iter().map(|(id, category, text, options)| Question {
+                id: id.to_string(),
+                text: text.to_string(),
+                category: category.to_string(),
+                blanks: 1,
+                kind: QuestionKind::PictureChoice {
+                    options: options
+                        .iter()
+                        .map(|(id, label, image)| PictureOption {
+                            id: id.to_string(),
+                            label: label.to_string(),
+                            image: image.to_string(),
+                        })
+                        .collect(),
+                },
+                follow_up: None,
+                translations: HashMap::new(),
+                version: 1,
+                retired: false,
+                tags: Vec::new(),
+                tenant: None,
+                mature: false,
+            }))
+            .chain(CHOICES.iter().map(|(id, category, text, options)| Question {
+                id: id.to_string(),
+                text: text.to_string(),
+                category: category.to_string(),
+                blanks: 1,
+                kind: QuestionKind::MultipleChoice {
+                    options: options.iter().map(|o| o.to_string()).collect(),
+                },
+                follow_up: None,
+                translations: HashMap::new(),
+                version: 1,
+                retired: false,
+                tags: Vec::new(),
+                tenant: None,
+                mature: false,
+            }))
+            .chain(SCALES.iter().map(|(id, category, text, min, max)| Question {
+                id: id.to_string(),
+                text: text.to_string(),
+                category: category.to_string(),
+                blanks: 1,
+                kind: QuestionKind::Scale { min: *min, max: *max },
+                follow_up: None,
+                translations: HashMap::new(),
+                version: 1,
+                retired: false,
+                tags: Vec::new(),
+                tenant: None,
+                mature: false,
+            }))
+            .chain(FOLLOW_UPS.iter().map(|(id, category, text, after, answer)| Question {
+                id: id.to_string(),
+                text: text.to_string(),
+                category: category.to_string(),
+                blanks: 1,
+                kind: QuestionKind::FreeText,
+                follow_up: Some(FollowUp {
+                    after: after.to_string(),
+                    when: Condition::AllSaid {
+                        answer: answer.to_string(),
+                    },
+                }),
+                translations: HashMap::new(),
+                version: 1,
+                retired: false,
+                tags: Vec::new(),
+                tenant: None,
+                mature: false,
+            }))
+            .collect::<Vec<_>>();
+        for (id, locale, text) in TRANSLATIONS {
+            if let Some(q) = questions.iter_mut().find(|q| q.id == *id) {
+                q.translations.insert(locale.to_string(), text.to_string());
+            }
+        }
+        QuestionBank {
+            questions,
+            history: HashMap::new(),
+        }
+    }
+
+    // The seed bank from `questions_path`: a JSON array of questions (or
+    // `{"questions": [...]}`), or `[[questions]]` tables in a .toml file.
+    // Fields as in the store file; only id, text and category are required.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("questions file `{}` can't be read: {}", path.display(), e))?;
+        let questions = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Figment::from(Toml::string(&raw))
+                .extract::<QuestionFile>()
+                .map(|f| f.questions)
+                .map_err(|e| e.to_string()),
+            _ if raw.trim_start().starts_with('[') => rocket::serde::json::from_str(&raw).map_err(|e| e.to_string()),
+            _ => rocket::serde::json::from_str::<QuestionFile>(&raw)
+                .map(|f| f.questions)
+                .map_err(|e| e.to_string()),
+        }
+        .map_err(|e| format!("questions file `{}` is malformed: {}", path.display(), e))?;
+        let bank = QuestionBank {
+            questions,
+            history: HashMap::new(),
+        };
+        let problems = bank.problems();
+        if !problems.is_empty() {
+            return Err(format!("questions file `{}`: {}", path.display(), problems.join("; ")));
+        }
+        Ok(bank)
+    }
+
+    // Everything wrong with the bank, for the self-test. Empty = fine.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, q) in self.questions.iter().enumerate() {
+            if self.questions[..i].iter().any(|o| o.id == q.id) {
+                problems.push(format!("question `{}` is defined twice", q.id));
+            }
+            if custom::is_custom(&q.id) {
+                problems.push(format!("question id `{}` is reserved for hosts' own questions", q.id));
+            }
+            if q.text.trim().is_empty() || q.category.trim().is_empty() {
+                problems.push(format!("question `{}` needs text and a category", q.id));
+            }
+            if !(1..=5).contains(&q.blanks) {
+                problems.push(format!("question `{}` asks for {} blanks (1-5)", q.id, q.blanks));
+            }
+            let options = match &q.kind {
+                QuestionKind::FreeText => None,
+                QuestionKind::Ranking { options } => Some(options.len()),
+                QuestionKind::PictureChoice { options } => Some(options.len()),
+                QuestionKind::MultipleChoice { options } => Some(options.len()),
+                QuestionKind::Scale { .. } => None,
+            };
+            if options.is_some_and(|n| n < 2) {
+                problems.push(format!("question `{}` needs at least two options", q.id));
+            }
+            if let QuestionKind::Scale { min, max } = q.kind {
+                if min >= max {
+                    problems.push(format!("question `{}` has a scale from {} to {}", q.id, min, max));
+                }
+            }
+            if let Some(follow) = &q.follow_up {
+                if self.get(&follow.after).is_none() {
+                    problems.push(format!("question `{}` follows unknown question `{}`", q.id, follow.after));
+                }
+            }
+        }
+        if self.drawable(None).next().is_none() {
+            problems.push("no questions can be dealt".to_string());
+        }
+        problems
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Question> {
+        self.questions.iter().find(|q| q.id == id)
+    }
+
+    // Every version of a question, oldest first, current last.
+    pub fn versions(&self, id: &str) -> Vec<&Question> {
+        let old = self.history.get(id).into_iter().flatten();
+        old.chain(self.get(id)).collect()
+    }
+
+    // Questions that can be dealt at random; follow-ups only come via chains.
+    pub fn drawable<'a>(&'a self, tenant: Option<&'a str>) -> impl Iterator<Item = &'a Question> {
+        self.questions
+            .iter()
+            .filter(move |q| q.follow_up.is_none() && !q.retired && q.visible_to(tenant))
+    }
+
+    // First follow-up to `after` whose condition the answers meet.
+    pub fn follow_up(&self, after: &str, answers: &[&[String]], credit: f64, tenant: Option<&str>) -> Option<&Question> {
+        self.questions.iter().filter(|q| !q.retired && q.visible_to(tenant)).find(|q| {
+            q.follow_up
+                .as_ref()
+                .is_some_and(|f| f.after == after && f.when.holds(answers, credit))
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct QuestionFile {
+    questions: Vec<Question>,
+}
+
+// --- Store ---
+// The live, editable bank. Edits swap in a new snapshot, so a request that
+// already holds one keeps a consistent view. Persisted to
+// `question_store_path` when set; otherwise edits last until restart.
+#[derive(Clone)]
+pub struct QuestionStore {
+    inner: Arc<RwLock<StoreInner>>,
+}
+
+struct StoreInner {
+    path: Option<PathBuf>,
+    bank: Arc<QuestionBank>,
+}
+
+// Fields an admin can edit; everything else carries over.
+pub struct QuestionEdit {
+    pub text: String,
+    pub category: String,
+    pub blanks: usize,
+    pub mature: bool,
+}
+
+impl QuestionStore {
+    pub fn new(bank: QuestionBank) -> Self {
+        QuestionStore {
+            inner: Arc::new(RwLock::new(StoreInner {
+                path: None,
+                bank: Arc::new(bank),
+            })),
+        }
+    }
+
+    pub fn snapshot(&self) -> Arc<QuestionBank> {
+        self.inner.read().bank.clone()
+    }
+
+    pub fn create(&self, id: &str, edit: QuestionEdit, tenant: Option<&str>) -> Result<(), String> {
+        self.update(|bank| {
+            if bank.get(id).is_some() {
+                return Err(format!("question `{}` already exists", id));
+            }
+            bank.questions.push(Question {
+                id: id.to_string(),
+                text: edit.text,
+                category: edit.category,
+                blanks: edit.blanks,
+                kind: QuestionKind::FreeText,
+                follow_up: None,
+                translations: HashMap::new(),
+                version: 1,
+                retired: false,
+                tags: Vec::new(),
+                tenant: tenant.map(str::to_string),
+                mature: edit.mature,
+            });
+            Ok(())
+        })
+    }
+
+    pub fn edit(&self, id: &str, edit: QuestionEdit) -> Result<(), String> {
+        self.update(|bank| {
+            let current = bank.get(id).ok_or_else(|| format!("no question `{}`", id))?.clone();
+            let mut next = current.clone();
+            next.text = edit.text;
+            next.category = edit.category;
+            next.blanks = edit.blanks;
+            next.mature = edit.mature;
+            next.version += 1;
+            // translations were of the old wording
+            next.translations.clear();
+            bank.history.entry(id.to_string()).or_default().push(current);
+            if let Some(q) = bank.questions.iter_mut().find(|q| q.id == id) {
+                *q = next;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn set_retired(&self, id: &str, retired: bool) -> Result<(), String> {
+        self.update(|bank| {
+            let q = bank
+                .questions
+                .iter_mut()
+                .find(|q| q.id == id)
+                .ok_or_else(|| format!("no question `{}`", id))?;
+            q.retired = retired;
+            Ok(())
+        })
+    }
+
+    // Adds `tags` to every listed question in one write. Returns the ids that
+    // don't exist; the rest are tagged regardless.
+    pub fn tag(&self, ids: &[String], tags: &[String]) -> Vec<String> {
+        let mut missing = Vec::new();
+        let _ = self.update(|bank| {
+            for id in ids {
+                let Some(q) = bank.questions.iter_mut().find(|q| &q.id == id) else {
+                    missing.push(id.clone());
+                    continue;
+                };
+                for tag in tags {
+                    if !q.tags.contains(tag) {
+                        q.tags.push(tag.clone());
+                    }
+                }
+            }
+            Ok(())
+        });
+        missing
+    }
+
+    fn update(&self, f: impl FnOnce(&mut QuestionBank) -> Result<(), String>) -> Result<(), String> {
+        let mut inner = self.inner.write();
+        let mut bank = (*inner.bank).clone();
+        f(&mut bank)?;
+        inner.bank = Arc::new(bank);
+        persist(&inner);
+        Ok(())
+    }
+
+    pub fn fairing(&self) -> AdHoc {
+        let store = self.clone();
+        AdHoc::on_ignite("Question Store", |rocket| async move {
+            let path: Option<PathBuf> = rocket.figment().extract_inner("question_store_path").ok();
+            let seed: Option<PathBuf> = rocket.figment().extract_inner("questions_path").ok();
+            let mut inner = store.inner.write();
+            // the seed replaces the built-in bank; saved edits still win below
+            if let Some(seed) = &seed {
+                match QuestionBank::from_file(seed) {
+                    Ok(bank) => inner.bank = Arc::new(bank),
+                    Err(e) => tracing::warn!("questions: {}", e),
+                }
+            }
+            if let Some(path) = &path {
+                match jsonfile::read(path) {
+                    Ok(Some(bank)) => inner.bank = Arc::new(bank),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("questions: ignoring {}", e),
+                }
+            }
+            inner.path = path;
+            drop(inner);
+            rocket
+        })
+    }
+}
+
+fn persist(inner: &StoreInner) {
+    let Some(path) = &inner.path else { return };
+    if let Err(e) = jsonfile::write(path, &*inner.bank) {
+        tracing::error!("questions: {}", e);
+    }
+}
+
+// --- Guard ---
+// The bank as of this request.
+pub struct Bank(Arc<QuestionBank>);
+
+impl Deref for Bank {
+    type Target = QuestionBank;
+
+    fn deref(&self) -> &QuestionBank {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Bank {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        match req.rocket().state::<QuestionStore>() {
+            Some(store) => request::Outcome::Success(Bank(store.snapshot())),
+            None => request::Outcome::Error((rocket::http::Status::InternalServerError, ())),
+        }
+    }
+}
+
+// --- Diff ---
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde", tag = "op", content = "text", rename_all = "snake_case")]
+pub enum DiffPart {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+// Word-level diff (LCS); question texts are short.
+pub fn text_diff(old: &str, new: &str) -> Vec<DiffPart> {
+    let a: Vec<&str> = old.split_whitespace().collect();
+    let b: Vec<&str> = new.split_whitespace().collect();
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut parts = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            parts.push(DiffPart::Same(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            parts.push(DiffPart::Removed(a[i].to_string()));
+            i += 1;
+        } else {
+            parts.push(DiffPart::Added(b[j].to_string()));
+            j += 1;
+        }
+    }
+    parts
+}
+
+impl Question {
+    // Shared questions are visible everywhere; a tenant's own only to them.
+    pub fn visible_to(&self, tenant: Option<&str>) -> bool {
+        self.tenant.is_none() || self.tenant.as_deref() == tenant
+    }
+
+    pub fn text_for(&self, locale: &str) -> &str {
+        self.translations.get(locale).map(String::as_str).unwrap_or(&self.text)
+    }
+
+    // The first few words, enough to know the topic without spoiling the
+    // question for the partner who'll answer it.
+    pub fn teaser(&self, locale: &str, max_chars: usize) -> String {
+        let text = self.text_for(locale);
+        if text.chars().count() <= max_chars {
+            return text.to_string();
+        }
+        let cut: String = text.chars().take(max_chars).collect();
+        let cut = cut.rsplit_once(' ').map(|(head, _)| head).unwrap_or(&cut);
+        format!("{}…", cut.trim_end_matches(|c: char| !c.is_alphanumeric()))
+    }
+
+    // What to show for a stored answer; picture answers are option ids.
+    pub fn display_answer(&self, items: &[String]) -> String {
+        match &self.kind {
+            QuestionKind::PictureChoice { options } => items
+                .iter()
+                .map(|id| {
+                    options
+                        .iter()
+                        .find(|o| &o.id == id)
+                        .map(|o| o.label.clone())
+                        .unwrap_or_else(|| id.clone())
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => items.join(", "),
+        }
+    }
+}
+
+// Starter set until the bank is loaded from a file.
+const BUILTIN: &[(&str, &str, &str, usize)] = &[
+    ("rom-001", "romantic", "Where would our dream date be?", 1),
+    ("rom-002", "romantic", "What song makes you think of us?", 1),
+    ("rom-003", "romantic", "What was the first thing you noticed about me?", 1),
+    ("rom-004", "romantic", "What's the most romantic gift you could get?", 1),
+    ("rom-005", "romantic", "Which city would we honeymoon in?", 1),
+    ("fun-001", "funny", "What's my most annoying habit?", 1),
+    ("fun-002", "funny", "Which cartoon character am I most like?", 1),
+    ("fun-003", "funny", "What would I be famous for?", 1),
+    ("fun-004", "funny", "What snack could I never live without?", 1),
+    ("fun-005", "funny", "Who falls asleep first during a movie?", 1),
+    ("deep-001", "deep", "What's my biggest dream?", 1),
+    ("deep-002", "deep", "What am I most afraid of?", 1),
+    ("deep-003", "deep", "Where do we see ourselves in five years?", 1),
+    ("deep-004", "deep", "What makes me feel most loved?", 1),
+    ("deep-005", "deep", "What's one thing I'd change about my past?", 1),
+    ("fav-001", "favorites", "What's my favorite food?", 1),
+    ("fav-002", "favorites", "What's my favorite color?", 1),
+    ("fav-003", "favorites", "What's my favorite movie?", 1),
+    ("fav-004", "favorites", "What's my favorite season?", 1),
+    ("fav-005", "favorites", "What's my favorite way to relax?", 1),
+    ("multi-001", "favorites", "Name 3 things we both love.", 3),
+    ("multi-002", "romantic", "Name 3 places we've been together.", 3),
+    ("multi-003", "funny", "Name 2 words I say way too often.", 2),
+    ("deep-006", "deep", "Would I move abroad with you? (yes/no)", 1),
+];
+
+// (id, category, text, after, asked when everyone answered)
+const FOLLOW_UPS: &[(&str, &str, &str, &str, &str)] = &[(
+    "deep-006a",
+    "deep",
+    "You both said yes — where would we move first?",
+    "deep-006",
+    "yes",
+)];
+
+const RANKINGS: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "rank-001",
+        "romantic",
+        "Rank these date ideas from my favorite to least favorite.",
+        &["Beach day", "Movie night", "Fancy dinner", "Hiking", "Game night"],
+    ),
+    (
+        "rank-002",
+        "deep",
+        "Rank what matters most to me in a relationship.",
+        &["Trust", "Humor", "Adventure", "Quality time", "Communication"],
+    ),
+];
+
+// (id, label, image)
+type PictureRow = (&'static str, &'static str, &'static str);
+
+const PICTURES: &[(&str, &str, &str, &[PictureRow])] = &[
+    (
+        "pic-001",
+        "romantic",
+        "Where would I rather wake up?",
+        &[
+            ("beach", "Beach", "img/beach.svg"),
+            ("mountains", "Mountains", "img/mountains.svg"),
+            ("city", "City lights", "img/city.svg"),
+            ("cabin", "Cosy cabin", "img/cabin.svg"),
+        ],
+    ),
+    (
+        "pic-002",
+        "funny",
+        "Cat person or dog person — which am I?",
+        &[("cat", "Cats", "img/cat.svg"), ("dog", "Dogs", "img/dog.svg")],
+    ),
+];
+
+// (id, locale, text); anything missing falls back to the English text.
+const CHOICES: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "choice-001",
+        "funny",
+        "What would I grab first in a fire?",
+        &["My phone", "The snacks", "You", "The pet"],
+    ),
+    (
+        "choice-002",
+        "romantic",
+        "How would I rather say I love you?",
+        &["Words", "A gift", "A hug", "Cooking dinner"],
+    ),
+];
+
+// (id, category, text, min, max)
+const SCALES: &[(&str, &str, &str, i32, i32)] = &[
+    ("scale-001", "funny", "How spicy can I handle my food, 1 to 10?", 1, 10),
+    ("scale-002", "deep", "How much of a morning person am I, 1 to 10?", 1, 10),
+];
+
+const TRANSLATIONS: &[(&str, &str, &str)] = &[
+    ("rom-001", "fr", "Où serait notre rendez-vous de rêve ?"),
+    ("rom-001", "es", "¿Dónde sería nuestra cita soñada?"),
+    ("rom-002", "fr", "Quelle chanson te fait penser à nous ?"),
+    ("rom-002", "es", "¿Qué canción te hace pensar en nosotros?"),
+    ("rom-005", "fr", "Dans quelle ville ferions-nous notre lune de miel ?"),
+    ("rom-005", "es", "¿En qué ciudad pasaríamos la luna de miel?"),
+    ("fun-001", "fr", "Quelle est ma manie la plus agaçante ?"),
+    ("fun-001", "es", "¿Cuál es mi hábito más molesto?"),
+    ("fun-004", "fr", "De quel snack ne pourrais-je jamais me passer ?"),
+    ("fun-004", "es", "¿Sin qué snack no podría vivir?"),
+    ("deep-001", "fr", "Quel est mon plus grand rêve ?"),
+    ("deep-001", "es", "¿Cuál es mi mayor sueño?"),
+    ("deep-002", "fr", "De quoi ai-je le plus peur ?"),
+    ("deep-002", "es", "¿Qué es lo que más miedo me da?"),
+    ("fav-001", "fr", "Quel est mon plat préféré ?"),
+    ("fav-001", "es", "¿Cuál es mi comida favorita?"),
+    ("fav-002", "fr", "Quelle est ma couleur préférée ?"),
+    ("fav-002", "es", "¿Cuál es mi color favorito?"),
+    ("fav-003", "fr", "Quel est mon film préféré ?"),
+    ("fav-003", "es", "¿Cuál es mi película favorita?"),
+    ("fav-004", "fr", "Quelle est ma saison préférée ?"),
+    ("fav-004", "es", "¿Cuál es mi estación favorita?"),
+];
@@ -0,0 +1,483 @@
+use parking_lot::{Mutex, RwLock};
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::http::{Header, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::api::ApiError;
+use crate::scheduler::now_secs;
+
+// Request budgets for /api/v1, per caller and scope, in fixed windows.
+// `[default.api_rate_limits]` sets `window_secs` and a budget per scope (0
+// turns a scope off). A caller is their bearer token when they send one
+// (admin scripts), else their IP. Every limited response carries
+// `X-RateLimit-Limit/-Remaining/-Reset`; going over answers 429 with the
+// API envelope and `Retry-After`. Routes opt in with an `ApiLimit<..>`
+// guard, like `BodyCap`.
+
+const DEFAULT_WINDOW_SECS: u64 = 60;
+
+// --- Scopes ---
+pub trait Scope {
+    const NAME: &'static str;
+    const DEFAULT: u32;
+}
+
+pub struct ApiLimit<S>(PhantomData<S>);
+
+pub mod scope {
+    use super::Scope;
+
+    // creating rooms
+    pub struct Create;
+    pub struct Join;
+    pub struct Answer;
+    // room state, results, public stats
+    pub struct Read;
+    pub struct Admin;
+    // emoji reactions during play; a form scope only
+    pub struct React;
+
+    impl Scope for Create {
+        const NAME: &'static str = "create";
+        const DEFAULT: u32 = 10;
+    }
+    impl Scope for Join {
+        const NAME: &'static str = "join";
+        const DEFAULT: u32 = 30;
+    }
+    impl Scope for Answer {
+        const NAME: &'static str = "answer";
+        const DEFAULT: u32 = 120;
+    }
+    impl Scope for Read {
+        const NAME: &'static str = "read";
+        const DEFAULT: u32 = 300;
+    }
+    impl Scope for Admin {
+        const NAME: &'static str = "admin";
+        const DEFAULT: u32 = 120;
+    }
+    impl Scope for React {
+        const NAME: &'static str = "react";
+        const DEFAULT: u32 = 60;
+    }
+}
+
+const SCOPES: &[&str] = &["create", "join", "answer", "read", "admin"];
+
+// --- Limiter ---
+// What one request used up; kept on the request for the response headers.
+#[derive(Clone, Copy, Debug)]
+struct Usage {
+    limit: u32,
+    remaining: u32,
+    // seconds until the window resets
+    reset: u64,
+    over: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    window_secs: u64,
+    // scope -> requests per window; absent = the scope's default
+    budgets: HashMap<String, u32>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            window_secs: DEFAULT_WINDOW_SECS,
+            budgets: HashMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let mut limits = RateLimitConfig::default();
+        if figment.find_value("api_rate_limits").is_err() {
+            return Ok(limits);
+        }
+        let raw: HashMap<String, u64> = figment
+            .extract_inner("api_rate_limits")
+            .map_err(|e| format!("invalid `api_rate_limits` config: {}", e))?;
+        for (key, value) in raw {
+            match key.as_str() {
+                "window_secs" if value == 0 => return Err("api_rate_limits.window_secs must be above 0".to_string()),
+                "window_secs" => limits.window_secs = value,
+                scope if SCOPES.contains(&scope) => {
+                    let budget = u32::try_from(value).map_err(|_| format!("api_rate_limits.{} is too large", scope))?;
+                    limits.budgets.insert(key, budget);
+                }
+                other => {
+                    return Err(format!(
+                        "unknown api_rate_limits key `{}`; expected window_secs or one of {}",
+                        other,
+                        SCOPES.join(", ")
+                    ))
+                }
+            }
+        }
+        Ok(limits)
+    }
+}
+
+// (scope, caller)
+type WindowKey = (String, u64);
+
+// Shared by the guard, the header fairing and the sweep job; the config is
+// read at ignite.
+#[derive(Clone, Default)]
+pub struct ApiRateLimits {
+    config: Arc<RwLock<RateLimitConfig>>,
+    // -> (window start, requests in it)
+    windows: Arc<Mutex<HashMap<WindowKey, (u64, u32)>>>,
+}
+
+impl ApiRateLimits {
+    // Counts a request; None when the scope is unlimited.
+    fn hit(&self, scope: &str, default: u32, caller: u64, now: u64) -> Option<Usage> {
+        let config = self.config.read();
+        let limit = config.budgets.get(scope).copied().unwrap_or(default);
+        if limit == 0 {
+            return None;
+        }
+        let start = now - now % config.window_secs;
+        let mut windows = self.windows.lock();
+        let window = windows.entry((scope.to_string(), caller)).or_insert((start, 0));
+        if window.0 != start {
+            *window = (start, 0);
+        }
+        let over = window.1 >= limit;
+        if !over {
+            window.1 += 1;
+        }
+        Some(Usage {
+            limit,
+            remaining: limit - window.1,
+            reset: start + config.window_secs - now,
+            over,
+        })
+    }
+
+    // Forgets windows that have ended. Run by the scheduler.
+    pub fn sweep(&self) {
+        let now = now_secs();
+        let current = now - now % self.config.read().window_secs;
+        self.windows.lock().retain(|_, (start, _)| *start == current);
+    }
+
+    // Reads the config, and adds the headers to every response a limit was
+    // counted for, errors included.
+    pub fn fairing(&self) -> AdHoc {
+        let limits = self.clone();
+        AdHoc::on_ignite("API Rate Limits", |rocket| async move {
+            *limits.config.write() = RateLimitConfig::load(rocket.figment()).unwrap_or_default();
+            rocket.attach(AdHoc::on_response("API Rate Limit Headers", |req, res| {
+                Box::pin(async move {
+                    let Some(usage) = *req.local_cache(|| None::<Usage>) else {
+                        return;
+                    };
+                    res.set_header(Header::new("X-RateLimit-Limit", usage.limit.to_string()));
+                    res.set_header(Header::new("X-RateLimit-Remaining", usage.remaining.to_string()));
+                    res.set_header(Header::new("X-RateLimit-Reset", usage.reset.to_string()));
+                    if usage.over {
+                        res.set_header(Header::new("Retry-After", usage.reset.to_string()));
+                    }
+                })
+            }))
+        })
+    }
+}
+
+// Bearer token if there is one, else the client's IP; hashed so tokens
+// aren't kept in memory longer than the request.
+fn caller(req: &Request<'_>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match req.headers().get_one("Authorization").and_then(|h| h.strip_prefix("Bearer ")) {
+        Some(token) => ("token", token).hash(&mut hasher),
+        None => ("ip", req.client_ip()).hash(&mut hasher),
+    }
+    hasher.finish()
+}
+
+#[rocket::async_trait]
+impl<'r, S: Scope + Send + Sync + 'static> FromRequest<'r> for ApiLimit<S> {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let Some(limits) = req.rocket().state::<ApiRateLimits>() else {
+            return request::Outcome::Success(ApiLimit(PhantomData));
+        };
+        let Some(usage) = limits.hit(S::NAME, S::DEFAULT, caller(req), now_secs()) else {
+            return request::Outcome::Success(ApiLimit(PhantomData));
+        };
+        req.local_cache(|| Some(usage));
+        if usage.over {
+            let error = ApiError::new(
+                Status::TooManyRequests,
+                "rate_limited",
+                None,
+                format!("over {} `{}` requests this window; retry in {}s", usage.limit, S::NAME, usage.reset),
+            );
+            return request::Outcome::Error((error.stash(req), ()));
+        }
+        request::Outcome::Success(ApiLimit(PhantomData))
+    }
+}
+
+// --- Form limits ---
+// The create and join pages, and reactions, per IP, as token buckets:
+// `burst` requests straight away, then `per_minute` more each minute. Going
+// over gets the 429 page (with `Retry-After`) before the form is even read,
+// so a bot can't fill memory with rooms or walk through room codes.
+// `[default.form_rate_limits]` sets `create`, `join` and `react` as
+// `{ burst, per_minute }`; a burst of 0 turns one off. Routes opt in with a
+// `FormLimit<..>` guard, see routes.rs; the buckets live in AppState.
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Bucket {
+    pub burst: u32,
+    pub per_minute: u32,
+}
+
+const FORM_SCOPES: &[&str] = &["create", "join", "react"];
+
+#[derive(Clone, Debug)]
+pub struct FormRateConfig {
+    // scope -> bucket
+    buckets: HashMap<String, Bucket>,
+}
+
+impl Default for FormRateConfig {
+    fn default() -> Self {
+        let buckets = [
+            ("create", Bucket { burst: 5, per_minute: 2 }),
+            ("join", Bucket { burst: 10, per_minute: 6 }),
+            ("react", Bucket { burst: 10, per_minute: 30 }),
+        ];
+        FormRateConfig {
+            buckets: buckets.into_iter().map(|(scope, b)| (scope.to_string(), b)).collect(),
+        }
+    }
+}
+
+impl FormRateConfig {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let mut config = FormRateConfig::default();
+        if figment.find_value("form_rate_limits").is_err() {
+            return Ok(config);
+        }
+        let raw: HashMap<String, Bucket> = figment
+            .extract_inner("form_rate_limits")
+            .map_err(|e| format!("invalid `form_rate_limits` config: {}", e))?;
+        for (scope, bucket) in raw {
+            if !FORM_SCOPES.contains(&scope.as_str()) {
+                return Err(format!(
+                    "unknown form_rate_limits key `{}`; expected one of {}",
+                    scope,
+                    FORM_SCOPES.join(", ")
+                ));
+            }
+            if bucket.burst > 0 && bucket.per_minute == 0 {
+                return Err(format!("form_rate_limits.{}.per_minute must be above 0", scope));
+            }
+            config.buckets.insert(scope, bucket);
+        }
+        Ok(config)
+    }
+}
+
+// Seconds a refused form should wait; on the request for the header.
+#[derive(Clone, Copy, Debug)]
+struct RetryAfter(u64);
+
+#[derive(Clone, Default)]
+pub struct FormRateLimits {
+    config: Arc<RwLock<FormRateConfig>>,
+    // (scope, ip) -> (tokens left, when they were counted)
+    buckets: Arc<Mutex<HashMap<(String, IpAddr), (f64, Instant)>>>,
+}
+
+impl FormRateLimits {
+    // Spends a token; Err(seconds until the next one) when there is none.
+    pub fn take(&self, scope: &str, ip: IpAddr) -> Result<(), u64> {
+        self.take_at(scope, ip, Instant::now())
+    }
+
+    fn take_at(&self, scope: &str, ip: IpAddr, now: Instant) -> Result<(), u64> {
+        let Some(rule) = self.config.read().buckets.get(scope).copied().filter(|b| b.burst > 0) else {
+            return Ok(());
+        };
+        let per_sec = f64::from(rule.per_minute) / 60.0;
+        let mut buckets = self.buckets.lock();
+        let (tokens, at) = buckets
+            .entry((scope.to_string(), ip))
+            .or_insert((f64::from(rule.burst), now));
+        *tokens = (*tokens + now.duration_since(*at).as_secs_f64() * per_sec).min(f64::from(rule.burst));
+        *at = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            return Ok(());
+        }
+        Err(((1.0 - *tokens) / per_sec).ceil() as u64)
+    }
+
+    // Forgets buckets that have filled back up. Run by the scheduler.
+    pub fn sweep(&self) {
+        let config = self.config.read();
+        let now = Instant::now();
+        self.buckets.lock().retain(|(scope, _), (tokens, at)| {
+            config.buckets.get(scope).is_some_and(|rule| {
+                let refilled = *tokens + now.duration_since(*at).as_secs_f64() * f64::from(rule.per_minute) / 60.0;
+                refilled < f64::from(rule.burst)
+            })
+        });
+    }
+
+    // Reads the config, and adds `Retry-After` to refused forms.
+    pub fn fairing(&self) -> AdHoc {
+        let limits = self.clone();
+        AdHoc::on_ignite("Form Rate Limits", |rocket| async move {
+            *limits.config.write() = FormRateConfig::load(rocket.figment()).unwrap_or_default();
+            rocket.attach(AdHoc::on_response("Form Rate Limit Headers", |req, res| {
+                Box::pin(async move {
+                    if let Some(RetryAfter(secs)) = *req.local_cache(|| None::<RetryAfter>) {
+                        res.set_header(Header::new("Retry-After", secs.to_string()));
+                    }
+                })
+            }))
+        })
+    }
+}
+
+// What a `FormLimit` guard does with the buckets it found: Ok to go ahead,
+// or the 429 that sends the browser to the error page.
+pub fn check_form<S: Scope>(req: &Request<'_>, limits: &FormRateLimits) -> Result<(), Status> {
+    let Some(ip) = req.client_ip() else {
+        return Ok(());
+    };
+    limits.take(S::NAME, ip).map_err(|secs| {
+        req.local_cache(|| Some(RetryAfter(secs)));
+        Status::TooManyRequests
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    const ADA: u64 = 1;
+    const BEN: u64 = 2;
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    fn api(window_secs: u64, answer: u32) -> ApiRateLimits {
+        let config = RateLimitConfig {
+            window_secs,
+            budgets: HashMap::from([("answer".to_string(), answer)]),
+        };
+        ApiRateLimits {
+            config: Arc::new(RwLock::new(config)),
+            ..Default::default()
+        }
+    }
+
+    // (remaining, reset, over) of one answer request at `now`
+    fn answer(limits: &ApiRateLimits, caller: u64, now: u64) -> (u32, u64, bool) {
+        let usage = limits.hit("answer", 120, caller, now).expect("answers are limited");
+        (usage.remaining, usage.reset, usage.over)
+    }
+
+    #[test]
+    fn a_window_allows_its_budget_then_refuses_until_it_ends() {
+        let limits = api(60, 3);
+        assert_eq!(answer(&limits, ADA, 120), (2, 60, false));
+        assert_eq!(answer(&limits, ADA, 130), (1, 50, false));
+        assert_eq!(answer(&limits, ADA, 140), (0, 40, false));
+        assert_eq!(answer(&limits, ADA, 150), (0, 30, true));
+        // the window's last second is still over
+        assert_eq!(answer(&limits, ADA, 179), (0, 1, true));
+        // and the next one starts full
+        assert_eq!(answer(&limits, ADA, 180), (2, 60, false));
+    }
+
+    #[test]
+    fn a_burst_either_side_of_the_edge_gets_two_budgets() {
+        let limits = api(60, 3);
+        for _ in 0..3 {
+            assert!(!answer(&limits, ADA, 179).2);
+        }
+        assert!(answer(&limits, ADA, 179).2);
+        for _ in 0..3 {
+            assert!(!answer(&limits, ADA, 180).2);
+        }
+        assert!(answer(&limits, ADA, 180).2);
+    }
+
+    #[test]
+    fn callers_and_unlimited_scopes_are_counted_apart() {
+        let limits = api(60, 1);
+        assert!(!answer(&limits, ADA, 0).2);
+        assert!(answer(&limits, ADA, 1).2);
+        assert!(!answer(&limits, BEN, 1).2);
+        assert!(api(60, 0).hit("answer", 120, ADA, 0).is_none());
+    }
+
+    fn forms(burst: u32, per_minute: u32) -> FormRateLimits {
+        let config = FormRateConfig {
+            buckets: HashMap::from([("join".to_string(), Bucket { burst, per_minute })]),
+        };
+        FormRateLimits {
+            config: Arc::new(RwLock::new(config)),
+            ..Default::default()
+        }
+    }
+
+
+    #[test]
+    fn a_bucket_spends_its_burst_then_refills_a_token_at_a_time() {
+        // a token every 2s
+        let limits = forms(2, 30);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        assert_eq!(limits.take_at("join", IP, at(0)), Ok(()));
+        assert_eq!(limits.take_at("join", IP, at(0)), Ok(()));
+        assert_eq!(limits.take_at("join", IP, at(0)), Err(2));
+        // half a token back
+        assert_eq!(limits.take_at("join", IP, at(1)), Err(1));
+        assert_eq!(limits.take_at("join", IP, at(2)), Ok(()));
+        assert_eq!(limits.take_at("join", IP, at(2)), Err(2));
+    }
+
+    #[test]
+    fn an_idle_bucket_refills_only_to_its_burst() {
+        let limits = forms(2, 30);
+        let start = Instant::now();
+        assert_eq!(limits.take_at("join", IP, start), Ok(()));
+        let later = start + Duration::from_secs(3600);
+        assert_eq!(limits.take_at("join", IP, later), Ok(()));
+        assert_eq!(limits.take_at("join", IP, later), Ok(()));
+        assert_eq!(limits.take_at("join", IP, later), Err(2));
+    }
+
+    #[test]
+    fn a_burst_of_zero_never_refuses() {
+        let limits = forms(0, 0);
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(limits.take_at("join", IP, now), Ok(()));
+        }
+    }
+}
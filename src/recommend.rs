@@ -0,0 +1,138 @@
+use rocket::serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::questions::Question;
+use crate::snapshots::{self, Snapshot, SnapshotStore};
+
+// "You two should talk about…": looks back over a couple's saved games for
+// the categories they agreed on least and suggests questions from them,
+// new ones first, then the ones they were furthest apart on. Feeds the
+// create page (`/create?from=<snapshot token>`) and the rematch deal.
+
+// a category needs this many answered questions before it says anything
+const MIN_ANSWERED: usize = 3;
+// categories this in sync or better aren't worth pointing out
+const IN_SYNC_PCT: u32 = 80;
+const MAX_SPOTS: usize = 2;
+const QUESTIONS_PER_SPOT: usize = 5;
+
+// --- Models ---
+#[derive(Debug, Default, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Recommendations {
+    // weakest first
+    pub spots: Vec<WeakSpot>,
+    pub games: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WeakSpot {
+    pub category: String,
+    // average agreement across their games
+    pub pct: u32,
+    pub answered: usize,
+    pub message: String,
+    pub questions: Vec<Suggestion>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Suggestion {
+    pub id: String,
+    pub text: String,
+    // asked before; None for questions they haven't seen
+    pub last_pct: Option<u32>,
+}
+
+impl Recommendations {
+    pub fn categories(&self) -> Vec<String> {
+        self.spots.iter().map(|s| s.category.clone()).collect()
+    }
+
+    // Every suggested question id, weakest category first.
+    pub fn question_ids(&self) -> Vec<String> {
+        self.spots.iter().flat_map(|s| s.questions.iter().map(|q| q.id.clone())).collect()
+    }
+}
+
+// --- Building ---
+// `pool` is what the couple could be dealt next time.
+pub fn recommend<'a>(history: &[Snapshot], pool: impl Iterator<Item = &'a Question>) -> Recommendations {
+    // category -> (credit sum, answered); question id -> latest credit
+    let mut by_category: BTreeMap<&str, (u32, usize)> = BTreeMap::new();
+    let mut last: HashMap<&str, u32> = HashMap::new();
+    for game in history {
+        for q in &game.transcript.questions {
+            if let Some(pct) = q.credit_pct {
+                let e = by_category.entry(&q.category).or_default();
+                e.0 += pct;
+                e.1 += 1;
+                last.insert(&q.question_id, pct);
+            }
+        }
+    }
+    let mut weak: Vec<(&str, u32, usize)> = by_category
+        .into_iter()
+        .filter(|(_, (_, n))| *n >= MIN_ANSWERED)
+        .map(|(c, (sum, n))| (c, sum / n as u32, n))
+        .filter(|(_, pct, _)| *pct < IN_SYNC_PCT)
+        .collect();
+    weak.sort_by_key(|(c, pct, _)| (*pct, *c));
+    weak.truncate(MAX_SPOTS);
+
+    let pool: Vec<&Question> = pool.collect();
+    let spots = weak
+        .into_iter()
+        .map(|(category, pct, answered)| {
+            let mut questions: Vec<Suggestion> = pool
+                .iter()
+                .filter(|q| q.category == category)
+                .map(|q| Suggestion {
+                    id: q.id.clone(),
+                    text: q.text.clone(),
+                    last_pct: last.get(q.id.as_str()).copied(),
+                })
+                .collect();
+            // unseen first, then the ones furthest apart
+            questions.sort_by_key(|q| (q.last_pct.is_some(), q.last_pct));
+            questions.truncate(QUESTIONS_PER_SPOT);
+            WeakSpot {
+                category: category.to_string(),
+                pct,
+                answered,
+                message: format!(
+                    "You two were {}% in sync on {} questions — worth talking about next time.",
+                    pct, category
+                ),
+                questions,
+            }
+        })
+        .collect();
+    Recommendations {
+        spots,
+        games: history.len(),
+    }
+}
+
+// --- Service ---
+#[derive(Clone)]
+pub struct Recommender {
+    snapshots: SnapshotStore,
+}
+
+impl Recommender {
+    pub fn new(snapshots: SnapshotStore) -> Self {
+        Recommender { snapshots }
+    }
+
+    pub fn for_players<'a>(&self, players: &[&str], pool: impl Iterator<Item = &'a Question>) -> Recommendations {
+        recommend(&self.snapshots.for_couple(players), pool)
+    }
+
+    // The couple behind a snapshot token; None if the token is unknown.
+    pub fn for_token<'a>(&self, token: &str, pool: impl Iterator<Item = &'a Question>) -> Option<Recommendations> {
+        let snapshot = self.snapshots.get(token)?;
+        Some(self.for_players(&snapshots::couple(&snapshot.transcript), pool))
+    }
+}
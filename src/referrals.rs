@@ -0,0 +1,59 @@
+use rocket::http::{Cookie, SameSite};
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::Serialize;
+use rocket::time::Duration;
+
+// Where players come from. `?ref=<code>` on the landing or join page is kept
+// in a cookie until the visitor creates or joins a room, and stored on the
+// room and player then; /admin/referrals adds it up per code.
+
+const COOKIE: &str = "ref";
+const COOKIE_DAYS: i64 = 30;
+const MAX_LEN: usize = 40;
+
+// --- Models ---
+#[derive(Debug, Default, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ReferralRow {
+    pub code: String,
+    // rooms created under this code
+    pub rooms: usize,
+    // of those, played to the end
+    pub finished: usize,
+    // everyone who created or joined a room under it
+    pub players: usize,
+}
+
+// Lowercase letters, digits, dashes and underscores; anything else is dropped.
+fn normalize(raw: &str) -> Option<String> {
+    let code = raw.trim().to_lowercase();
+    let ok = (1..=MAX_LEN).contains(&code.len())
+        && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    ok.then_some(code)
+}
+
+// --- Guard ---
+// The visitor's referral code: from `?ref=` (which also renews the cookie),
+// else from the cookie.
+pub struct Referral(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Referral {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let from_query = req
+            .query_value::<&str>("ref")
+            .and_then(Result::ok)
+            .and_then(normalize);
+        if let Some(code) = &from_query {
+            let mut cookie = Cookie::new(COOKIE, code.clone());
+            cookie.set_path("/");
+            cookie.set_same_site(SameSite::Lax);
+            cookie.set_max_age(Duration::days(COOKIE_DAYS));
+            req.cookies().add(cookie);
+        }
+        let code = from_query.or_else(|| req.cookies().get(COOKIE).and_then(|c| normalize(c.value())));
+        request::Outcome::Success(Referral(code))
+    }
+}
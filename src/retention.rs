@@ -0,0 +1,179 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::serde::Serialize;
+use std::sync::Arc;
+
+use crate::audit::AuditLog;
+use crate::routes::AppState;
+use crate::scheduler::now_secs;
+use crate::snapshots::SnapshotStore;
+
+// How long things are kept. Each policy is an age: rooms idle for
+// `room_retention_days`, results (snapshots) older than
+// `result_retention_months`, audit entries older than `audit_retention_days`.
+// A daily job deletes whatever is past its policy; /admin/retention shows
+// what the next run would delete, without deleting it. Unset room and result
+// policies keep everything, as before. Rooms can instead expire in minutes
+// (`room_ttl_minutes`), swept every few minutes rather than daily.
+
+const MINUTE_SECS: u64 = 60;
+const DAY_SECS: u64 = 24 * 60 * 60;
+const MONTH_DAYS: u64 = 30;
+const DEFAULT_AUDIT_DAYS: u64 = 90;
+
+// --- Models ---
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Policy {
+    pub room_days: Option<u64>,
+    pub room_ttl_minutes: Option<u64>,
+    pub result_months: Option<u64>,
+    pub audit_days: u64,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            room_days: None,
+            room_ttl_minutes: None,
+            result_months: None,
+            audit_days: DEFAULT_AUDIT_DAYS,
+        }
+    }
+}
+
+impl Policy {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let positive = |key: &str| -> Result<Option<u64>, String> {
+            if figment.find_value(key).is_err() {
+                return Ok(None);
+            }
+            match figment.extract_inner::<u64>(key) {
+                Ok(0) => Err(format!("`{}` must be at least 1", key)),
+                Ok(n) => Ok(Some(n)),
+                Err(e) => Err(format!("invalid `{}` config: {}", key, e)),
+            }
+        };
+        let room_days = positive("room_retention_days")?;
+        let room_ttl_minutes = positive("room_ttl_minutes")?;
+        if room_days.is_some() && room_ttl_minutes.is_some() {
+            return Err("set `room_ttl_minutes` or `room_retention_days`, not both".to_string());
+        }
+        Ok(Policy {
+            room_days,
+            room_ttl_minutes,
+            result_months: positive("result_retention_months")?,
+            audit_days: positive("audit_retention_days")?.unwrap_or(DEFAULT_AUDIT_DAYS),
+        })
+    }
+
+    // How long a room may sit idle, whichever way it was configured.
+    pub fn room_idle_secs(&self) -> Option<u64> {
+        self.room_ttl_minutes
+            .map(|m| m * MINUTE_SECS)
+            .or(self.room_days.map(|d| d * DAY_SECS))
+    }
+}
+
+// What one run deleted, or would delete on a dry run.
+#[derive(Debug, Default, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    // room codes
+    pub rooms: Vec<String>,
+    // snapshot tokens
+    pub results: Vec<String>,
+    pub audit_entries: usize,
+}
+
+// Running totals since launch, for /admin/retention.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PurgeTotals {
+    pub runs: u64,
+    pub last_run_at: Option<u64>,
+    pub rooms: u64,
+    pub results: u64,
+    pub audit_entries: u64,
+}
+
+// --- Purge ---
+#[derive(Clone)]
+pub struct Retention {
+    policy: Arc<RwLock<Policy>>,
+    totals: Arc<RwLock<PurgeTotals>>,
+    state: AppState,
+    snapshots: SnapshotStore,
+    audit: AuditLog,
+}
+
+impl Retention {
+    pub fn new(state: AppState, snapshots: SnapshotStore, audit: AuditLog) -> Self {
+        Retention {
+            policy: Arc::default(),
+            totals: Arc::default(),
+            state,
+            snapshots,
+            audit,
+        }
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy.read().clone()
+    }
+
+    pub fn totals(&self) -> PurgeTotals {
+        self.totals.read().clone()
+    }
+
+    // Applies every policy as of now. Called daily from the scheduler, and
+    // with `dry_run` from the admin preview.
+    pub fn run(&self, dry_run: bool) -> PurgeReport {
+        let policy = self.policy();
+        let now = now_secs();
+        let before = |days: u64| now.saturating_sub(days * DAY_SECS);
+        let report = PurgeReport {
+            dry_run,
+            rooms: policy
+                .room_idle_secs()
+                .map(|idle| self.state.purge_rooms(now.saturating_sub(idle), dry_run))
+                .unwrap_or_default(),
+            results: policy
+                .result_months
+                .map(|months| self.snapshots.purge(before(months * MONTH_DAYS), dry_run))
+                .unwrap_or_default(),
+            audit_entries: self.audit.prune(before(policy.audit_days), dry_run),
+        };
+        if !dry_run {
+            let mut totals = self.totals.write();
+            totals.runs += 1;
+            totals.last_run_at = Some(now);
+            totals.rooms += report.rooms.len() as u64;
+            totals.results += report.results.len() as u64;
+            totals.audit_entries += report.audit_entries as u64;
+        }
+        report
+    }
+
+    // Just the room policy, for the frequent expiry sweep. Returns how many
+    // rooms went.
+    pub fn expire_rooms(&self) -> usize {
+        let Some(idle) = self.policy().room_idle_secs() else { return 0 };
+        let now = now_secs();
+        let purged = self.state.purge_rooms(now.saturating_sub(idle), false).len();
+        let mut totals = self.totals.write();
+        totals.last_run_at = Some(now);
+        totals.rooms += purged as u64;
+        purged
+    }
+
+    pub fn fairing(&self) -> AdHoc {
+        let retention = self.clone();
+        AdHoc::on_ignite("Data Retention", |rocket| async move {
+            *retention.policy.write() = Policy::load(rocket.figment()).unwrap_or_default();
+            rocket.manage(retention)
+        })
+    }
+}
@@ -0,0 +1,186 @@
+use parking_lot::RwLock;
+use rocket::serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::snapshots::{Snapshot, SnapshotStore};
+
+// "Year of Us": everything a couple played in one calendar year (UTC),
+// built from their result snapshots. Reports are cached until a new
+// snapshot lands, since a year of transcripts is a lot to re-walk per view.
+
+// --- Models ---
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct YearReview {
+    pub year: i64,
+    pub games: usize,
+    pub questions_answered: usize,
+    pub average_score: u32,
+    pub best: Option<BestGame>,
+    pub most_disputed: Option<Disputed>,
+    pub dimensions: Vec<DimensionTrend>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BestGame {
+    pub code: String,
+    pub score: u32,
+    pub imported: bool,
+}
+
+// The question you agreed on least, averaged over every time it came up.
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Disputed {
+    pub question: String,
+    pub times_asked: usize,
+    pub average_pct: u32,
+}
+
+// One category across the year: where it started, where it ended up.
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DimensionTrend {
+    pub category: String,
+    pub first_pct: u32,
+    pub last_pct: u32,
+    pub change: i32,
+    pub average_pct: u32,
+    // one point per game that asked the category, in play order
+    pub points: Vec<u32>,
+}
+
+pub fn year_review(year: i64, games: &[&Snapshot]) -> YearReview {
+    let scores: Vec<u32> = games.iter().map(|s| s.transcript.score).collect();
+    let best = games
+        .iter()
+        .max_by_key(|s| s.transcript.score)
+        .map(|s| BestGame {
+            code: s.transcript.code.clone(),
+            score: s.transcript.score,
+            imported: s.imported,
+        });
+
+    // question id -> (text, credits)
+    let mut asked: HashMap<&str, (&str, Vec<u32>)> = HashMap::new();
+    let mut per_category: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+    for s in games {
+        let mut in_game: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+        for q in &s.transcript.questions {
+            let Some(pct) = q.credit_pct else { continue };
+            asked.entry(&q.question_id).or_insert((&q.question, Vec::new())).1.push(pct);
+            let e = in_game.entry(&q.category).or_default();
+            e.0 += pct;
+            e.1 += 1;
+        }
+        for (category, (sum, n)) in in_game {
+            per_category.entry(category).or_default().push(sum / n);
+        }
+    }
+
+    let most_disputed = asked
+        .values()
+        .map(|(text, credits)| Disputed {
+            question: text.to_string(),
+            times_asked: credits.len(),
+            average_pct: average(credits),
+        })
+        // lowest agreement first; the more often it split you, the worse
+        .min_by(|a, b| a.average_pct.cmp(&b.average_pct).then(b.times_asked.cmp(&a.times_asked)))
+        .filter(|d| d.average_pct < 100);
+
+    let dimensions = per_category
+        .into_iter()
+        .map(|(category, points)| {
+            let first_pct = points[0];
+            let last_pct = points[points.len() - 1];
+            DimensionTrend {
+                category: category.to_string(),
+                first_pct,
+                last_pct,
+                change: last_pct as i32 - first_pct as i32,
+                average_pct: average(&points),
+                points,
+            }
+        })
+        .collect();
+
+    YearReview {
+        year,
+        games: games.len(),
+        questions_answered: asked.values().map(|(_, c)| c.len()).sum(),
+        average_score: average(&scores),
+        best,
+        most_disputed,
+        dimensions,
+    }
+}
+
+fn average(values: &[u32]) -> u32 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.iter().sum::<u32>() / values.len() as u32
+}
+
+// Calendar year of a unix timestamp (civil-from-days, Howard Hinnant).
+pub fn year_of(secs: u64) -> i64 {
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    yoe + era * 400 + i64::from(month <= 2)
+}
+
+// --- Service ---
+type CacheKey = (Vec<String>, i64);
+// snapshot generation it was built from, and the report
+type CacheEntry = (u64, Arc<YearReview>);
+
+#[derive(Clone)]
+pub struct ReviewService {
+    snapshots: SnapshotStore,
+    // (couple, year) -> report
+    cache: Arc<RwLock<HashMap<CacheKey, CacheEntry>>>,
+}
+
+impl ReviewService {
+    pub fn new(snapshots: SnapshotStore) -> Self {
+        ReviewService {
+            snapshots,
+            cache: Arc::default(),
+        }
+    }
+
+    // The couple behind `token` and their review for `year`; None if the
+    // token is unknown or they played nothing that year.
+    pub fn for_token(&self, token: &str, year: i64) -> Option<Arc<YearReview>> {
+        let snapshot = self.snapshots.get(token)?;
+        let couple: Vec<String> = crate::snapshots::couple(&snapshot.transcript)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let key = (couple, year);
+        let generation = self.snapshots.generation();
+        if let Some((built, review)) = self.cache.read().get(&key) {
+            if *built == generation {
+                return Some(review.clone());
+            }
+        }
+
+        let ids: Vec<&str> = key.0.iter().map(String::as_str).collect();
+        let history = self.snapshots.for_couple(&ids);
+        let games: Vec<&Snapshot> = history.iter().filter(|s| year_of(s.taken_at) == year).collect();
+        if games.is_empty() {
+            return None;
+        }
+        let review = Arc::new(year_review(year, &games));
+        self.cache.write().insert(key, (generation, review.clone()));
+        Some(review)
+    }
+}
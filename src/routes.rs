@@ -1,32 +1,355 @@
+use async_graphql_rocket::{GraphQLRequest, GraphQLResponse};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
-use rand::{distributions::Alphanumeric, Rng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use rocket::form::Form;
+use rocket::data::Capped;
+use rocket::fs::TempFile;
+use rocket::http::uri::Host;
+use rocket::http::{ContentType, CookieJar};
 use rocket::http::Status;
-use rocket::response::Redirect;
+use rocket::response::stream::{Event, EventStream};
+use rocket::response::{self, status, Redirect, Responder};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::{self, error::RecvError};
+use rocket::fairing::AdHoc;
+use rocket::futures::StreamExt;
+use rocket::either::Either;
+use rocket::request::{self, FromRequest};
+use rocket::{Request, Shutdown};
+use rocket::serde::json::{self, json, Json, Map, Value};
 use rocket::serde::{Deserialize, Serialize};
 use rocket::State;
-use rocket_dyn_templates::{context, Template};
-use std::collections::HashMap;
+use rocket_dyn_templates::{context, Metadata, Template};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
+use crate::announcements::Announcements;
+use crate::analytics::{self, ExperimentStats, PlayerLatency, QuestionTiming};
+use crate::api::{ApiError, Validate, Validated};
+use crate::conditional::{Conditional, Fresh, Stamp};
+use crate::custom;
+use crate::dashboard::{DashboardStats, GameTally, MemoryUsage, RoomDetail, RoomRow, SeatRow};
+use crate::delta::{MergePatch, ViewHistory};
+use crate::negotiate::{ApiBody, Negotiated};
+use crate::assets::AssetStore;
+use crate::audit::AuditLog;
+use crate::calibration::{self, Calibration};
+use crate::codes::{CodeScheme, RoomCode};
+use crate::leaderboard::LeaderboardService;
+use crate::lightning::{self, LightningRound};
+use crate::i18n::{self, AcceptLanguage};
+use crate::identity::{self, CurrentPlayer};
+use crate::invite;
+use crate::experiments::Variants;
+use crate::export::{self, Download, Transcript, TranscriptAnswer, TranscriptPlayer, TranscriptQuestion};
+use crate::features::{self, Features};
+use crate::footprint::{self, ListSize};
+use crate::errors;
+use crate::events::{Cue, LoggedEvent, RevealedAnswer, RoomEvent, WagerOutcome};
+use crate::history::{GameHistory, GameSummary};
+use crate::graphql::{GameSchema, Games, GamesData, PlayerView, QuestionResultView, QuestionView, ResultView, RoomView};
+use crate::notify::{Notifier, NotifyPrefs};
+use crate::outbox::{Outbox, WebhookSender};
+use crate::ratelimit::{self, scope, ApiLimit, ApiRateLimits, FormRateLimits, Scope};
+use crate::questions::{Bank, Question, QuestionBank, QuestionKind, QuestionStore};
+use crate::recommend::Recommender;
+use crate::scheduler::{now_secs, Schedule, Scheduler};
+use crate::scoring::{self, CategoryWeights, GameMode, Level, Pairwise};
+use crate::sealed::{same_secret, CookieKeys, MessageKey, Sealed};
+use crate::sensitive::Sensitive;
+use crate::review::{self, ReviewService};
+use crate::series::{self, Series};
+use crate::content::{AgeGate, ContentRules, Restrictions};
+use crate::presets::Presets;
+use crate::referrals::{ReferralRow, Referral};
+use crate::retention::Retention;
+use crate::site::Site;
+use crate::snapshots::{self, CompareError, SnapshotStore};
+use crate::storage::{RoomStorage, RoomsDb, ShutdownSnapshot, StorageKind};
+use crate::story::{Story, StoryProgress};
+use crate::surprise::{self, Surprise};
+use crate::tenants::Tenant;
+use crate::timer::QuestionTimer;
+use crate::translate::Translations;
+use crate::verdicts::Verdicts;
+use crate::wordcloud::WordCloudService;
+
+mod flow;
+#[cfg(test)]
+mod tests;
+
 // --- Templates attachment ---
 pub fn build_rocket() -> rocket::Rocket<rocket::Build> {
+    let scheduler = Scheduler::default();
+
+    let outbox = Outbox::default();
+    outbox.register_sender("webhook", WebhookSender::default());
+    let translations = Translations::default();
+    let notifier = Notifier::new(outbox.clone());
+    let questions = QuestionStore::new(QuestionBank::builtin());
+    let snapshots = SnapshotStore::default();
+    let audit = AuditLog::default();
+    let reviews = ReviewService::new(snapshots.clone());
+    let recommender = Recommender::new(snapshots.clone());
+    let api_limits = ApiRateLimits::default();
+    let history = GameHistory::default();
+    let announcements = Announcements::default();
+    let word_cloud = WordCloudService::new(snapshots.clone(), questions.clone());
+    let leaderboard = LeaderboardService::new(snapshots.clone());
+    {
+        let outbox = outbox.clone();
+        scheduler.register("outbox", Schedule::Every(Duration::from_secs(15)), move || {
+            let outbox = outbox.clone();
+            async move { outbox.drain().await }
+        });
+    }
+    {
+        let rooms = APP_STATE.rooms.clone();
+        let questions = questions.clone();
+        scheduler.register("presence", Schedule::Every(Duration::from_secs(5)), move || {
+            let now = now_secs();
+            let bank = questions.snapshot();
+            for room in rooms.write().values_mut() {
+                room.sweep_presence(now);
+                room.expire_question(now, &bank);
+                room.settle_lightning(now);
+            }
+            async { Ok(()) }
+        });
+    }
+
+    {
+        let word_cloud = word_cloud.clone();
+        scheduler.register("word cloud", Schedule::Every(Duration::from_secs(60 * 60)), move || {
+            word_cloud.refresh();
+            async { Ok(()) }
+        });
+    }
+    {
+        let api_limits = api_limits.clone();
+        scheduler.register("rate limit sweep", Schedule::Every(Duration::from_secs(5 * 60)), move || {
+            api_limits.sweep();
+            async { Ok(()) }
+        });
+    }
+    {
+        let form_limits = APP_STATE.form_limits.clone();
+        scheduler.register("form limit sweep", Schedule::Every(Duration::from_secs(5 * 60)), move || {
+            form_limits.sweep();
+            async { Ok(()) }
+        });
+    }
+    {
+        let leaderboard = leaderboard.clone();
+        scheduler.register("leaderboard", Schedule::Every(Duration::from_secs(60 * 60)), move || {
+            leaderboard.refresh();
+            async { Ok(()) }
+        });
+    }
+
+    let room_storage = RoomStorage::default();
+    {
+        let room_storage = room_storage.clone();
+        scheduler.register("room store", Schedule::Every(Duration::from_secs(10)), move || {
+            let room_storage = room_storage.clone();
+            let rooms = APP_STATE.stored_rooms();
+            async move { room_storage.save(rooms).await }
+        });
+    }
+
+    let retention = Retention::new(APP_STATE.clone(), snapshots.clone(), audit.clone());
+    {
+        let retention = retention.clone();
+        scheduler.register("retention", Schedule::DailyAt { hour: 3, minute: 30 }, move || {
+            retention.run(false);
+            async { Ok(()) }
+        });
+    }
+    {
+        let retention = retention.clone();
+        scheduler.register("room expiry", Schedule::Every(Duration::from_secs(5 * 60)), move || {
+            retention.expire_rooms();
+            async { Ok(()) }
+        });
+    }
+
+    {
+        let rooms = APP_STATE.rooms.clone();
+        let notifier = notifier.clone();
+        let history = history.clone();
+        scheduler.register("summaries", Schedule::Every(Duration::from_secs(30)), move || {
+            for room in rooms.write().values_mut().filter(|r| r.finished && !r.summary_sent) {
+                let score = room.compatibility();
+                let answered = room.completed_answers().count();
+                let finished_at = room.finished_at().unwrap_or_else(now_secs);
+                for p in &room.players {
+                    notifier.game_summary(&room.code, &p.notify, score, answered);
+                    history.record(
+                        &p.id,
+                        GameSummary {
+                            code: room.code.clone(),
+                            finished_at,
+                            score,
+                            partners: room
+                                .players
+                                .iter()
+                                .filter(|o| o.id != p.id)
+                                .map(|o| o.name.expose().clone())
+                                .collect(),
+                            mode: room.mode().to_string(),
+                        },
+                    );
+                }
+                room.summary_sent = true;
+            }
+            async { Ok(()) }
+        });
+    }
+
     rocket::build()
         .manage(APP_STATE.clone())
-        .attach(rocket_dyn_templates::Template::fairing())
+        .manage(questions.clone())
+        .manage(snapshots.clone())
+        .manage(history.clone())
+        .manage(announcements.clone())
+        .manage(audit.clone())
+        .manage(reviews)
+        .manage(recommender)
+        .manage(api_limits.clone())
+        .manage(word_cloud.clone())
+        .manage(leaderboard.clone())
+        .manage(AssetStore::scan(crate::checks::PUBLIC_DIR))
+        .manage(scheduler.clone())
+        .manage(outbox.clone())
+        .manage(translations.clone())
+        .manage(notifier.clone())
+        .manage(ExperimentStats::default())
+        .manage(crate::graphql::schema())
+        .manage(ViewHistory::default())
+        .attach(crate::checks::fairing())
+        .attach(features::fairing())
+        .attach(crate::telemetry::RequestLog)
+        .attach(crate::conditional::fairing())
+        .attach(Template::custom({
+            let announcements = announcements.clone();
+            move |engines| engines.tera.register_function("announcement", announcements.template_function())
+        }))
+        .attach(announcements.fairing())
+        .attach(questions.fairing())
+        .attach(room_storage_fairing(room_storage))
+        .attach(snapshots.fairing())
+        .attach(history.fairing())
+        .attach(word_cloud.fairing())
+        .attach(leaderboard.fairing())
+        .attach(api_limits.fairing())
+        .attach(APP_STATE.form_limits.fairing())
+        .attach(crate::verdicts::fairing())
+        .attach(crate::tenants::fairing())
+        .attach(crate::site::fairing())
+        .attach(crate::content::fairing())
+        .attach(crate::codes::fairing())
+        .attach(crate::scoring::fairing())
+        .attach(crate::experiments::fairing())
+        .attach(crate::admin::fairing())
+        .attach(crate::story::fairing())
+        .attach(crate::sealed::fairing())
+        .attach(crate::sealed::cookie_fairing())
+        .attach(outbox.fairing())
+        .attach(audit.fairing())
+        .attach(retention.fairing())
+        .attach(translations.fairing())
+        .attach(notifier.fairing())
+        .attach(scheduler.fairing())
         .mount(
             "/",
             routes![
                 index,
                 create_room_get,
+                create_preview_get,
                 create_room_post,
+                custom_questions_post,
                 join_room_get,
                 join_room_post,
+                invite_get,
+                invite_qr_get,
+                events_get,
+                pending_get,
+                leave_post,
+                handoff_post,
+                claim_get,
+                claim_post,
+                approve_post,
+                start_post,
+                kick_post,
+                close_post,
+                consent_get,
+                consent_post,
                 play_get,
-                result_get
+                flow::answer_post,
+                flow::rank_post,
+                flow::pick_post,
+                flow::rate_post,
+                finish_post,
+                lightning_get,
+                lightning_post,
+                score_get,
+                heartbeat_post,
+                extend_post,
+                react_post,
+                locale_post,
+                settings_get,
+                settings_post,
+                adjust_post,
+                calibrate_get,
+                calibrate_post,
+                story_get,
+                story_post,
+                result_get,
+                export_get,
+                surprise_photo_get,
+                rematch_post,
+                continue_post,
+                replay_post,
+                series_get,
+                compare_get,
+                compare_api,
+                words_get,
+                words_api,
+                leaderboard_api,
+                api_create_room,
+                api_room_get,
+                api_join_room,
+                api_answer,
+                api_result,
+                api_capabilities,
+                graphql_post,
+                graphql_stream,
+                crate::graphql::playground,
+                review_get,
+                sitemap_get,
+                healthz,
+                readyz
             ],
         )
+        .mount("/", crate::content::routes())
+        .mount("/", crate::presets::routes())
+        .mount("/", crate::history::routes())
+        .mount("/assets", crate::assets::routes())
+        .mount("/admin", crate::admin::routes())
+        .mount("/api/v1/admin", crate::admin::api_routes())
+        .register("/", crate::limits::catchers())
+        .register("/", crate::errors::catchers())
+        .register("/api", crate::api::catchers())
+        // JSON, though it sits with the admin pages
+        .register("/admin/import", crate::api::catchers())
 }
 
 // --- Models ---
@@ -34,8 +357,54 @@ pub fn build_rocket() -> rocket::Rocket<rocket::Build> {
 #[serde(crate = "rocket::serde")]
 struct Player {
     id: String,
-    name: String,
+    name: Sensitive<String>,
     score: u32,
+    // unix secs of the last heartbeat / action from this player
+    #[serde(default)]
+    last_seen: u64,
+    // preferred language; None = follow the browser
+    #[serde(default)]
+    locale: Option<String>,
+    #[serde(default)]
+    notify: NotifyPrefs,
+    // `?ref=` code they arrived with, if any
+    #[serde(default)]
+    referral: Option<String>,
+    // unix secs they agreed to mature questions; only asked in mature rooms
+    #[serde(default)]
+    consented_at: Option<u64>,
+    // the device cookie must carry this; None = no device has claimed them
+    // yet (a rematch seat), which the result page's "continue" does
+    #[serde(default)]
+    session: Option<String>,
+    // open "continue on another device" code, if any
+    #[serde(default)]
+    handoff: Option<Handoff>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct Handoff {
+    code: String,
+    expires_at: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct Answer {
+    // one entry per blank; plain questions have exactly one
+    items: Sensitive<Vec<String>>,
+    // points staked on matching (wager rooms only)
+    #[serde(default)]
+    wager: u32,
+    #[serde(default)]
+    answered_at: u64,
+    // form it came from; a resend of the same one changes nothing
+    #[serde(default)]
+    submission: Option<String>,
+    // the clock ran out before they answered; `items` is empty
+    #[serde(default)]
+    timed_out: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -43,165 +412,4737 @@ struct Player {
 struct Room {
     code: String,
     players: Vec<Player>,
+    // couple, or a group game scored pair by pair
+    #[serde(default)]
+    game_mode: GameMode,
+    // seats; a couple's two, or up to scoring::MAX_GROUP_SIZE for groups
+    #[serde(default = "couple_size")]
+    capacity: usize,
+    // play waits for the host's start instead of beginning once full
+    #[serde(default)]
+    wait_for_host: bool,
     current_question_index: usize,
-    // later: store answers, challenge progress, etc.
+    // deployment tenant this room was created under; limits what it draws
+    #[serde(default)]
+    tenant: Option<String>,
+    // question ids in play order; endless rooms draw more as they go
+    questions: Vec<String>,
+    // one map per drawn question: player id -> answer
+    answers: Vec<HashMap<String, Answer>>,
+    // agreement per resolved question (0.0-1.0), in play order
+    #[serde(default)]
+    credits: Vec<f64>,
+    // category weight of each credit, as configured when it was earned
+    #[serde(default)]
+    credit_weights: Vec<f64>,
+    #[serde(default)]
+    category_weights: CategoryWeights,
+    // question index -> when it was put in front of both players
+    #[serde(default)]
+    asked_at: HashMap<usize, u64>,
+    // question index -> players who took extra time on it
+    #[serde(default)]
+    extensions: HashMap<usize, Vec<String>>,
+    endless: bool,
+    // running score visible during play; off keeps the result a surprise
+    show_score: bool,
+    // guess mode: players may bet points on each guess, double-or-nothing
+    wagers: bool,
+    timer: QuestionTimer,
+    // play a this-or-that lightning round after the last question
+    #[serde(default)]
+    lightning_enabled: bool,
+    #[serde(default)]
+    lightning: Option<LightningRound>,
+    // warm-up before the first question that tunes match_threshold
+    #[serde(default)]
+    calibration: Option<Calibration>,
+    // how similar free-text items must be to match; see scoring::credit
+    #[serde(default = "default_match_threshold")]
+    match_threshold: f64,
+    // story mode plays the date story instead of questions
+    #[serde(default)]
+    story: Option<StoryProgress>,
+    // co-op mode: a shared score target, optionally unlocking a host message
+    #[serde(default)]
+    goal: Option<CoopGoal>,
+    #[serde(default)]
+    surprise: Option<Surprise>,
+    // series this room belongs to, and the rematch that followed it
+    #[serde(default)]
+    series: Option<String>,
+    #[serde(default)]
+    rematch: Option<String>,
+    // end-of-game emails have been handed to the outbox
+    #[serde(default)]
+    summary_sent: bool,
+    // token of the result snapshot, taken once the game is over
+    #[serde(default)]
+    snapshot: Option<String>,
+    // sealed like the other host secrets
+    #[serde(default)]
+    passphrase: Option<Sealed>,
+    #[serde(default)]
+    join_guard: JoinGuard,
+    // host lets each joiner in by hand; until then they wait in `pending`
+    #[serde(default)]
+    approve_joins: bool,
+    #[serde(default)]
+    pending: Vec<Player>,
+    // full rooms put latecomers in line instead of turning them away; the
+    // first in line gets the next free seat
+    #[serde(default)]
+    queue_when_full: bool,
+    #[serde(default)]
+    queue: Vec<Player>,
+    // host opted in to the result being listed in the sitemap
+    #[serde(default)]
+    public_result: bool,
+    // `?ref=` code the host arrived with; rematches don't inherit it
+    #[serde(default)]
+    referral: Option<String>,
+    // host opted in to 18+ questions; dealt only once every player consented
+    #[serde(default)]
+    mature: bool,
+    // categories the host's region doesn't allow; never dealt here
+    #[serde(default)]
+    blocked_categories: Vec<String>,
+    // categories the host picked; empty = all of them
+    #[serde(default)]
+    categories: Vec<String>,
+    // question ids dealt ahead of the rest, from the recommender
+    #[serde(default)]
+    focus: Vec<String>,
+    // what this room's deals and draws are shuffled by (see shuffler)
+    #[serde(default = "shuffle_seed")]
+    seed: u64,
+    // bank ids the couple already had in earlier games of their series;
+    // only dealt again once nothing fresh is left
+    #[serde(default)]
+    played_before: Vec<String>,
+    // the host's own questions (see custom.rs), dealt in this room only
+    #[serde(default)]
+    custom_questions: Vec<Question>,
+    // questions per game, endless rooms aside
+    #[serde(default = "questions_per_game")]
+    question_count: usize,
+    // question id -> copy as dealt; later bank edits or retirements never
+    // change a game that already drew the question
+    #[serde(default)]
+    deck: HashMap<String, Question>,
+    // where the current pass over the bank started (endless rooms recycle)
+    deck_start: usize,
+    finished: bool,
+    // one-off message for the play page (e.g. deck reshuffled)
+    notice: Option<String>,
+    events: Vec<LoggedEvent>,
+    // unix secs; what room expiry goes by (see last_activity)
+    #[serde(default)]
+    created_at: u64,
+    #[serde(default)]
+    last_active: u64,
+    // pushes every logged event to open /events streams; made on first listen
+    #[serde(skip)]
+    live: Option<broadcast::Sender<LoggedEvent>>,
+    // first sighting of the current `version`, for Last-Modified
+    #[serde(skip)]
+    stamp: Stamp,
+    // estimated bytes held (see footprint.rs); recounted as events are logged
+    #[serde(skip)]
+    bytes: usize,
+    #[serde(skip)]
+    event_bytes: ListSize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct CoopGoal {
+    // compatibility % to reach together
+    target: u32,
+    message: Option<Sealed>,
+}
+
+// Wrong passphrases since the last good one or lockout.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct JoinGuard {
+    failures: u32,
+    locked_until: u64,
+}
+
+enum PassphraseCheck {
+    Ok,
+    Wrong,
+    // this miss tripped the lock
+    JustLocked,
+    Locked,
+    // sealed under a key this server no longer has
+    Unreadable,
 }
 
 #[derive(Clone, Default)]
-struct AppState {
-    // code -> Room
-    rooms: RwLock<HashMap<String, Room>>,
+pub(crate) struct AppState {
+    // code -> Room (Arc so background jobs can hold a handle too)
+    rooms: Arc<RwLock<HashMap<String, Room>>>,
+    // series id -> linked rooms; lock after `rooms` when taking both
+    series: Arc<RwLock<HashMap<String, Series>>>,
+    // code -> when it was purged, so old links get "expired" rather than
+    // "not found"; forgotten after EXPIRED_MEMORY_SECS
+    expired: Arc<RwLock<HashMap<String, u64>>>,
+    // per-IP buckets for the create and join forms
+    form_limits: FormRateLimits,
+    // games finished, for the admin dashboard
+    tally: GameTally,
 }
 
+const EXPIRED_MEMORY_SECS: u64 = 30 * 24 * 60 * 60;
+
 static APP_STATE: Lazy<AppState> = Lazy::new(|| AppState {
-    rooms: RwLock::new(HashMap::new()),
+    rooms: Arc::new(RwLock::new(HashMap::new())),
+    series: Arc::new(RwLock::new(HashMap::new())),
+    expired: Arc::new(RwLock::new(HashMap::new())),
+    form_limits: FormRateLimits::default(),
+    tally: GameTally::default(),
 });
 
+// Loads the rooms saved by the configured backend before launch, and saves
+// them once more on the way down; the "room store" job covers the rest.
+// Without a persistent backend the way down writes a one-off snapshot
+// instead (see storage::ShutdownSnapshot).
+fn room_storage_fairing(storage: RoomStorage) -> AdHoc {
+    AdHoc::on_ignite("Room Storage", |rocket| async move {
+        let kind = StorageKind::load(rocket.figment()).unwrap_or_default();
+        // fairings attached here run after this one, the pool's first
+        let rocket = match kind {
+            StorageKind::Sqlite => rocket.attach(RoomsDb::init()),
+            _ => rocket,
+        };
+        rocket.attach(room_restore_fairing(storage, kind))
+    })
+}
+
+fn room_restore_fairing(storage: RoomStorage, kind: StorageKind) -> AdHoc {
+    AdHoc::on_ignite("Room Restore", |rocket| async move {
+        let snapshot = ShutdownSnapshot::load(rocket.figment(), &kind);
+        match kind.open(&rocket).await {
+            Ok(backend) => storage.set_backend(backend),
+            Err(e) => tracing::warn!("room store: keeping rooms in memory: {}", e),
+        }
+        match storage.load().await {
+            Ok(saved) if !saved.is_empty() => {
+                let restored = APP_STATE.restore_rooms(saved);
+                tracing::info!("room store: restored {} rooms from {}", restored, storage.describe());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("room store: starting empty: {}", e),
+        }
+        if let Some(snapshot) = &snapshot {
+            match snapshot.take() {
+                Ok(saved) if !saved.is_empty() => {
+                    let restored = APP_STATE.restore_rooms(saved);
+                    tracing::info!("room store: restored {} rooms from {}", restored, snapshot.describe());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("room store: shutdown snapshot not restored: {}", e),
+            }
+        }
+        let flush = storage.clone();
+        rocket.manage(storage).attach(AdHoc::on_shutdown("Room Storage Flush", |_| {
+            Box::pin(async move {
+                let rooms = APP_STATE.stored_rooms();
+                if let Some(snapshot) = snapshot {
+                    match snapshot.save(&rooms) {
+                        Ok(()) if !rooms.is_empty() => {
+                            tracing::info!("room store: saved {} rooms to {}", rooms.len(), snapshot.describe())
+                        }
+                        Ok(()) => {}
+                        Err(e) => tracing::error!("room store: failed to snapshot on shutdown: {}", e),
+                    }
+                }
+                if let Err(e) = flush.save(rooms).await {
+                    tracing::error!("room store: failed to save on shutdown: {}", e);
+                }
+            })
+        }))
+    })
+}
+
+impl AppState {
+    // Every room as a JSON document, in code order, for the room store.
+    fn stored_rooms(&self) -> Vec<Value> {
+        let map = self.rooms.read();
+        let mut rooms: Vec<&Room> = map.values().collect();
+        rooms.sort_by(|a, b| a.code.cmp(&b.code));
+        rooms
+            .into_iter()
+            .filter_map(|r| rocket::serde::json::to_value(r).ok())
+            .collect()
+    }
+
+    // Puts saved rooms back; ones that no longer parse are skipped.
+    fn restore_rooms(&self, saved: Vec<Value>) -> usize {
+        let mut map = self.rooms.write();
+        for value in saved {
+            match rocket::serde::json::from_value::<Room>(value) {
+                Ok(mut room) => {
+                    room.recount();
+                    map.insert(room.code.clone(), room);
+                }
+                Err(e) => tracing::warn!("room store: skipping unreadable room: {}", e),
+            }
+        }
+        map.len()
+    }
+
+    // Admin cleanup: ends each listed game where it stands, as if the players
+    // had finished. Returns the codes that couldn't be closed, with why.
+    // A tenant admin (`tenant` is Some) only sees their own rooms.
+    pub(crate) fn close_rooms(&self, codes: &[String], tenant: Option<&str>) -> Vec<(String, &'static str)> {
+        let mut map = self.rooms.write();
+        let mut failed = Vec::new();
+        for code in codes {
+            let visible = map.get_mut(code).filter(|r| tenant.is_none() || r.tenant.as_deref() == tenant);
+            match visible {
+                None => failed.push((code.clone(), "not_found")),
+                Some(room) if room.finished => failed.push((code.clone(), "already_finished")),
+                Some(room) => room.finish(),
+            }
+        }
+        failed
+    }
+
+    // Retention: removes rooms nobody has touched since `cutoff` (unix
+    // secs) and returns their codes; a dry run only lists them.
+    pub(crate) fn purge_rooms(&self, cutoff: u64, dry_run: bool) -> Vec<String> {
+        let idle = |map: &HashMap<String, Room>| -> Vec<String> {
+            map.values()
+                .filter(|r| r.last_activity() < cutoff)
+                .map(|r| r.code.clone())
+                .collect()
+        };
+        if dry_run {
+            return idle(&self.rooms.read());
+        }
+        let mut map = self.rooms.write();
+        let codes = idle(&map);
+        let now = now_secs();
+        let mut expired = self.expired.write();
+        expired.retain(|_, at| now.saturating_sub(*at) < EXPIRED_MEMORY_SECS);
+        for code in &codes {
+            map.remove(code);
+            expired.insert(code.clone(), now);
+        }
+        codes
+    }
+
+    fn is_expired(&self, code: &str) -> bool {
+        self.expired.read().contains_key(code)
+    }
+
+    // Open, or expired recently enough that its players would still see the
+    // expired page; either way not free for a new room.
+    fn code_taken(&self, rooms: &HashMap<String, Room>, code: &str) -> bool {
+        rooms.contains_key(code) || self.is_expired(code)
+    }
+
+    // Rooms and players per referral code, most rooms first. Scoped like
+    // `close_rooms`.
+    pub(crate) fn referral_report(&self, tenant: Option<&str>) -> Vec<ReferralRow> {
+        let map = self.rooms.read();
+        let mut rows: BTreeMap<&str, ReferralRow> = BTreeMap::new();
+        for room in map.values().filter(|r| tenant.is_none() || r.tenant.as_deref() == tenant) {
+            if let Some(code) = room.referral.as_deref() {
+                let row = rows.entry(code).or_default();
+                row.rooms += 1;
+                row.finished += usize::from(room.finished);
+            }
+            for code in room.players.iter().filter_map(|p| p.referral.as_deref()) {
+                rows.entry(code).or_default().players += 1;
+            }
+        }
+        let mut rows: Vec<ReferralRow> = rows
+            .into_iter()
+            .map(|(code, row)| ReferralRow {
+                code: code.to_string(),
+                ..row
+            })
+            .collect();
+        rows.sort_by(|a, b| b.rooms.cmp(&a.rooms).then(b.players.cmp(&a.players)));
+        rows
+    }
+
+    // The admin dashboard: totals, and every room, open ones first and the
+    // most recently active at the top. Scoped like `close_rooms`.
+    pub(crate) fn dashboard(&self, tenant: Option<&str>, now: u64) -> (DashboardStats, Vec<RoomRow>) {
+        let map = self.rooms.read();
+        let mut rows: Vec<RoomRow> = map
+            .values()
+            .filter(|r| tenant.is_none() || r.tenant.as_deref() == tenant)
+            .map(|r| r.dashboard_row(now))
+            .collect();
+        rows.sort_by(|a, b| a.finished.cmp(&b.finished).then(b.last_active.cmp(&a.last_active)));
+        let (games_today, average_score_today) = self.tally.today(now);
+        let room_max_bytes = features::current().room_max_bytes;
+        let stats = DashboardStats {
+            active_rooms: rows.iter().filter(|r| !r.finished).count(),
+            players_online: rows.iter().map(|r| r.online).sum(),
+            games_today,
+            average_score_today,
+            games_since_launch: self.tally.since_launch(),
+            memory: MemoryUsage::of(rows.iter().map(|r| r.bytes), room_max_bytes),
+        };
+        (stats, rows)
+    }
+
+    pub(crate) fn room_detail(&self, code: &str, tenant: Option<&str>, now: u64) -> Option<RoomDetail> {
+        let map = self.rooms.read();
+        let room = map.get(code).filter(|r| tenant.is_none() || r.tenant.as_deref() == tenant)?;
+        let seats = room
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| SeatRow {
+                seat: i + 1,
+                player_id: p.id.clone(),
+                score: p.score,
+                online: room.is_online(p, now),
+                last_seen: p.last_seen,
+                is_host: i == 0,
+            })
+            .collect();
+        let recent = room
+            .events
+            .iter()
+            .rev()
+            .take(20)
+            .map(|e| json::to_value(&e.event).unwrap_or_default()["type"].as_str().unwrap_or_default().to_string())
+            .collect();
+        Some(RoomDetail {
+            room: room.dashboard_row(now),
+            seats,
+            events: room.events.len(),
+            recent,
+        })
+    }
+
+    // The room as the contract test compares it (see tests/contract.rs).
+    #[cfg(any(test, feature = "contract"))]
+    pub(crate) fn transitions(&self, code: &str) -> Option<Value> {
+        self.rooms.read().get(code).map(Room::transitions)
+    }
+}
+
+// --- Guards ---
+// Spends one of the caller's create or join tokens; a 429 page without one.
+struct FormLimit<S>(PhantomData<S>);
+
+#[rocket::async_trait]
+impl<'r, S: Scope + Send + Sync + 'static> FromRequest<'r> for FormLimit<S> {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let Some(state) = req.rocket().state::<AppState>() else {
+            return request::Outcome::Success(FormLimit(PhantomData));
+        };
+        match ratelimit::check_form::<S>(req, &state.form_limits) {
+            Ok(()) => request::Outcome::Success(FormLimit(PhantomData)),
+            Err(status) => request::Outcome::Error((status, ())),
+        }
+    }
+}
+
 // --- Forms ---
 #[derive(FromForm)]
-struct CreateRoomForm {
+struct CreateRoomForm<'r> {
     host_name: String,
+    // couple (the default), friends or party
+    game_mode: Option<GameMode>,
+    // seats for group modes, blank = the mode's default
+    capacity: Option<usize>,
+    // checkbox: hold play until the host presses start
+    wait_for_host: bool,
+    // checkbox: keep drawing questions until someone taps "finish"
+    endless: bool,
+    // checkbox: let players peek at the running score
+    show_score: bool,
+    // checkbox: guess mode with double-or-nothing wagers
+    wagers: bool,
+    // seconds per question, blank = untimed
+    time_limit: Option<u64>,
+    // one of QUESTION_COUNTS, blank = QUESTIONS_PER_GAME
+    question_count: Option<usize>,
+    // checkboxes: categories to deal from, none ticked = all
+    categories: Vec<String>,
+    // hidden: question ids the recommender suggested, dealt first
+    focus: Vec<String>,
+    // checkbox: finish with a 60s this-or-that round
+    lightning: bool,
+    // checkbox: three warm-up prompts first, to learn how strictly to match answers
+    calibration: bool,
+    // checkbox: play the branching date story instead of questions
+    story: bool,
+    // co-op: compatibility % to reach together, blank = no goal
+    goal_target: Option<u32>,
+    // revealed on the result page only if the goal is reached
+    secret_message: Option<String>,
+    // hidden surprise for the result page, plus when it may unlock
+    surprise_text: Option<String>,
+    surprise_photo: Option<Capped<TempFile<'r>>>,
+    surprise_min_score: Option<u32>,
+    // YYYY-MM-DD, not before this day (UTC)
+    surprise_date: Option<String>,
+    lang: Option<String>,
+    // asked of whoever joins, blank = open room
+    passphrase: Option<String>,
+    // checkbox: the host approves whoever joins
+    approve_joins: bool,
+    // checkbox: latecomers wait in line for a seat
+    queue_when_full: bool,
+    // checkbox: list the result page in the sitemap
+    public_result: bool,
+    // checkbox: include 18+ questions, with the partner's consent
+    mature: bool,
+}
+
+#[derive(FromForm)]
+struct CustomQuestionsForm {
+    code: String,
+    // repeated, one per question; blank ones are skipped
+    question: Vec<String>,
 }
 
 #[derive(FromForm)]
 struct JoinRoomForm {
     code: String,
     name: String,
+    lang: Option<String>,
+    passphrase: Option<String>,
 }
 
-// --- Helpers ---
-fn generate_code() -> String {
-    // 6-char friendly code, e.g., "A9K4ZT"
-    rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .filter(|c| c.is_ascii_alphanumeric())
-        .take(6)
-        .map(char::from)
-        .collect::<String>()
-        .to_uppercase()
+#[derive(FromForm)]
+struct ApproveForm {
+    // the host deciding
+    player: String,
+    // false = turn them away
+    approve: bool,
 }
 
-// --- Routes ---
+#[derive(FromForm)]
+struct ClaimForm {
+    code: String,
+}
 
-#[get("/")]
-fn index() -> Template {
-    Template::render(
-        "index",
-        context! {
-            title: "Welcome Moyosola 💖",
-            subtitle: "Created with love by Kamzy 💙",
-        },
-    )
+#[derive(FromForm)]
+struct ConsentForm {
+    player: String,
+    // false = play on without mature questions
+    agree: bool,
 }
 
-#[get("/create")]
-fn create_room_get() -> Template {
-    Template::render("create", context! {})
+#[derive(FromForm)]
+struct SettingsForm {
+    player: String,
+    // checkboxes
+    partner_answered: bool,
+    email_summary: bool,
+    email: Option<String>,
 }
 
-#[post("/create", data = "<form>")]
-fn create_room_post(form: Form<CreateRoomForm>, state: &State<AppState>) -> Redirect {
-    let code = generate_code();
-    let host = Player {
-        id: Uuid::new_v4().to_string(),
-        name: form.host_name.clone(),
-        score: 0,
-    };
-    let room = Room {
-        code: code.clone(),
-        players: vec![host],
-        current_question_index: 0,
-    };
+#[derive(FromForm)]
+struct LocaleForm {
+    player: String,
+    lang: String,
+}
 
-    {
-        let mut map = state.rooms.write();
-        map.insert(code.clone(), room);
+// `order` (option indexes) as option texts, if it's a full permutation.
+fn ranking(order: &[usize], options: &[String]) -> Option<Vec<String>> {
+    let mut seen = vec![false; options.len()];
+    if order.len() != options.len() {
+        return None;
     }
+    for &i in order {
+        if i >= options.len() || std::mem::replace(&mut seen[i], true) {
+            return None;
+        }
+    }
+    Some(order.iter().map(|&i| options[i].clone()).collect())
+}
 
-    Redirect::to(uri!(join_room_get(code = code)))
+// Which render of the play page an answer came from: the question it
+// showed and a fresh id per render. Lets a second tab, or a double click,
+// submit without answering the wrong question twice. Both are optional so
+// older pages still work.
+#[derive(FromForm, Default)]
+struct TabStamp {
+    question: Option<usize>,
+    submission: Option<String>,
 }
 
-#[get("/join?<code>")]
-fn join_room_get(code: Option<String>) -> Template {
-    Template::render(
-        "join",
-        context! {
-            code: code.unwrap_or_default(),
-            error: ""
-        },
-    )
+#[derive(FromForm)]
+struct LightningForm {
+    player: String,
+    // which pair this pick is for, guards against double-submits
+    index: usize,
+    pick: u8,
 }
 
-#[post("/join", data = "<form>")]
-fn join_room_post(form: Form<JoinRoomForm>, state: &State<AppState>) -> Result<Redirect, Status> {
-    let mut map = state.rooms.write();
-    if let Some(room) = map.get_mut(&form.code) {
-        if room.players.len() >= 2 {
-            return Err(Status::BadRequest);
-        }
-        let p = Player {
-            id: Uuid::new_v4().to_string(),
-            name: form.name.clone(),
-            score: 0,
+#[derive(FromForm)]
+struct CalibrateForm {
+    player: String,
+    // which prompt this is for, guards against double-submits
+    index: usize,
+    answer: Option<String>,
+    // judging: were the two answers the same?
+    same: Option<bool>,
+    // host only: start the game without calibrating
+    skip: bool,
+}
+
+#[derive(FromForm)]
+struct StoryForm {
+    player: String,
+    choice: usize,
+}
+
+#[derive(FromForm)]
+struct ReactForm {
+    // one of REACTIONS
+    emoji: String,
+}
+
+#[derive(FromForm)]
+struct FinishForm {
+    player: String,
+}
+
+#[derive(FromForm)]
+struct AdjustForm {
+    // the host doing the adjusting
+    player: String,
+    target: String,
+    #[field(validate = range(-50..=50))]
+    delta: i32,
+    #[field(validate = len(1..=100))]
+    reason: String,
+}
+
+// --- Responses ---
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct ScorePeek {
+    score: u32,
+    answered: usize,
+}
+
+// --- Helpers ---
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn default_match_threshold() -> f64 {
+    scoring::DEFAULT_MATCH_THRESHOLD
+}
+
+fn couple_size() -> usize {
+    scoring::COUPLE_SIZE
+}
+
+fn questions_per_game() -> usize {
+    QUESTIONS_PER_GAME
+}
+
+fn shuffle_seed() -> u64 {
+    rand::random()
+}
+
+// --- Game flow ---
+const QUESTIONS_PER_GAME: usize = 10;
+// game lengths the host can pick
+pub(crate) const QUESTION_COUNTS: &[usize] = &[10, 25, 50];
+const POINTS_PER_MATCH: u32 = 10;
+// no heartbeat for this long = offline
+const PRESENCE_TIMEOUT_SECS: u64 = 15;
+const SECRET_MESSAGE_MAX_CHARS: usize = 500;
+// extra time a player may ask for, once per question
+const EXTENSION_SECS: u64 = 30;
+// wrong passphrases before joining is locked, and for how long
+const MAX_JOIN_FAILURES: u32 = 5;
+const JOIN_LOCKOUT_SECS: u64 = 5 * 60;
+// joiners an approval room holds at once, so nobody can flood the host
+const MAX_PENDING_JOINS: usize = 5;
+// latecomers a full room keeps in line
+const MAX_QUEUED_JOINS: usize = 5;
+// question text shown on the pre-game preview
+const PREVIEW_CHARS: usize = 32;
+// what players can react with during play
+pub(crate) const REACTIONS: &[&str] = &["❤️", "😂", "😮", "🥰", "🙈", "👏", "🔥", "😢"];
+// per player and question, on top of the per-IP rate limit
+const MAX_REACTIONS_PER_QUESTION: usize = 5;
+
+
+impl Room {
+    fn new(
+        code: String,
+        host: Player,
+        settings: &CreateRoomForm<'_>,
+        bank: &QuestionBank,
+        story: &Story,
+        key: &MessageKey,
+        tenant: Option<String>,
+    ) -> Room {
+        let endless = settings.endless;
+        // ones the bank doesn't deal from would leave nothing to play
+        let categories = settings
+            .categories
+            .iter()
+            .filter(|c| bank.drawable(tenant.as_deref()).any(|q| q.category == **c))
+            .cloned()
+            .collect();
+        let game_mode = settings.game_mode.unwrap_or_default();
+        let mut room = Room {
+            code,
+            tenant,
+            players: vec![host],
+            game_mode,
+            capacity: settings.capacity.unwrap_or(game_mode.default_capacity()),
+            wait_for_host: settings.wait_for_host,
+            current_question_index: 0,
+            questions: Vec::new(),
+            answers: Vec::new(),
+            credits: Vec::new(),
+            credit_weights: Vec::new(),
+            category_weights: CategoryWeights::default(),
+            asked_at: HashMap::new(),
+            extensions: HashMap::new(),
+            endless,
+            show_score: settings.show_score,
+            wagers: settings.wagers,
+            timer: QuestionTimer::new(settings.time_limit),
+            lightning_enabled: settings.lightning,
+            lightning: None,
+            calibration: (settings.calibration && !settings.story).then(Calibration::start),
+            match_threshold: scoring::DEFAULT_MATCH_THRESHOLD,
+            story: None,
+            goal: settings
+                .goal_target
+                .filter(|t| (1..=100).contains(t))
+                .map(|target| CoopGoal {
+                    target,
+                    message: settings
+                        .secret_message
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|m| !m.is_empty())
+                        .map(|m| m.chars().take(SECRET_MESSAGE_MAX_CHARS).collect::<String>())
+                        .and_then(|m| key.seal(&m)),
+                }),
+            surprise: None,
+            series: None,
+            rematch: None,
+            summary_sent: false,
+            snapshot: None,
+            passphrase: settings
+                .passphrase
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .and_then(|p| key.seal(p)),
+            join_guard: JoinGuard::default(),
+            approve_joins: settings.approve_joins,
+            pending: Vec::new(),
+            queue_when_full: settings.queue_when_full,
+            queue: Vec::new(),
+            public_result: settings.public_result,
+            referral: None,
+            mature: settings.mature,
+            blocked_categories: Vec::new(),
+            categories,
+            focus: settings.focus.clone(),
+            seed: shuffle_seed(),
+            played_before: Vec::new(),
+            custom_questions: Vec::new(),
+            question_count: settings.question_count.unwrap_or(QUESTIONS_PER_GAME),
+            deck: HashMap::new(),
+            deck_start: 0,
+            finished: false,
+            notice: None,
+            events: Vec::new(),
+            created_at: now_secs(),
+            last_active: now_secs(),
+            live: None,
+            stamp: Stamp::default(),
+            bytes: 0,
+            event_bytes: ListSize::default(),
         };
-        room.players.push(p);
-        Ok(Redirect::to(uri!(play_get(code = form.code.clone()))))
-    } else {
-        // back to join with error
-        Ok(Redirect::to(uri!(join_room_get(Some(format!("{}", form.code))))))
+        tracing::info!(
+            target: "game",
+            room = %room.code,
+            game_mode = ?room.game_mode,
+            capacity = room.capacity,
+            "room created"
+        );
+        if settings.story {
+            room.story = Some(StoryProgress::start(story));
+            return room;
+        }
+        if !endless {
+            room.deal(0, bank);
+        } else {
+            room.draw_next(bank);
+        }
+        room
     }
-}
 
-#[get("/play/<code>")]
-fn play_get(code: String, state: &State<AppState>) -> Template {
-    let map = state.rooms.read();
-    let maybe_room = map.get(&code);
+    // Fills the fixed deck from `keep` on, leaving questions before it as
+    // they are; used at creation and again once mature questions unlock.
+    fn deal(&mut self, keep: usize, bank: &QuestionBank) {
+        let mut rng = self.shuffler(keep);
+        let keep = keep.min(self.questions.len());
+        let mature = self.mature_allowed();
+        let kept = &self.questions[..keep];
+        let mut ids: Vec<String> = bank
+            .drawable(self.tenant.as_deref())
+            .filter(|q| (mature || !q.mature) && !self.blocked_categories.contains(&q.category))
+            .filter(|q| self.categories.is_empty() || self.categories.contains(&q.category))
+            .map(|q| q.id.clone())
+            .filter(|id| !kept.contains(id))
+            .collect();
+        ids.shuffle(&mut rng);
+        // suggested questions lead, in the order they were suggested, and
+        // ones the couple had before trail
+        ids.sort_by_key(|id| {
+            let suggested = self.focus.iter().position(|f| f == id).unwrap_or(usize::MAX);
+            (self.played_before.contains(id), suggested)
+        });
+        // the host's own questions always make it in, somewhere in the mix
+        let left = self.question_count.saturating_sub(keep);
+        let own: Vec<String> = self
+            .custom_questions
+            .iter()
+            .map(|q| q.id.clone())
+            .filter(|id| !kept.contains(id))
+            .take(left)
+            .collect();
+        ids.truncate(left - own.len());
+        for id in own {
+            let at = rng.gen_range(0..=ids.len());
+            ids.insert(at, id);
+        }
+        self.questions.truncate(keep);
+        self.answers.truncate(keep);
+        for id in ids {
+            self.pin(&id, bank);
+            self.questions.push(id);
+            self.answers.push(HashMap::new());
+        }
+    }
 
-    // For Step 1 we just show the room and players.
-    // In Step 2 we'll load 200 questions and start the flow.
-    if let Some(room) = maybe_room {
-        let players: Vec<String> = room.players.iter().map(|p| p.name.clone()).collect();
-        Template::render(
-            "play",
-            context! {
-                code: room.code.clone(),
-                players,
-                question_placeholder: "Questions loading soon… (Step 2 will add 200 💕)"
-            },
-        )
-    } else {
-        Template::render(
-            "play",
-            context! {
-                code,
-                players: Vec::<String>::new(),
-                question_placeholder: "Room not found."
-            },
-        )
+    // The room's own random order for its `n`th question on: the same seed
+    // and `n` shuffle the same way, so a redeal before play (custom
+    // questions added, say) keeps the bank's picks where they were.
+    fn shuffler(&self, n: usize) -> StdRng {
+        StdRng::seed_from_u64(self.seed.wrapping_add(n as u64))
     }
-}
 
-#[get("/result/<code>")]
-fn result_get(code: String, state: &State<AppState>) -> Template {
-    let map = state.rooms.read();
-    if let Some(room) = map.get(&code) {
-        // placeholder logic: equal split score just to render page
-        let score = (room.players.len() as u32 * 42) % 100;
-        let message = if score >= 85 {
-            "Perfect Match 💍💖"
-        } else if score >= 60 {
-            "Good Match 💕"
+    // Bank ids this couple has had so far in their series, this game
+    // included, for the rematch to leave out.
+    fn played_so_far(&self) -> Vec<String> {
+        let mut played = self.played_before.clone();
+        for id in self.questions.iter().filter(|id| !custom::is_custom(id)) {
+            if !played.contains(id) {
+                played.push(id.clone());
+            }
+        }
+        played
+    }
+
+    // Applies the host's regional content rules and deals again without
+    // what they rule out. Only called before anyone has answered.
+    fn restrict(&mut self, blocked: Vec<String>, mature: bool, bank: &QuestionBank) {
+        self.blocked_categories = blocked;
+        self.mature &= mature;
+        if self.story.is_none() {
+            self.redeal(bank);
+        }
+    }
+
+    // Deals from scratch; only before anyone has answered.
+    fn redeal(&mut self, bank: &QuestionBank) {
+        if self.endless {
+            self.questions.clear();
+            self.answers.clear();
+            self.deck.clear();
+            self.draw_next(bank);
+        } else {
+            self.deal(0, bank);
+        }
+    }
+
+    // The host may add questions of their own while the room is still in
+    // the lobby; story rooms have no questions to mix them into.
+    fn can_add_custom(&self) -> bool {
+        !self.in_play() && !self.started() && self.story.is_none()
+    }
+
+    // Adds them and deals again with them in. Refused outside the lobby and
+    // past custom::MAX_PER_ROOM.
+    fn add_custom(&mut self, texts: Vec<String>, bank: &QuestionBank) -> bool {
+        if !self.can_add_custom() || self.custom_questions.len() + texts.len() > custom::MAX_PER_ROOM {
+            return false;
+        }
+        for text in texts {
+            let n = self.custom_questions.len() + 1;
+            self.custom_questions.push(custom::question(n, text));
+        }
+        self.redeal(bank);
+        self.recount();
+        true
+    }
+
+    // Mature questions are only ever dealt with a full room that all agreed.
+    fn mature_allowed(&self) -> bool {
+        self.mature && self.is_full() && self.players.iter().all(|p| p.consented_at.is_some())
+    }
+
+    // Every seat taken; nobody else gets one.
+    fn is_full(&self) -> bool {
+        self.players.len() >= self.capacity
+    }
+
+    // Full, and not held for the host's start; questions are asked only then.
+    fn in_play(&self) -> bool {
+        self.is_full() && !self.wait_for_host
+    }
+
+    // The host may start with whoever is seated, two at the least.
+    fn can_start(&self) -> bool {
+        !self.finished && !self.in_play() && self.players.len() >= scoring::COUPLE_SIZE
+    }
+
+    // Host's call: play begins with the players seated now, and the empty
+    // seats go. Anyone still waiting for approval is turned away.
+    fn start(&mut self, now: u64) -> bool {
+        if !self.can_start() {
+            return false;
+        }
+        self.capacity = self.players.len();
+        self.wait_for_host = false;
+        for p in std::mem::take(&mut self.pending) {
+            self.log(RoomEvent::JoinRejected {
+                player_id: p.id,
+                player_name: p.name.expose().clone(),
+            });
+        }
+        self.log(RoomEvent::GameStarted { players: self.players.len() });
+        if self.players.iter().all(|p| !self.needs_consent(&p.id)) {
+            self.start_question(now);
+        }
+        true
+    }
+
+    // Host's call: takes someone out of the room, the line or the approval
+    // list. Seats are only taken back before the first reveal, as with leaving.
+    fn kick(&mut self, player_id: &str, now: u64) -> bool {
+        let player = if let Some(i) = self.pending.iter().position(|p| p.id == player_id) {
+            self.pending.remove(i)
         } else {
-            "Nice Try 😅"
+            match self.unseat(player_id) {
+                Some(player) => player,
+                None => return false,
+            }
         };
-        Template::render(
-            "result",
-            context! { code, score, message },
-        )
-    } else {
-        Template::render(
-            "result",
-            context! { code, score: 0, message: "Room not found." },
-        )
+        self.log(RoomEvent::PlayerKicked {
+            player_id: player.id,
+            player_name: player.name.expose().clone(),
+        });
+        self.promote_queued(now);
+        true
     }
-          }
+
+    // Host's call: ends the game where it stands for everyone.
+    fn close(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+        self.timer.stop();
+        self.log(RoomEvent::RoomClosed);
+        self.finish();
+        true
+    }
+
+    // This player still has to pass the consent gate before playing.
+    fn needs_consent(&self, player_id: &str) -> bool {
+        self.mature && self.players.iter().any(|p| p.id == player_id && p.consented_at.is_none())
+    }
+
+    // Records a player's answer at the gate. Declining turns mature
+    // questions off for the room rather than stranding it.
+    fn consent(&mut self, player_id: &str, agree: bool, bank: &QuestionBank, now: u64) {
+        if !agree {
+            self.mature = false;
+        } else if let Some(p) = self.players.iter_mut().find(|p| p.id == player_id) {
+            p.consented_at.get_or_insert(now);
+        }
+        // fixed decks swap in a deal that may include mature questions from
+        // the first unanswered one on
+        if self.mature_allowed() && !self.endless && self.story.is_none() {
+            let answered = self.answers.iter().take_while(|a| !a.is_empty()).count();
+            self.deal(answered.max(self.current_question_index), bank);
+        }
+        if self.in_play() && !self.asked_at.contains_key(&self.current_question_index) {
+            self.start_question(now);
+        }
+    }
+
+    // Changes whenever anything the room's pages show changes. Hashed from
+    // the room itself so no mutation can forget to bump it; presence is left
+    // out so heartbeats don't invalidate every page.
+    fn version(&self) -> u64 {
+        let mut value = json::to_value(self).unwrap_or_default();
+        if let Some(players) = value.get_mut("players").and_then(Value::as_array_mut) {
+            for player in players.iter_mut().filter_map(Value::as_object_mut) {
+                player.remove("last_seen");
+            }
+        }
+        hash_of(&value.to_string())
+    }
+
+    fn check_passphrase(&mut self, given: Option<&str>, key: &MessageKey, now: u64) -> PassphraseCheck {
+        let Some(sealed) = &self.passphrase else { return PassphraseCheck::Ok };
+        if now < self.join_guard.locked_until {
+            return PassphraseCheck::Locked;
+        }
+        // unreadable after a key change: nobody gets in, but it isn't their miss
+        let Some(expected) = key.open(sealed) else { return PassphraseCheck::Unreadable };
+        if given.is_some_and(|given| same_secret(&expected, given.trim())) {
+            self.join_guard.failures = 0;
+            return PassphraseCheck::Ok;
+        }
+        self.join_guard.failures += 1;
+        if self.join_guard.failures < MAX_JOIN_FAILURES {
+            return PassphraseCheck::Wrong;
+        }
+        self.join_guard = JoinGuard {
+            failures: 0,
+            locked_until: now + JOIN_LOCKOUT_SECS,
+        };
+        self.log(RoomEvent::JoinLocked {
+            failed_attempts: MAX_JOIN_FAILURES,
+            until: now + JOIN_LOCKOUT_SECS,
+        });
+        PassphraseCheck::JustLocked
+    }
+
+    // Approval rooms: lets a waiting player in, or turns them away. Once the
+    // room is full, anyone else still waiting is turned away too. False if
+    // they weren't waiting or there's no seat left.
+    fn decide_join(&mut self, player_id: &str, approve: bool, now: u64) -> bool {
+        let Some(i) = self.pending.iter().position(|p| p.id == player_id) else {
+            return false;
+        };
+        if approve && self.is_full() {
+            return false;
+        }
+        let player = self.pending.remove(i);
+        let (id, name) = (player.id.clone(), player.name.expose().clone());
+        if !approve {
+            self.log(RoomEvent::JoinRejected { player_id: id, player_name: name });
+            // the seat is still free for whoever is next in line
+            self.promote_queued(now);
+            return true;
+        }
+        self.players.push(player);
+        self.log(RoomEvent::JoinApproved {
+            player_id: id.clone(),
+            player_name: name,
+        });
+        if self.is_full() {
+            for p in std::mem::take(&mut self.pending) {
+                self.log(RoomEvent::JoinRejected {
+                    player_id: p.id,
+                    player_name: p.name.expose().clone(),
+                });
+            }
+            // mature rooms start once the newcomer has answered the consent gate
+            if self.in_play() && !self.needs_consent(&id) {
+                self.start_question(now);
+            }
+        }
+        true
+    }
+
+    // Anything resolved yet? Until then a partner may still step out.
+    fn started(&self) -> bool {
+        self.finished
+            || !self.credits.is_empty()
+            || self.lightning.is_some()
+            || self.story.as_ref().is_some_and(|s| !s.path.is_empty())
+    }
+
+    // The host can't leave their own room; partners can until the first
+    // reveal, since scores after that belong to the pair.
+    fn can_leave(&self, player_id: &str) -> bool {
+        self.has_player(player_id) && !self.is_host(player_id) && !self.started()
+    }
+
+    // Takes a player out of the room (or out of line) and gives a freed seat
+    // to the first in line. False if they're in neither, or can't leave now.
+    fn leave(&mut self, player_id: &str, now: u64) -> bool {
+        let Some(player) = self.unseat(player_id) else {
+            return false;
+        };
+        self.log(RoomEvent::PlayerLeft {
+            player_id: player.id,
+            player_name: player.name.expose().clone(),
+        });
+        self.promote_queued(now);
+        true
+    }
+
+    // Out of line, or out of their seat while they still may leave it.
+    fn unseat(&mut self, player_id: &str) -> Option<Player> {
+        if let Some(i) = self.queue.iter().position(|p| p.id == player_id) {
+            return Some(self.queue.remove(i));
+        }
+        if !self.can_leave(player_id) {
+            return None;
+        }
+        let i = self.players.iter().position(|p| p.id == player_id)?;
+        // the question starts over for whoever takes the seat
+        for answers in self.answers.iter_mut() {
+            answers.remove(player_id);
+        }
+        self.asked_at.remove(&self.current_question_index);
+        self.timer.stop();
+        Some(self.players.remove(i))
+    }
+
+    // First in line takes a free seat; approval rooms still ask the host.
+    fn promote_queued(&mut self, now: u64) {
+        if self.is_full() || self.queue.is_empty() {
+            return;
+        }
+        let next = self.queue.remove(0);
+        let (id, name) = (next.id.clone(), next.name.expose().clone());
+        self.log(RoomEvent::QueuePromoted {
+            player_id: id.clone(),
+            player_name: name.clone(),
+        });
+        if self.approve_joins {
+            self.log(RoomEvent::JoinRequested { player_id: id, player_name: name });
+            self.pending.push(next);
+            return;
+        }
+        self.players.push(next);
+        if self.in_play() && !self.needs_consent(&id) {
+            self.start_question(now);
+        }
+    }
+
+    // Whether this cookie's session is still the player's: a handoff to
+    // another device signs the old one out. Covers the line and the host's
+    // pending list too. An unclaimed seat only takes cookies from before
+    // sessions, which carry none.
+    fn bound(&self, me: &CurrentPlayer) -> bool {
+        self.players
+            .iter()
+            .chain(&self.pending)
+            .chain(&self.queue)
+            .find(|p| p.id == me.id)
+            .is_some_and(|p| p.session.as_deref().unwrap_or_default() == me.session)
+    }
+
+    // Who's looking at the play page: the cookie's player while it's still
+    // theirs. True when the cookie was signed out by a handoff.
+    fn viewer(&self, current: Option<&CurrentPlayer>) -> (Option<String>, bool) {
+        match current.filter(|me| self.bound(me)) {
+            Some(me) => (Some(me.id.clone()), false),
+            None => (None, current.is_some()),
+        }
+    }
+
+    // Gives rematch seat `player_id` to the device taking it: a fresh
+    // session, so nobody else can. None if it's taken or not there.
+    fn claim_seat(&mut self, player_id: &str) -> Option<String> {
+        let player = self.players.iter_mut().find(|p| p.id == player_id && p.session.is_none())?;
+        let session = identity::new_session();
+        player.session = Some(session.clone());
+        Some(session)
+    }
+
+    // Hands the player to the device claiming `code`: a fresh session, so the
+    // old device's cookie stops working. Returns (player id, session).
+    fn claim_handoff(&mut self, code: &str, now: u64) -> Option<(String, String)> {
+        let player = self
+            .players
+            .iter_mut()
+            .find(|p| p.handoff.as_ref().is_some_and(|h| h.code == code && now < h.expires_at))?;
+        let session = identity::new_session();
+        player.session = Some(session.clone());
+        player.handoff = None;
+        let (id, name) = (player.id.clone(), player.name.expose().clone());
+        self.log(RoomEvent::DeviceChanged {
+            player_id: id.clone(),
+            player_name: name,
+        });
+        Some((id, session))
+    }
+
+    fn was_rejected(&self, player_id: &str) -> bool {
+        self.events
+            .iter()
+            .any(|e| matches!(&e.event, RoomEvent::JoinRejected { player_id: id, .. } if id == player_id))
+    }
+
+    // Latest heartbeat or action from anyone in the room, or when it was
+    // made if nothing has happened since.
+    fn last_activity(&self) -> u64 {
+        let seen = self.players.iter().map(|p| p.last_seen).max().unwrap_or(0);
+        seen.max(self.last_active).max(self.created_at)
+    }
+
+    // Seen within the presence timeout; finished games have nobody online.
+    fn is_online(&self, player: &Player, now: u64) -> bool {
+        !self.finished && now.saturating_sub(player.last_seen) <= PRESENCE_TIMEOUT_SECS
+    }
+
+    fn dashboard_row(&self, now: u64) -> RoomRow {
+        RoomRow {
+            code: self.code.clone(),
+            game_mode: json::to_value(self.game_mode).unwrap_or_default().as_str().unwrap_or_default().to_string(),
+            mode: self.mode(),
+            capacity: self.capacity,
+            players: self.players.len(),
+            online: self.players.iter().filter(|p| self.is_online(p, now)).count(),
+            question: (self.current_question_index + 1).min(self.questions.len().max(1)),
+            total: (!self.endless).then_some(self.questions.len()),
+            finished: self.finished,
+            score: self.compatibility(),
+            bytes: self.bytes,
+            created_at: self.created_at,
+            last_active: self.last_activity(),
+            tenant: self.tenant.clone(),
+        }
+    }
+
+    // What a game's moves change, with players by seat and without anything
+    // that differs between two rooms playing the same moves: ids, the code,
+    // question order, times, answer texts.
+    #[cfg(any(test, feature = "contract"))]
+    fn transitions(&self) -> Value {
+        let seat = |id: &str| self.players.iter().position(|p| p.id == id);
+        let answered: Vec<Vec<usize>> = self
+            .answers
+            .iter()
+            .map(|a| {
+                let mut seats: Vec<usize> = a.keys().filter_map(|id| seat(id)).collect();
+                seats.sort_unstable();
+                seats
+            })
+            .collect();
+        let events: Vec<Value> = self
+            .events
+            .iter()
+            .map(|e| {
+                let event = json::to_value(&e.event).unwrap_or_default();
+                json!({
+                    "type": event["type"],
+                    "seat": event["player_id"].as_str().and_then(seat),
+                    "question_index": event["question_index"],
+                    "matched": event["matched"],
+                    "credit_pct": event["credit_pct"],
+                })
+            })
+            .collect();
+        json!({
+            "players": self
+                .players
+                .iter()
+                .map(|p| json!({ "name": p.name.expose(), "score": p.score }))
+                .collect::<Vec<_>>(),
+            "in_play": self.in_play(),
+            "finished": self.finished,
+            "current_question_index": self.current_question_index,
+            "questions": self.questions.len(),
+            "answered": answered,
+            "credits": self.credits,
+            "events": events,
+        })
+    }
+
+    fn has_player(&self, id: &str) -> bool {
+        self.players.iter().any(|p| p.id == id)
+    }
+
+    // The room creator is always the first player.
+    fn is_host(&self, id: &str) -> bool {
+        self.players.first().map(|p| p.id == id).unwrap_or(false)
+    }
+
+    fn touch(&mut self, player_id: &str, now: u64) {
+        if let Some(p) = self.players.iter_mut().find(|p| p.id == player_id) {
+            p.last_seen = now;
+            self.last_active = self.last_active.max(now);
+        }
+    }
+
+    // Pauses the question timer while someone is offline and picks it back up
+    // once everyone is here again. Run periodically by the scheduler.
+    fn sweep_presence(&mut self, now: u64) {
+        if self.finished || !(self.timer.is_running() || self.timer.is_paused()) {
+            return;
+        }
+        let offline = self
+            .players
+            .iter()
+            .find(|p| now.saturating_sub(p.last_seen) > PRESENCE_TIMEOUT_SECS)
+            .map(|p| (p.id.clone(), p.name.expose().clone()));
+
+        match offline {
+            Some((player_id, player_name)) if self.timer.is_running() => {
+                self.timer.pause(now);
+                self.log(RoomEvent::TimerPaused {
+                    player_id,
+                    player_name,
+                    remaining_secs: self.timer.remaining(now).unwrap_or(0),
+                });
+            }
+            None if self.timer.is_paused() => {
+                self.timer.resume(now);
+                self.log(RoomEvent::TimerResumed {
+                    remaining_secs: self.timer.remaining(now).unwrap_or(0),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn log(&mut self, event: RoomEvent) {
+        self.log_from(event, None);
+    }
+
+    // As `log`, tagged with the play-page submission that caused it.
+    fn log_from(&mut self, event: RoomEvent, submission: Option<String>) {
+        let logged = LoggedEvent {
+            submission,
+            ..LoggedEvent::now(event)
+        };
+        self.last_active = logged.at;
+        match &logged.event {
+            RoomEvent::PlayerJoined { player_id, .. } => {
+                tracing::info!(target: "game", room = %self.code, player = %player_id, "player joined");
+            }
+            RoomEvent::GameFinished { score, .. } => {
+                tracing::info!(target: "game", room = %self.code, mode = self.mode(), score, "game finished");
+                APP_STATE.tally.finished(*score, logged.at);
+            }
+            _ => {}
+        }
+        if let Some(live) = &self.live {
+            // nobody listening is fine
+            let _ = live.send(logged.clone());
+        }
+        self.events.push(logged);
+        self.recount();
+    }
+
+    // See footprint.rs. Events are measured once each as they come; the rest
+    // is small enough to go over every time.
+    fn recount(&mut self) {
+        let players: usize = self
+            .players
+            .iter()
+            .chain(&self.pending)
+            .chain(&self.queue)
+            .map(footprint::json_len)
+            .sum();
+        let answers: usize = self
+            .answers
+            .iter()
+            .flat_map(HashMap::values)
+            .map(|a| footprint::ENTRY + footprint::texts(a.items.expose()))
+            .sum();
+        let deck: usize = self.deck.values().map(footprint::json_len).sum();
+        let ids = footprint::texts(&self.questions) + footprint::texts(&self.played_before);
+        let media = self.surprise.as_ref().map_or(0, Surprise::stored_len);
+        let events = self.event_bytes.update(&self.events);
+        self.bytes = players + answers + deck + ids + media + events;
+    }
+
+    // Past `room_max_bytes`: the game can still be finished, but takes no
+    // more answers.
+    fn is_full_up(&self) -> bool {
+        self.bytes >= features::current().room_max_bytes
+    }
+
+    fn subscribe(&mut self) -> broadcast::Receiver<LoggedEvent> {
+        self.live
+            .get_or_insert_with(|| broadcast::channel(features::current().live_buffer).0)
+            .subscribe()
+    }
+
+    fn adjust_points(&mut self, target: &str, delta: i32, reason: &str) -> bool {
+        let Some(p) = self.players.iter_mut().find(|p| p.id == target) else {
+            return false;
+        };
+        p.score = (p.score as i64 + delta as i64).max(0) as u32;
+        let player_name = p.name.expose().clone();
+        self.log(RoomEvent::PointsAdjusted {
+            player_id: target.to_string(),
+            player_name,
+            delta,
+            reason: reason.trim().to_string(),
+        });
+        true
+    }
+
+    // Endless rooms pull one question at a time. Once the bank is used up we
+    // start the deck over rather than stalling the game.
+    fn draw_next(&mut self, bank: &QuestionBank) {
+        let mut rng = self.shuffler(self.questions.len());
+        let tenant = self.tenant.as_deref();
+        let mature = self.mature_allowed();
+        let blocked = &self.blocked_categories;
+        let picked = &self.categories;
+        let own = &self.custom_questions;
+        let pool = || {
+            bank.drawable(tenant)
+                .filter(move |q| (mature || !q.mature) && !blocked.contains(&q.category))
+                .filter(move |q| picked.is_empty() || picked.contains(&q.category))
+                .chain(own)
+        };
+        let this_pass = &self.questions[self.deck_start..];
+        let unused: Vec<&str> = pool()
+            .map(|q| q.id.as_str())
+            .filter(|id| !this_pass.iter().any(|used| used == id))
+            .collect();
+
+        // what the couple had in earlier games comes back last
+        let fresh: Vec<&str> = unused
+            .iter()
+            .copied()
+            .filter(|id| !self.played_before.iter().any(|p| p == id))
+            .collect();
+        let suggested = self.focus.iter().map(String::as_str).find(|f| fresh.contains(f));
+        let drawn = suggested
+            .or_else(|| fresh.choose(&mut rng).copied())
+            .or_else(|| unused.choose(&mut rng).copied());
+        let next = if let Some(id) = drawn {
+            Some(id.to_string())
+        } else {
+            self.deck_start = self.questions.len();
+            self.notice =
+                Some("You've answered every question we have — shuffling the deck again 🔁".into());
+            // avoid asking the one that was just answered twice in a row
+            let last = self.questions.last().map(String::as_str);
+            let pass: Vec<&str> = pool()
+                .map(|q| q.id.as_str())
+                .filter(|id| Some(*id) != last || pool().count() == 1)
+                .collect();
+            pass.choose(&mut rng).map(|id| id.to_string())
+        };
+
+        if let Some(id) = next {
+            self.pin(&id, bank);
+            self.questions.push(id);
+            self.answers.push(HashMap::new());
+        }
+    }
+
+    fn pin(&mut self, id: &str, bank: &QuestionBank) {
+        if let Some(q) = self.custom_questions.iter().find(|q| q.id == id).or_else(|| bank.get(id)) {
+            self.deck.insert(id.to_string(), q.clone());
+        }
+    }
+
+    fn extended(&self, idx: usize, player_id: &str) -> bool {
+        self.extensions.get(&idx).is_some_and(|ids| ids.iter().any(|id| id == player_id))
+    }
+
+    // Whether `player_id` may still ask for more time: the question is on
+    // the clock, they haven't answered it, and haven't asked already.
+    fn can_extend(&self, player_id: &str, now: u64) -> bool {
+        let idx = self.current_question_index;
+        !self.finished
+            && !self.in_lightning()
+            && self.timer.remaining(now).is_some_and(|left| left > 0)
+            && self.answers.get(idx).is_some_and(|a| !a.contains_key(player_id))
+            && !self.extended(idx, player_id)
+    }
+
+    fn reactions(&self, idx: usize) -> impl Iterator<Item = &LoggedEvent> {
+        self.events
+            .iter()
+            .filter(move |e| matches!(e.event, RoomEvent::Reacted { question_index, .. } if question_index == idx))
+    }
+
+    // Passes one of REACTIONS on to everyone watching, through the event log
+    // like everything else live.
+    fn react(&mut self, player_id: &str, emoji: &str) -> Result<(), Status> {
+        if !REACTIONS.contains(&emoji) {
+            return Err(Status::UnprocessableEntity);
+        }
+        if self.finished || !self.in_play() {
+            return Err(Status::Conflict);
+        }
+        if self.is_full_up() {
+            return Err(Status::InsufficientStorage);
+        }
+        let idx = self.current_question_index;
+        let sent = self
+            .reactions(idx)
+            .filter(|e| matches!(&e.event, RoomEvent::Reacted { player_id: from, .. } if from == player_id))
+            .count();
+        if sent >= MAX_REACTIONS_PER_QUESTION {
+            return Err(Status::TooManyRequests);
+        }
+        let player_name = self
+            .players
+            .iter()
+            .find(|p| p.id == player_id)
+            .map(|p| p.name.expose().clone())
+            .unwrap_or_default();
+        self.log(RoomEvent::Reacted {
+            player_id: player_id.to_string(),
+            player_name,
+            question_index: idx,
+            emoji: emoji.to_string(),
+        });
+        Ok(())
+    }
+
+    // EXTENSION_SECS more on the current question, once per player.
+    fn extend_time(&mut self, player_id: &str, now: u64) -> Result<(), Status> {
+        if !self.can_extend(player_id, now) {
+            return Err(Status::Conflict);
+        }
+        let idx = self.current_question_index;
+        self.timer.extend(EXTENSION_SECS);
+        self.extensions.entry(idx).or_default().push(player_id.to_string());
+        let player_name = self
+            .players
+            .iter()
+            .find(|p| p.id == player_id)
+            .map(|p| p.name.expose().clone())
+            .unwrap_or_default();
+        self.log(RoomEvent::TimeExtended {
+            question_index: idx,
+            player_id: player_id.to_string(),
+            player_name,
+            added_secs: EXTENSION_SECS,
+            remaining_secs: self.timer.remaining(now).unwrap_or(0),
+        });
+        Ok(())
+    }
+
+    // Full matches in a row, counting back from the latest question.
+    fn match_streak(&self) -> u32 {
+        self.credits.iter().rev().take_while(|c| **c >= 1.0).count() as u32
+    }
+
+    fn story_pick(&mut self, story: &Story, player_id: &str, choice: usize) -> bool {
+        let ids: Vec<String> = self.players.iter().map(|p| p.id.clone()).collect();
+        if !self.in_play() {
+            return false;
+        }
+        let Some(progress) = self.story.as_mut() else { return false };
+        let id_refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        if !progress.pick(story, player_id, choice, &id_refs) {
+            return false;
+        }
+        if progress.is_over(story) {
+            self.finish();
+        }
+        true
+    }
+
+    fn calibrating(&self) -> bool {
+        !self.finished && self.calibration.as_ref().is_some_and(|c| !c.done)
+    }
+
+    // Settles the threshold once the round is over, and lets the first
+    // question start if everyone's already in.
+    fn finish_calibration(&mut self, now: u64) {
+        let Some(calibration) = self.calibration.as_ref().filter(|c| c.done) else {
+            return;
+        };
+        let ids: Vec<&str> = self.players.iter().map(|p| p.id.as_str()).collect();
+        self.match_threshold = calibration.threshold(&ids, scoring::DEFAULT_MATCH_THRESHOLD);
+        let ready = self.in_play() && self.players.iter().all(|p| !self.needs_consent(&p.id));
+        if ready && !self.asked_at.contains_key(&self.current_question_index) {
+            self.start_question(now);
+        }
+    }
+
+    fn in_lightning(&self) -> bool {
+        !self.finished && self.lightning.is_some()
+    }
+
+    // Closes the lightning round once time is up (or everyone ran out of
+    // pairs) and pays out the bonus for every pair picked the same way.
+    fn settle_lightning(&mut self, now: u64) {
+        let ids: Vec<String> = self.players.iter().map(|p| p.id.clone()).collect();
+        let Some(round) = self.lightning.as_mut() else { return };
+        if round.finished {
+            return;
+        }
+        let all_done = ids.iter().all(|id| round.next_pair(id).is_none());
+        if !round.is_over(now) && !all_done {
+            return;
+        }
+
+        round.finished = true;
+        let id_refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let matches = round.matches(&id_refs);
+        let bonus = matches * lightning::BONUS_PER_MATCH;
+        for p in self.players.iter_mut() {
+            p.score += bonus;
+        }
+        self.log(RoomEvent::LightningFinished {
+            matches,
+            bonus,
+            cue: Cue::for_lightning(matches),
+        });
+        self.finish();
+    }
+
+    // Scores a finished question and logs what everyone said.
+    fn reveal(&mut self, idx: usize, question: Option<&Question>) {
+        let answers = self.answers[idx].clone();
+        let pairs = self.pairwise(idx, question);
+        let credit = match &pairs {
+            Some((_, pairs)) => pairs.overall(),
+            None => {
+                let items: Vec<&[String]> = answers.values().map(|a| a.items.as_slice()).collect();
+                self.score_items(question, &items)
+            }
+        };
+        let streak_before = self.match_streak();
+        self.credits.push(credit);
+        let weight = question.map(|q| self.category_weights.weight(&q.category)).unwrap_or(1.0);
+        self.credit_weights.push(weight);
+        // wagers are all-or-nothing, partial credit doesn't win the bet
+        let matched = credit >= 1.0;
+        let points = |credit: f64| (POINTS_PER_MATCH as f64 * credit).round() as u32;
+        let mut wagers = Vec::new();
+
+        for p in self.players.iter_mut() {
+            // parties race each other: you earn for how well you matched the room
+            p.score += match &pairs {
+                Some((ids, pairs)) if self.game_mode == GameMode::Party => {
+                    ids.iter().position(|id| *id == p.id).map_or(0, |i| points(pairs.member(i)))
+                }
+                _ => points(credit),
+            };
+            let Some(answer) = answers.get(&p.id) else { continue };
+            // points may have moved since the bet was placed
+            let stake = answer.wager.min(p.score);
+            if stake == 0 {
+                continue;
+            }
+            let delta = if matched {
+                p.score += stake;
+                stake as i32
+            } else {
+                p.score -= stake;
+                -(stake as i32)
+            };
+            wagers.push(WagerOutcome {
+                player_id: p.id.clone(),
+                player_name: p.name.expose().clone(),
+                stake,
+                delta,
+            });
+        }
+
+        self.log(RoomEvent::QuestionRevealed {
+            question_index: idx,
+            question_id: self.questions[idx].clone(),
+            answers: self
+                .players
+                .iter()
+                .filter_map(|p| {
+                    answers.get(&p.id).map(|a| RevealedAnswer {
+                        player_id: p.id.clone(),
+                        player_name: p.name.expose().clone(),
+                        answer: match question {
+                            _ if a.timed_out => "⏱️ (out of time)".to_string(),
+                            Some(q) => q.display_answer(&a.items),
+                            None => a.items.join(", "),
+                        },
+                    })
+                })
+                .collect(),
+            matched,
+            credit_pct: (credit * 100.0).round() as u32,
+            cue: Cue::for_reveal(credit, streak_before, &wagers),
+            wagers,
+        });
+    }
+
+    fn score_items(&self, question: Option<&Question>, items: &[&[String]]) -> f64 {
+        match question {
+            Some(q) => scoring::question_credit(q, items, self.match_threshold),
+            // question vanished from the bank; score it as plain text
+            None => scoring::credit(items, 1, self.match_threshold),
+        }
+    }
+
+    // Group rooms score question `idx` pair by pair; the ids are the players
+    // who answered, by the positions `Pairwise` uses. None for couples.
+    fn pairwise(&self, idx: usize, question: Option<&Question>) -> Option<(Vec<String>, Pairwise)> {
+        if !self.game_mode.is_group() {
+            return None;
+        }
+        let answers = self.answers.get(idx)?;
+        let (ids, items): (Vec<String>, Vec<&[String]>) = self
+            .players
+            .iter()
+            .filter_map(|p| answers.get(&p.id).map(|a| (p.id.clone(), a.items.as_slice())))
+            .unzip();
+        let pairs = Pairwise::score(ids.len(), |a, b| self.score_items(question, &[items[a], items[b]]));
+        Some((ids, pairs))
+    }
+
+    fn last_reveal(&self) -> Option<&LoggedEvent> {
+        self.events
+            .iter()
+            .rev()
+            .find(|e| matches!(e.event, RoomEvent::QuestionRevealed { .. }))
+    }
+
+    // Questions both players got through, however many that was.
+    fn completed_answers(&self) -> impl Iterator<Item = &HashMap<String, Answer>> {
+        let players = self.players.len().max(self.capacity);
+        self.answers.iter().filter(move |a| a.len() >= players)
+    }
+
+    // How long each player took on each completed question.
+    fn timings(&self, bank: &QuestionBank) -> Vec<QuestionTiming> {
+        let players = self.players.len().max(self.capacity);
+        self.answers
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.len() >= players)
+            .filter_map(|(idx, answers)| {
+                let asked = *self.asked_at.get(&idx)?;
+                let question = self.seen(idx, bank).map(|q| q.text.clone()).unwrap_or_default();
+                let latencies = self
+                    .players
+                    .iter()
+                    .filter_map(|p| {
+                        let a = answers.get(&p.id)?;
+                        (a.answered_at > 0).then(|| PlayerLatency {
+                            player_id: p.id.clone(),
+                            player_name: p.name.expose().clone(),
+                            secs: a.answered_at.saturating_sub(asked),
+                            extended: self.extended(idx, &p.id),
+                        })
+                    })
+                    .collect();
+                Some(QuestionTiming {
+                    question_index: idx,
+                    question,
+                    latencies,
+                    heat: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    // When the game ended, per its event log.
+    fn finished_at(&self) -> Option<u64> {
+        self.events.iter().rev().find_map(|e| match e.event {
+            RoomEvent::GameFinished { .. } => Some(e.at),
+            _ => None,
+        })
+    }
+
+    // Everything that happened, question by question, for export.
+    fn transcript(&self, bank: &QuestionBank) -> Transcript {
+        let mut resolved = 0;
+        let questions = self
+            .answers
+            .iter()
+            .enumerate()
+            .map(|(idx, answers)| {
+                let question = self.seen(idx, bank);
+                let asked = self.asked_at.get(&idx).copied();
+                // credits are pushed in play order as questions resolve
+                let credit_pct = if answers.len() >= self.players.len().max(self.capacity) {
+                    resolved += 1;
+                    self.credits.get(resolved - 1).map(|c| (c * 100.0).round() as u32)
+                } else {
+                    None
+                };
+                TranscriptQuestion {
+                    index: idx,
+                    question_id: self.questions[idx].clone(),
+                    question: question.map(|q| q.text.clone()).unwrap_or_default(),
+                    category: question.map(|q| q.category.clone()).unwrap_or_default(),
+                    credit_pct,
+                    answers: self
+                        .players
+                        .iter()
+                        .filter_map(|p| {
+                            let a = answers.get(&p.id)?;
+                            Some(TranscriptAnswer {
+                                player_id: p.id.clone(),
+                                player_name: p.name.expose().clone(),
+                                answer: match question {
+                                    Some(q) => q.display_answer(&a.items),
+                                    None => a.items.join(", "),
+                                },
+                                wager: a.wager,
+                                secs: asked
+                                    .filter(|_| a.answered_at > 0)
+                                    .map(|t| a.answered_at.saturating_sub(t)),
+                            })
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        Transcript {
+            code: self.code.clone(),
+            score: self.compatibility(),
+            finished_at: self.finished_at(),
+            mode: Some(self.mode().to_string()),
+            players: self
+                .players
+                .iter()
+                .map(|p| TranscriptPlayer {
+                    id: p.id.clone(),
+                    name: p.name.expose().clone(),
+                    score: p.score,
+                })
+                .collect(),
+            questions,
+        }
+    }
+
+    // This room's setup as the create form would have posted it; secrets
+    // and surprises stay behind.
+    fn settings(&self, focus: Vec<String>) -> CreateRoomForm<'static> {
+        CreateRoomForm {
+            host_name: String::new(),
+            game_mode: Some(self.game_mode),
+            capacity: Some(self.capacity),
+            wait_for_host: self.wait_for_host,
+            endless: self.endless,
+            show_score: self.show_score,
+            wagers: self.wagers,
+            time_limit: self.timer.limit_secs,
+            question_count: Some(self.question_count),
+            categories: self.categories.clone(),
+            focus,
+            lightning: self.lightning_enabled,
+            // the threshold they settled on carries over instead
+            calibration: false,
+            story: self.story.is_some(),
+            goal_target: self.goal.as_ref().map(|g| g.target),
+            secret_message: None,
+            surprise_text: None,
+            surprise_photo: None,
+            surprise_min_score: None,
+            surprise_date: None,
+            lang: None,
+            passphrase: None,
+            approve_joins: self.approve_joins,
+            queue_when_full: self.queue_when_full,
+            public_result: self.public_result,
+            mature: self.mature,
+        }
+    }
+
+    fn rematch(&self, code: String, focus: Vec<String>, bank: &QuestionBank, story: &Story, key: &MessageKey) -> Room {
+        let mut settings = self.settings(focus);
+        // everyone's already here
+        settings.wait_for_host = false;
+        let now = now_secs();
+        let fresh = |p: &Player| Player {
+            id: p.id.clone(),
+            name: p.name.clone(),
+            score: 0,
+            last_seen: now,
+            locale: p.locale.clone(),
+            notify: p.notify.clone(),
+            referral: p.referral.clone(),
+            consented_at: p.consented_at,
+            session: None,
+            handoff: None,
+        };
+        let mut room = Room::new(code, fresh(&self.players[0]), &settings, bank, story, key, self.tenant.clone());
+        room.players.extend(self.players.iter().skip(1).map(fresh));
+        room.series = self.series.clone();
+        room.played_before = self.played_so_far();
+        // same rules as the first game, dealt again without what they've
+        // had; the couple's consent carries over too
+        room.restrict(self.blocked_categories.clone(), true, bank);
+        room.category_weights = self.category_weights.clone();
+        room.match_threshold = self.match_threshold;
+        if room.in_play() {
+            room.start_question(now);
+        }
+        room
+    }
+
+    // A new room with this one's setup for `host`, who shares the new code
+    // with whoever they play next. `same_questions` deals the very questions
+    // played here, in order, for a re-test; mature ones wait for consent
+    // again and are replaced for now.
+    fn replay(
+        &self,
+        code: String,
+        host: Player,
+        same_questions: bool,
+        bank: &QuestionBank,
+        story: &Story,
+        key: &MessageKey,
+    ) -> Room {
+        let mut room = Room::new(code, host, &self.settings(self.focus.clone()), bank, story, key, self.tenant.clone());
+        room.series = self.series.clone();
+        room.restrict(self.blocked_categories.clone(), true, bank);
+        room.category_weights = self.category_weights.clone();
+        if same_questions && !self.endless && room.story.is_none() {
+            let played: Vec<&Question> = (0..self.questions.len())
+                .filter_map(|idx| self.seen(idx, bank))
+                .filter(|q| !q.mature)
+                .collect();
+            room.questions = played.iter().map(|q| q.id.clone()).collect();
+            room.answers = vec![HashMap::new(); played.len()];
+            room.deck = played.into_iter().map(|q| (q.id.clone(), q.clone())).collect();
+            // tops up whatever was left out
+            room.deal(room.questions.len(), bank);
+        }
+        room
+    }
+
+    // Couple points banked by this room towards its series.
+    fn series_points(&self) -> u32 {
+        self.players.iter().map(|p| p.score).sum()
+    }
+
+    // Unseals the host's surprise the first time its conditions hold.
+    fn reveal_surprise(&mut self, key: &MessageKey, now: u64) {
+        let score = self.compatibility();
+        let Some(surprise) = self.surprise.as_mut() else { return };
+        if surprise.revealed || !surprise.is_unlocked(self.finished, score, now) {
+            return;
+        }
+        surprise.revealed = true;
+        let opened = surprise.open(key);
+        self.log(RoomEvent::SurpriseRevealed {
+            has_text: opened.text.is_some(),
+            has_photo: opened.has_photo,
+        });
+    }
+
+    // Weighted average agreement over resolved questions (0-100), partial
+    // credit included. Rooms from before weights count every question once.
+    fn compatibility(&self) -> u32 {
+        // story rooms: how often you picked the same branch
+        if let Some(progress) = self.story.as_ref().filter(|p| !p.path.is_empty()) {
+            let agreed = progress.path.iter().filter(|s| s.agreed).count();
+            return (agreed * 100 / progress.path.len()) as u32;
+        }
+        if self.credits.is_empty() {
+            return 0;
+        }
+        let (mut earned, mut possible) = (0.0, 0.0);
+        for (idx, credit) in self.credits.iter().enumerate() {
+            let weight = self.credit_weight(idx);
+            earned += credit * weight;
+            possible += weight;
+        }
+        (earned * 100.0 / possible).round() as u32
+    }
+
+    // How the game was played, for comparing scores across games.
+    fn mode(&self) -> &'static str {
+        if self.story.is_some() {
+            "story"
+        } else if self.goal.is_some() {
+            "coop"
+        } else if self.wagers {
+            "guess"
+        } else if self.endless {
+            "endless"
+        } else {
+            "classic"
+        }
+    }
+
+    fn credit_weight(&self, idx: usize) -> f64 {
+        self.credit_weights.get(idx).copied().unwrap_or(1.0)
+    }
+
+    // Friends rooms: how in sync each pair was over the game, closest first.
+    fn pair_scores(&self, bank: &QuestionBank) -> Vec<Value> {
+        let players = self.players.len().max(self.capacity);
+        let mut totals: HashMap<(String, String), (f64, usize)> = HashMap::new();
+        for idx in (0..self.answers.len()).filter(|i| self.answers[*i].len() >= players) {
+            let Some((ids, pairs)) = self.pairwise(idx, self.seen(idx, bank)) else { continue };
+            for &(a, b, credit) in pairs.pairs() {
+                let total = totals.entry((ids[a].clone(), ids[b].clone())).or_default();
+                total.0 += credit;
+                total.1 += 1;
+            }
+        }
+        let name = |id: &str| self.players.iter().find(|p| p.id == id).map(|p| p.name.expose().clone());
+        let mut rows: Vec<(String, String, u32)> = totals
+            .into_iter()
+            .map(|((a, b), (sum, n))| {
+                let pct = (sum * 100.0 / n as f64).round() as u32;
+                (name(a.as_str()).unwrap_or_default(), name(b.as_str()).unwrap_or_default(), pct)
+            })
+            .collect();
+        rows.sort_by(|x, y| y.2.cmp(&x.2).then_with(|| (&x.0, &x.1).cmp(&(&y.0, &y.1))));
+        rows.into_iter()
+            .map(|(a, b, pct)| json!({ "players": [a, b], "score": pct, "level": Level::hint(pct) }))
+            .collect()
+    }
+
+    // One row per resolved question for the result page: what was asked and
+    // how much you agreed on it.
+    fn breakdown(&self, bank: &QuestionBank) -> Vec<Value> {
+        self.credits
+            .iter()
+            .enumerate()
+            .map(|(idx, credit)| {
+                let question = self.seen(idx, bank);
+                let credit_pct = (credit * 100.0).round() as u32;
+                json!({
+                    "number": idx + 1,
+                    "question": question.map(|q| q.text.clone()),
+                    "category": question.map(|q| q.category.clone()),
+                    "credit_pct": credit_pct,
+                    "level": Level::hint(credit_pct),
+                    "matched": *credit >= 1.0,
+                    "weight": self.credit_weight(idx),
+                })
+            })
+            .collect()
+    }
+}
+
+// --- Routes ---
+
+#[get("/")]
+// `?ref=` is picked up by the `Referral` guard and kept for later.
+fn index(site: &State<Site>, variants: Variants, _referral: Referral) -> Template {
+    Template::render(
+        "index",
+        context! {
+            title: "Welcome Moyosola 💖",
+            subtitle: "Created with love by Kamzy 💙",
+            meta: site.meta("/", "Welcome Moyosola 💖", None, true),
+            // experiment -> variant, for copy and layout tests
+            experiments: variants.assigned,
+        },
+    )
+}
+
+// `from` is a snapshot token from an earlier game: the page then suggests
+// what the couple agreed on least, ticked and dealt first.
+#[get("/create?<from>&<preset>")]
+#[allow(clippy::result_large_err, clippy::too_many_arguments)]
+fn create_room_get(
+    from: Option<String>,
+    preset: Option<String>,
+    site: &State<Site>,
+    age: AgeGate,
+    restrictions: Restrictions,
+    bank: Bank,
+    tenant: Tenant,
+    recommender: &State<Recommender>,
+    features: &State<Features>,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Template, Redirect> {
+    age.check("/create")?;
+    let pool = || bank.drawable(tenant.0.as_deref()).filter(|q| restrictions.allows(q) && !q.mature);
+    let recommended = from.and_then(|token| recommender.for_token(&token, pool())).unwrap_or_default();
+    // what this host could be dealt, same as the preview
+    let mut categories: Vec<&str> = pool().map(|q| q.category.as_str()).collect();
+    categories.sort();
+    categories.dedup();
+    // the host's saved settings, the default one unless they picked another
+    let presets = Presets::load(cookies, cookie_keys);
+    let chosen = presets.pick(preset.as_deref()).cloned();
+    let picked = match &chosen {
+        Some(p) if recommended.spots.is_empty() => p.categories.clone(),
+        _ => recommended.categories(),
+    };
+    let preset_names: Vec<&str> = presets.items.iter().map(|p| p.name.as_str()).collect();
+    Ok(Template::render(
+        "create",
+        context! {
+            meta: site.meta("/create", "Create a room", None, true),
+            // the 18+ option isn't offered where the region rules it out
+            mature_allowed: restrictions.mature,
+            categories,
+            question_counts: QUESTION_COUNTS,
+            default_count: chosen.as_ref().and_then(|p| p.question_count).unwrap_or(QUESTIONS_PER_GAME),
+            custom_max: custom::MAX_PER_ROOM,
+            max_group_size: scoring::MAX_GROUP_SIZE,
+            default_group_size: scoring::DEFAULT_GROUP_SIZE,
+            presets: preset_names,
+            preset: chosen,
+            picked,
+            focus: recommended.question_ids(),
+            recommended,
+            surprise_photos: features.surprise_photos,
+        },
+    ))
+}
+
+// Spoiler-safe peek at what will be dealt, so the host knows what they're
+// signing their partner up for. `category` repeats to narrow the list.
+#[get("/create/preview?<category>&<lang>")]
+#[allow(clippy::result_large_err)]
+fn create_preview_get(
+    bank: Bank,
+    tenant: Tenant,
+    accept: AcceptLanguage,
+    age: AgeGate,
+    restrictions: Restrictions,
+    category: Vec<String>,
+    lang: Option<String>,
+) -> Result<Template, Redirect> {
+    age.check("/create/preview")?;
+    let locale = accept.resolve(lang.as_deref());
+    let offered = || bank.drawable(tenant.0.as_deref()).filter(|q| restrictions.allows(q));
+    let mut categories: Vec<&str> = offered().map(|q| q.category.as_str()).collect();
+    categories.sort();
+    categories.dedup();
+    let mut packs: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for q in offered() {
+        if category.is_empty() || category.contains(&q.category) {
+            packs.entry(q.category.as_str()).or_default().push(q.teaser(&locale, PREVIEW_CHARS));
+        }
+    }
+    let packs: Vec<_> = packs
+        .into_iter()
+        .map(|(name, titles)| json!({ "category": name, "titles": titles }))
+        .collect();
+    Ok(Template::render(
+        "create_preview",
+        context! { categories, selected: category, packs, lang: locale },
+    ))
+}
+
+#[post("/create", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
+async fn create_room_post(
+    _limit: FormLimit<scope::Create>,
+    form: Form<CreateRoomForm<'_>>,
+    state: &State<AppState>,
+    bank: Bank,
+    story: &State<Story>,
+    key: &State<MessageKey>,
+    outbox: &State<Outbox>,
+    tenant: Tenant,
+    variants: Variants,
+    referral: Referral,
+    age: AgeGate,
+    restrictions: Restrictions,
+    scheme: &State<CodeScheme>,
+    weights: &State<CategoryWeights>,
+    features: &State<Features>,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Redirect, CreateError> {
+    if !age.passed() {
+        return Err(CreateError::Underage);
+    }
+    if form.question_count.is_some_and(|n| !QUESTION_COUNTS.contains(&n)) {
+        return Err(CreateError::QuestionCount);
+    }
+    if form.capacity.is_some_and(|n| !form.game_mode.unwrap_or_default().capacities().contains(&n)) {
+        return Err(CreateError::Capacity);
+    }
+    let unlock_day = match form.surprise_date.as_deref().filter(|d| !d.is_empty()) {
+        Some(date) => Some(surprise::parse_day(date).ok_or(CreateError::SurpriseDate)?),
+        None => None,
+    };
+    // the form doesn't offer a photo then; anything sent anyway is dropped
+    let photo = match form.surprise_photo.as_ref().filter(|f| !f.is_empty() && features.surprise_photos) {
+        Some(file) => Some(read_photo(file).await.map_err(CreateError::Photo)?),
+        None => None,
+    };
+
+    let locale = form.lang.as_deref().and_then(i18n::normalize);
+    // held until the room is in, so nobody else can draw the same code
+    let mut map = state.rooms.write();
+    if !features.room_fits(map.len()) {
+        return Err(CreateError::Full);
+    }
+    let code = RoomCode::generate_unique(**scheme, locale.as_deref(), |c| state.code_taken(&map, c))
+        .ok_or(CreateError::NoFreeCode)?
+        .into_string();
+    let mut host = new_player(form.host_name.clone(), locale, referral.0.clone());
+    // ticking the box is the host's own consent
+    host.consented_at = (form.mature && restrictions.mature).then(now_secs);
+    let host_id = host.id.clone();
+    identity::remember(cookies, cookie_keys, &code, &host_id, host.session.as_deref());
+    let mut room = Room::new(code.clone(), host, &form, &bank, story, key, tenant.0);
+    room.restrict(restrictions.blocked, restrictions.mature, &bank);
+    room.category_weights = weights.inner().clone();
+    room.referral = referral.0;
+    room.surprise = Surprise::seal(
+        key,
+        form.surprise_text.as_deref(),
+        photo,
+        form.surprise_min_score,
+        unlock_day,
+    );
+    room.recount();
+    map.insert(code.clone(), room);
+    drop(map);
+    outbox.notify_webhook(json!({ "event": "room_created", "code": code }));
+    variants.convert("room_created");
+
+    // host is already in the room, send them straight to the lobby
+    Ok(Redirect::to(uri!(play_get(code = code, player = Some(host_id)))))
+}
+
+// Shown when `max_rooms` are open already.
+const ROOMS_FULL: &str = "We're hosting as many games as we can right now. Please try again in a few minutes.";
+
+// The create form's failures, as a page saying what happened.
+enum CreateError {
+    // hasn't been through the age gate
+    Underage,
+    QuestionCount,
+    // seats outside what the game mode allows
+    Capacity,
+    SurpriseDate,
+    // every code drawn was in use
+    NoFreeCode,
+    // `max_rooms` are open already
+    Full,
+    // from read_photo
+    Photo(Status),
+}
+
+impl<'r> Responder<'r, 'static> for CreateError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let (status, message) = match self {
+            CreateError::Underage => (Status::Forbidden, "Confirm your age on the home page before creating a room."),
+            CreateError::QuestionCount => (Status::UnprocessableEntity, "Pick 10, 25 or 50 questions."),
+            CreateError::Capacity => (Status::UnprocessableEntity, "Couples play as two; groups seat 2 to 8 players."),
+            CreateError::SurpriseDate => {
+                (Status::UnprocessableEntity, "The surprise date should look like 2025-02-14.")
+            }
+            CreateError::NoFreeCode => {
+                (Status::ServiceUnavailable, "We couldn't find a free room code just now. Please try again.")
+            }
+            CreateError::Full => (Status::ServiceUnavailable, ROOMS_FULL),
+            CreateError::Photo(status) if status == Status::UnsupportedMediaType => {
+                (Status::UnsupportedMediaType, "The surprise photo has to be an image.")
+            }
+            // too large has its own page, see limits.rs
+            CreateError::Photo(status) => return Err(status),
+        };
+        errors::page(status, message).respond_to(req)
+    }
+}
+
+// Uploaded surprise photo: images only, capped in size. Rocket stops writing
+// at the `file` limit, so an incomplete file was over it.
+async fn read_photo(file: &Capped<TempFile<'_>>) -> Result<(Vec<u8>, String), Status> {
+    use rocket::tokio::io::AsyncReadExt;
+
+    let content_type = file
+        .content_type()
+        .filter(|ct| ct.top() == "image")
+        .ok_or(Status::UnsupportedMediaType)?
+        .to_string();
+    if !file.is_complete() || file.len() as usize > surprise::MAX_PHOTO_BYTES {
+        return Err(Status::PayloadTooLarge);
+    }
+    let mut bytes = Vec::new();
+    file.open()
+        .await
+        .map_err(|_| Status::BadRequest)?
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|_| Status::BadRequest)?;
+    Ok((bytes, content_type))
+}
+
+// Host-only, by their cookie, from the lobby: questions of their own for
+// this room, mixed in with the bank's.
+#[post("/create/questions", data = "<form>")]
+fn custom_questions_post(
+    form: Form<CustomQuestionsForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    bank: Bank,
+) -> Result<Redirect, (Status, Template)> {
+    let CustomQuestionsForm { code, question } = form.into_inner();
+    let invalid = |message: &'static str| errors::page(Status::UnprocessableEntity, message);
+    let mut texts = Vec::new();
+    for raw in &question {
+        texts.extend(custom::clean(raw).map_err(invalid)?);
+    }
+    let mut map = state.rooms.write();
+    let room = map
+        .get_mut(&code)
+        .ok_or_else(|| errors::page(Status::NotFound, "That room doesn't exist (any more)."))?;
+    if !room.bound(&me) || !room.is_host(&me.id) {
+        return Err(errors::page(Status::Forbidden, "Only the host can add questions to this room."));
+    }
+    if !texts.is_empty() && !room.add_custom(texts, &bank) {
+        return Err(invalid("Add up to 5 questions of your own, before the game begins."));
+    }
+    Ok(Redirect::to(uri!(play_get(code = code, player = Some(me.id)))))
+}
+
+#[get("/join?<code>")]
+#[allow(clippy::result_large_err)]
+fn join_room_get(
+    code: Option<String>,
+    site: &State<Site>,
+    _referral: Referral,
+    age: AgeGate,
+    state: &State<AppState>,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Template, Redirect> {
+    match code.as_deref() {
+        Some(code) => age.check(&format!("/join?code={}", code))?,
+        None => age.check("/join")?,
+    }
+    // back button from a room this browser is already in
+    if let Some(code) = code.as_deref().and_then(RoomCode::parse).map(RoomCode::into_string) {
+        let map = state.rooms.read();
+        let back = map
+            .get(&code)
+            .zip(identity::recall(cookies, cookie_keys, &code))
+            .and_then(|(room, me)| rejoin(room, &me));
+        if let Some(back) = back {
+            return Err(back);
+        }
+        drop(map);
+        // an old invite link
+        if state.is_expired(&code) {
+            return Ok(Template::render("expired", context! { code }));
+        }
+    }
+    Ok(Template::render(
+        "join",
+        context! {
+            code: code.unwrap_or_default(),
+            error: "",
+            meta: site.meta("/join", "Join a room", None, true),
+        },
+    ))
+}
+
+// Live room updates for open pages: every event the room logs, as JSON
+// (`type` says which). Ends when the room goes away or the server stops.
+// Events carry player ids and answers, so only the room's own browsers
+// get them.
+#[get("/events/<code>")]
+fn events_get(
+    code: String,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], Status> {
+    let (mut rx, version) = {
+        let mut map = state.rooms.write();
+        let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+        if !room.bound(&me) {
+            return Err(Status::Forbidden);
+        }
+        (room.subscribe(), room.version())
+    };
+    Ok(EventStream! {
+        // first, where the room is now: a page rendered before a change it
+        // missed (another tab, a dropped connection) sees it's out of date
+        yield Event::json(&json!({ "type": "sync", "version": version.to_string() }));
+        loop {
+            let event = select! {
+                received = rx.recv() => match received {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => break,
+                    // fell behind; the page reloads anyway
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut shutdown => break,
+            };
+            yield Event::json(&event);
+        }
+    })
+}
+
+// Liveness, for load balancers and deploy checks: 200 whenever the process
+// answers at all. Also says whether templates are reloaded from disk or
+// compiled once (see checks::template_mode).
+#[get("/healthz")]
+fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok", "templates": crate::checks::template_mode() }))
+}
+
+// Readiness: 503 until pages can render, there are questions to deal, and,
+// with `room_storage = "file"` or `"sqlite"`, rooms can be saved. Each check
+// says why.
+#[get("/readyz")]
+async fn readyz(
+    templates: Option<Metadata<'_>>,
+    questions: &State<QuestionStore>,
+    room_storage: Option<&State<RoomStorage>>,
+) -> (Status, Json<Value>) {
+    let storage = match room_storage.filter(|s| s.is_persistent()) {
+        Some(storage) => Some(storage.ping().await),
+        None => None,
+    };
+    let mut checks = Map::new();
+    let mut check = |name: &str, outcome: Result<(), String>| {
+        let body = match &outcome {
+            Ok(()) => json!({ "status": "ok" }),
+            Err(e) => json!({ "status": "failing", "error": e }),
+        };
+        checks.insert(name.to_string(), body);
+        outcome.is_ok()
+    };
+    let templates_ok = check(
+        "templates",
+        match templates {
+            Some(meta) if meta.contains_template("index") => Ok(()),
+            Some(_) => Err("templates loaded without the index page".to_string()),
+            None => Err("template engine not attached".to_string()),
+        },
+    );
+    let count = questions.snapshot().questions.len();
+    let bank_ok = check(
+        "question_bank",
+        if count > 0 { Ok(()) } else { Err("no questions loaded".to_string()) },
+    );
+    let storage_ok = match storage {
+        Some(ping) => check("room_storage", ping),
+        None => true,
+    };
+    let ready = templates_ok && bank_ok && storage_ok;
+    let status = if ready { Status::Ok } else { Status::ServiceUnavailable };
+    let body = json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "checks": checks,
+    });
+    (status, Json(body))
+}
+
+// Home, create, join and the word cloud, plus results their hosts made public.
+#[get("/sitemap.xml")]
+fn sitemap_get(site: &State<Site>, state: &State<AppState>) -> Option<(ContentType, String)> {
+    let mut pages: Vec<(String, Option<u64>)> = ["/", "/create", "/join", "/words"]
+        .iter()
+        .map(|path| (path.to_string(), None))
+        .collect();
+    let map = state.rooms.read();
+    let mut results: Vec<(String, Option<u64>)> = map
+        .values()
+        .filter(|room| room.finished && room.public_result)
+        .map(|room| {
+            let finished_at = room.events.iter().rev().find_map(|e| match e.event {
+                RoomEvent::GameFinished { .. } => Some(e.at),
+                _ => None,
+            });
+            (format!("/result/{}", room.code), finished_at)
+        })
+        .collect();
+    results.sort();
+    pages.extend(results);
+    Some((ContentType::XML, site.sitemap(&pages)?))
+}
+
+// A page the host can hold up for their partner to scan.
+#[get("/invite/<code>")]
+fn invite_get(
+    code: String,
+    state: &State<AppState>,
+    site: &State<Site>,
+    features: &State<Features>,
+    host: Option<&Host<'_>>,
+) -> Result<Template, Status> {
+    if !state.rooms.read().contains_key(&code) {
+        if state.is_expired(&code) {
+            return Ok(Template::render("expired", context! { code }));
+        }
+        return Err(Status::NotFound);
+    }
+    let join_url = invite::join_url(site, host, &code).unwrap_or_else(|| format!("/join?code={}", code));
+    Ok(Template::render(
+        "invite",
+        context! {
+            code,
+            join_url,
+            qr_codes: features.qr_codes,
+            meta: site.meta("/join", "You're invited", None, false),
+        },
+    ))
+}
+
+#[get("/invite/<code>/qr.png")]
+fn invite_qr_get(
+    code: String,
+    state: &State<AppState>,
+    site: &State<Site>,
+    features: &State<Features>,
+    host: Option<&Host<'_>>,
+) -> Result<(ContentType, Vec<u8>), Status> {
+    if !features.qr_codes || !state.rooms.read().contains_key(&code) {
+        return Err(Status::NotFound);
+    }
+    let url = invite::join_url(site, host, &code).ok_or(Status::NotFound)?;
+    let png = invite::qr_png(&url).ok_or(Status::InternalServerError)?;
+    Ok((ContentType::PNG, png))
+}
+
+#[post("/join", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
+fn join_room_post(
+    _limit: FormLimit<scope::Join>,
+    form: Form<JoinRoomForm>,
+    state: &State<AppState>,
+    key: &State<MessageKey>,
+    outbox: &State<Outbox>,
+    audit: &State<AuditLog>,
+    referral: Referral,
+    age: AgeGate,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Redirect, JoinError> {
+    if !age.passed() {
+        return Err(JoinError::Underage);
+    }
+    // either code scheme, however it was typed
+    let code = RoomCode::parse(&form.code).map(RoomCode::into_string).unwrap_or_default();
+    let mut map = state.rooms.write();
+    if let Some(room) = map.get_mut(&code) {
+        // a refresh or double-click resubmits the form; don't seat them twice
+        if let Some(back) = identity::recall(cookies, cookie_keys, &code).and_then(|me| rejoin(room, &me)) {
+            return Ok(back);
+        }
+        let p = new_player(
+            form.name.clone(),
+            form.lang.as_deref().and_then(i18n::normalize),
+            referral.0,
+        );
+        let (player_id, session) = (p.id.clone(), p.session.clone());
+        let seat = admit(room, p, form.passphrase.as_deref(), key, outbox, audit)?;
+        identity::remember(cookies, cookie_keys, &code, &player_id, session.as_deref());
+        Ok(match seat {
+            Seat::Queued | Seat::Pending => Redirect::to(uri!(pending_get(code = code, player = player_id))),
+            // mature rooms wait at the consent gate instead
+            Seat::Consent => Redirect::to(uri!(consent_get(code = code, player = player_id))),
+            Seat::Playing => Redirect::to(uri!(play_get(code = code, player = Some(player_id)))),
+        })
+    } else {
+        // back to join with error
+        Ok(Redirect::to(uri!(join_room_get(Some(form.code.to_string())))))
+    }
+}
+
+// A player new to the room, signed in on the browser they joined from.
+fn new_player(name: String, locale: Option<String>, referral: Option<String>) -> Player {
+    Player {
+        id: Uuid::new_v4().to_string(),
+        name: name.into(),
+        score: 0,
+        last_seen: now_secs(),
+        locale,
+        notify: NotifyPrefs::default(),
+        referral,
+        consented_at: None,
+        session: Some(identity::new_session()),
+        handoff: None,
+    }
+}
+
+// Where a joiner ended up.
+enum Seat {
+    Playing,
+    // seated, but the consent gate comes first
+    Consent,
+    // waiting for the host
+    Pending,
+    // waiting for a free seat
+    Queued,
+}
+
+// Why a joiner was turned away.
+enum JoinRefused {
+    Full,
+    // the waiting line or the host's pile of requests is as long as it gets
+    LineFull,
+    WrongPassphrase,
+    // too many wrong passphrases; try again later
+    Locked,
+    // the room's passphrase can't be checked any more
+    Unreadable,
+}
+
+impl JoinRefused {
+    fn status(self) -> Status {
+        match self {
+            JoinRefused::Full => Status::BadRequest,
+            JoinRefused::WrongPassphrase => Status::Forbidden,
+            JoinRefused::LineFull | JoinRefused::Locked => Status::TooManyRequests,
+            JoinRefused::Unreadable => Status::Conflict,
+        }
+    }
+
+    fn api_error(self) -> ApiError {
+        let too_many = Status::TooManyRequests;
+        match self {
+            JoinRefused::Full => ApiError::new(Status::Conflict, "room_full", None, "this room is full"),
+            JoinRefused::LineFull => ApiError::new(too_many, "line_full", None, "too many people are waiting to join"),
+            JoinRefused::WrongPassphrase => {
+                ApiError::invalid("passphrase", "wrong_passphrase", "that passphrase doesn't open this room")
+            }
+            JoinRefused::Locked => {
+                ApiError::new(too_many, "locked", Some("passphrase"), "too many wrong passphrases; try again later")
+            }
+            JoinRefused::Unreadable => ApiError::new(
+                Status::Conflict,
+                "passphrase_unreadable",
+                Some("passphrase"),
+                "this room's passphrase can no longer be checked; ask the host to start a new room",
+            ),
+        }
+    }
+}
+
+// The join form's failures, as a page saying what happened.
+enum JoinError {
+    // hasn't been through the age gate
+    Underage,
+    Refused(JoinRefused),
+}
+
+impl From<JoinRefused> for JoinError {
+    fn from(refused: JoinRefused) -> Self {
+        JoinError::Refused(refused)
+    }
+}
+
+impl<'r> Responder<'r, 'static> for JoinError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let message = match self {
+            JoinError::Underage => "Confirm your age on the home page before joining a room.",
+            JoinError::Refused(JoinRefused::Full) => "This room already has everyone it needs.",
+            JoinError::Refused(JoinRefused::LineFull) => {
+                "Too many people are already waiting to join this room. Try again later."
+            }
+            JoinError::Refused(JoinRefused::WrongPassphrase) => {
+                "That passphrase doesn't open this room. Check with the host and try again."
+            }
+            JoinError::Refused(JoinRefused::Locked) => {
+                "Too many wrong passphrases for this room. Wait a few minutes before trying again."
+            }
+            JoinError::Refused(JoinRefused::Unreadable) => {
+                "This room's passphrase can no longer be checked. Ask the host to start a new room."
+            }
+        };
+        let status = match self {
+            JoinError::Underage => Status::Forbidden,
+            JoinError::Refused(refused) => refused.status(),
+        };
+        errors::page(status, message).respond_to(req)
+    }
+}
+
+// Lets `p` into `room`: a seat if there's one, else the line (rooms that
+// queue) or the host's approval (rooms that ask). Shared by the join form
+// and the API; the caller signs the browser in.
+fn admit(
+    room: &mut Room,
+    p: Player,
+    passphrase: Option<&str>,
+    key: &MessageKey,
+    outbox: &Outbox,
+    audit: &AuditLog,
+) -> Result<Seat, JoinRefused> {
+    let full = room.is_full();
+    if full && !room.queue_when_full {
+        return Err(JoinRefused::Full);
+    }
+    if full && room.queue.len() >= MAX_QUEUED_JOINS {
+        return Err(JoinRefused::LineFull);
+    }
+    if room.approve_joins && room.pending.len() >= MAX_PENDING_JOINS {
+        return Err(JoinRefused::LineFull);
+    }
+    match room.check_passphrase(passphrase, key, now_secs()) {
+        PassphraseCheck::Ok => {}
+        PassphraseCheck::Wrong => return Err(JoinRefused::WrongPassphrase),
+        PassphraseCheck::Locked => return Err(JoinRefused::Locked),
+        PassphraseCheck::Unreadable => return Err(JoinRefused::Unreadable),
+        PassphraseCheck::JustLocked => {
+            outbox.notify_webhook(json!({
+                "event": "join_locked",
+                "code": room.code,
+                "until": room.join_guard.locked_until,
+            }));
+            audit.record(
+                "system",
+                "join_locked",
+                Some(&room.code),
+                json!({ "until": room.join_guard.locked_until }),
+            );
+            return Err(JoinRefused::Locked);
+        }
+    }
+    let player_id = p.id.clone();
+    let player_name = p.name.expose().clone();
+    if full {
+        room.queue.push(p);
+        room.log(RoomEvent::JoinQueued {
+            player_id,
+            player_name,
+            position: room.queue.len(),
+        });
+        return Ok(Seat::Queued);
+    }
+    // approval rooms park them until the host decides
+    if room.approve_joins {
+        room.log(RoomEvent::JoinRequested { player_id, player_name });
+        room.pending.push(p);
+        return Ok(Seat::Pending);
+    }
+    room.log(RoomEvent::PlayerJoined {
+        player_id: player_id.clone(),
+        player_name,
+    });
+    room.players.push(p);
+    if room.needs_consent(&player_id) {
+        return Ok(Seat::Consent);
+    }
+    // room is complete, first question's clock starts now
+    if room.in_play() {
+        room.start_question(now_secs());
+    }
+    Ok(Seat::Playing)
+}
+
+// Where someone this browser already joined as belongs: their seat (via the
+// consent gate if it's still ahead), or their place in line. None if the
+// room doesn't know them (turned away, left, or another room's cookie).
+fn rejoin(room: &Room, me: &CurrentPlayer) -> Option<Redirect> {
+    if !room.bound(me) {
+        return None;
+    }
+    let code = room.code.clone();
+    let (player_id, player) = (me.id.as_str(), me.id.clone());
+    if room.needs_consent(player_id) {
+        return Some(Redirect::to(uri!(consent_get(code = code, player = player))));
+    }
+    if room.has_player(player_id) {
+        return Some(Redirect::to(uri!(play_get(code = code, player = Some(player)))));
+    }
+    let waiting = room.pending.iter().chain(&room.queue).any(|p| p.id == player_id);
+    waiting.then(|| Redirect::to(uri!(pending_get(code = code, player = player))))
+}
+
+// Where a joiner waits: for the host's answer in approval rooms, or for a
+// seat when the room was full.
+#[get("/play/<code>/pending?<player>")]
+#[allow(clippy::result_large_err)]
+fn pending_get(code: String, player: String, state: &State<AppState>) -> Result<Template, Redirect> {
+    let map = state.rooms.read();
+    let Some(room) = map.get(&code) else {
+        return Err(Redirect::to(uri!(join_room_get(Some(code)))));
+    };
+    if room.has_player(&player) {
+        return Err(Redirect::to(uri!(play_get(code = code, player = Some(player)))));
+    }
+    let waiting = room.pending.iter().any(|p| p.id == player);
+    // 1 = next
+    let position = room.queue.iter().position(|p| p.id == player).map(|i| i + 1);
+    if !waiting && position.is_none() && !room.was_rejected(&player) {
+        return Err(Redirect::to(uri!(join_room_get(Some(code)))));
+    }
+    let host = room.players.first().map(|p| p.name.clone()).unwrap_or_default();
+    Ok(Template::render(
+        "pending",
+        context! { code: &code, player: &player, host, waiting, position },
+    ))
+}
+
+// "Continue on my phone": a code, good for a few minutes, that the other
+// device enters at /handoff. Claiming it moves the player over and signs
+// this device out.
+#[post("/play/<code>/handoff")]
+fn handoff_post(code: String, me: CurrentPlayer, state: &State<AppState>) -> Result<Template, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) {
+        return Err(Status::Forbidden);
+    }
+    let player = room.players.iter_mut().find(|p| p.id == me.id).ok_or(Status::Forbidden)?;
+    let transfer = identity::handoff_code();
+    player.handoff = Some(Handoff {
+        code: transfer.clone(),
+        expires_at: now_secs() + identity::HANDOFF_TTL_SECS,
+    });
+    Ok(Template::render(
+        "handoff",
+        context! { code: &code, player: &me.id, transfer, minutes: identity::HANDOFF_TTL_SECS / 60 },
+    ))
+}
+
+#[get("/handoff?<code>")]
+fn claim_get(code: Option<String>) -> Template {
+    Template::render("handoff_claim", context! { code: code.unwrap_or_default(), error: false })
+}
+
+#[post("/handoff", data = "<form>")]
+fn claim_post(
+    form: Form<ClaimForm>,
+    state: &State<AppState>,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Redirect, Template> {
+    let wanted: String = form.code.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_uppercase();
+    let now = now_secs();
+    let mut map = state.rooms.write();
+    for room in map.values_mut() {
+        if let Some((id, session)) = room.claim_handoff(&wanted, now) {
+            identity::remember(cookies, cookie_keys, &room.code, &id, Some(&session));
+            return Ok(Redirect::to(uri!(play_get(code = room.code.clone(), player = Some(id)))));
+        }
+    }
+    Err(Template::render("handoff_claim", context! { code: &form.code, error: true }))
+}
+
+// A partner steps out before the game gets going, or a latecomer gives up
+// their place in line.
+#[post("/play/<code>/leave")]
+fn leave_post(code: String, me: CurrentPlayer, state: &State<AppState>) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) {
+        return Err(Status::Forbidden);
+    }
+    if !room.leave(&me.id, now_secs()) {
+        return Err(Status::BadRequest);
+    }
+    Ok(Redirect::to(uri!(index)))
+}
+
+// Host-only: let a waiting joiner in, or turn them away.
+#[post("/room/<code>/approve/<player_id>", data = "<form>")]
+fn approve_post(
+    code: String,
+    player_id: String,
+    form: Form<ApproveForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) || !room.is_host(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    if !room.decide_join(&player_id, form.approve, now_secs()) {
+        return Err(Status::BadRequest);
+    }
+    audit.record(
+        &format!("host:{}", room.players[0].name.expose()),
+        if form.approve { "join_approved" } else { "join_rejected" },
+        Some(&code),
+        json!({ "player_id": player_id }),
+    );
+    Ok(Redirect::to(uri!(play_get(
+        code = code,
+        player = Some(form.player.clone())
+    ))))
+}
+
+// Host-only, by their cookie: begin with whoever is seated.
+#[post("/room/<code>/start")]
+fn start_post(code: String, me: CurrentPlayer, state: &State<AppState>) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) || !room.is_host(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    if !room.start(now_secs()) {
+        return Err(Status::BadRequest);
+    }
+    Ok(Redirect::to(uri!(play_get(code = code, player = Some(me.id)))))
+}
+
+// Host-only: remove a stray joiner, from their seat or from the line.
+#[post("/room/<code>/kick/<player_id>")]
+fn kick_post(
+    code: String,
+    player_id: String,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) || !room.is_host(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    if player_id == me.id || !room.kick(&player_id, now_secs()) {
+        return Err(Status::BadRequest);
+    }
+    audit.record(
+        &format!("host:{}", room.players[0].name.expose()),
+        "player_kicked",
+        Some(&code),
+        json!({ "player_id": player_id }),
+    );
+    Ok(Redirect::to(uri!(play_get(code = code, player = Some(me.id)))))
+}
+
+// Host-only: end the game for everyone, straight to the result.
+#[post("/room/<code>/close")]
+fn close_post(code: String, me: CurrentPlayer, state: &State<AppState>) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) || !room.is_host(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    if !room.close() {
+        return Err(Status::BadRequest);
+    }
+    Ok(Redirect::to(uri!(result_get(code = code))))
+}
+
+// Mature rooms: everyone says yes to 18+ questions here before playing.
+#[get("/play/<code>/consent?<player>")]
+#[allow(clippy::result_large_err)]
+fn consent_get(
+    code: String,
+    player: String,
+    current: Option<CurrentPlayer>,
+    state: &State<AppState>,
+) -> Result<Template, Redirect> {
+    let map = state.rooms.read();
+    let mine = |r: &&Room| current.as_ref().is_some_and(|me| acts_as(r, me, &player));
+    let Some(room) = map.get(&code).filter(mine).filter(|r| r.needs_consent(&player)) else {
+        return Err(Redirect::to(uri!(play_get(code = code, player = Some(player)))));
+    };
+    let host = room.players.first().map(|p| p.name.clone()).unwrap_or_default();
+    Ok(Template::render("consent", context! { code: &code, player: &player, host }))
+}
+
+#[post("/play/<code>/consent", data = "<form>")]
+fn consent_post(
+    code: String,
+    form: Form<ConsentForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    bank: Bank,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) {
+        return Err(Status::Forbidden);
+    }
+    if !room.needs_consent(&form.player) {
+        return Err(Status::BadRequest);
+    }
+    room.consent(&form.player, form.agree, &bank, now_secs());
+    let name = room.players.iter().find(|p| p.id == form.player).map(|p| p.name.as_str()).unwrap_or_default();
+    audit.record(
+        &format!("player:{}", name),
+        if form.agree { "mature_consented" } else { "mature_declined" },
+        Some(&code),
+        json!({}),
+    );
+    Ok(Redirect::to(uri!(play_get(code = code, player = Some(form.player.clone())))))
+}
+
+#[get("/play/<code>?<player>")]
+#[allow(clippy::result_large_err, clippy::too_many_arguments)]
+async fn play_get(
+    code: String,
+    player: Option<String>,
+    state: &State<AppState>,
+    bank: Bank,
+    assets: &State<AssetStore>,
+    translations: &State<Translations>,
+    accept: AcceptLanguage,
+    conditional: Conditional,
+    current: Option<CurrentPlayer>,
+) -> Result<Fresh<Negotiated>, Redirect> {
+    let (player, moved_away) = match state.rooms.read().get(&code) {
+        Some(room) => room.viewer(current.as_ref()),
+        None => (player, false),
+    };
+    // a poll that changes nothing stops here, before translating or rendering
+    let (validator, moved_on) = {
+        let now = now_secs();
+        let map = state.rooms.read();
+        match map.get(&code) {
+            Some(room) => {
+                let at_gate = player.as_deref().is_some_and(|id| room.needs_consent(id));
+                let moved_on =
+                    room.finished || room.in_lightning() || room.story.is_some() || at_gate || room.calibrating();
+                // the countdown and the passphrase lock move with the clock
+                let lock_mins = room.join_guard.locked_until.saturating_sub(now).div_ceil(60);
+                let version = (room.version(), room.timer.remaining(now), lock_mins);
+                let me = player.as_deref().and_then(|id| room.players.iter().find(|p| p.id == id));
+                let locale = accept.resolve(me.and_then(|p| p.locale.as_deref()));
+                let modified = room.stamp.since(hash_of(&version), now);
+                (conditional.validator((version, &player, locale), modified), moved_on)
+            }
+            None => (conditional.validator(&code, now), false),
+        }
+    };
+    // rooms that moved on redirect below, HEAD or not
+    let validator = match conditional.check(validator) {
+        Ok(validator) => validator,
+        Err(fresh) if !moved_on => return Ok(fresh),
+        Err(fresh) => fresh.into_validator(),
+    };
+
+    // partner's free-text answers in the viewer's language; done before
+    // taking the lock for the page since providers may be remote
+    let mut translated: HashMap<String, String> = HashMap::new();
+    for (key, author, text, from, to) in pending_translations(&code, player.as_deref(), state, &bank, &accept) {
+        if let Some(t) = translations.translate_answer(key, &text, &from, &to).await {
+            translated.insert(author, t);
+        }
+    }
+
+    let map = state.rooms.read();
+    let maybe_room = map.get(&code);
+
+    if let Some(room) = maybe_room {
+        if room.finished {
+            return Err(Redirect::to(uri!(result_get(code = code))));
+        }
+        if room.in_lightning() {
+            return Err(Redirect::to(uri!(lightning_get(code = code, player = player))));
+        }
+        if room.story.is_some() {
+            return Err(Redirect::to(uri!(story_get(code = code, player = player))));
+        }
+        if let Some(id) = player.as_deref().filter(|id| room.needs_consent(id)) {
+            return Err(Redirect::to(uri!(consent_get(code = code, player = id))));
+        }
+        if room.calibrating() {
+            return Err(Redirect::to(uri!(calibrate_get(code = code, player = player))));
+        }
+        let players: Vec<String> = room.players.iter().map(|p| p.name.expose().clone()).collect();
+        let player = player.filter(|id| room.has_player(id));
+        let is_host = player.as_deref().map(|id| room.is_host(id)).unwrap_or(false);
+        let can_leave = player.as_deref().is_some_and(|id| room.can_leave(id));
+        let can_start = is_host && room.can_start();
+        // the host's own questions stay a surprise for everyone else
+        let custom_questions: Vec<&str> = if is_host {
+            room.custom_questions.iter().map(|q| q.text.as_str()).collect()
+        } else {
+            Vec::new()
+        };
+        let custom_slots = (is_host && room.can_add_custom()).then(|| custom::MAX_PER_ROOM - custom_questions.len());
+        let me = player.as_deref().and_then(|id| room.players.iter().find(|p| p.id == id));
+        let balance = me.map(|p| p.score).unwrap_or(0);
+        // resolved for whoever is looking; partners may differ
+        let locale = accept.resolve(me.and_then(|p| p.locale.as_deref()));
+        let current = room.current_question(&bank);
+        let question = current.map(|q| q.text_for(&locale).to_string());
+        let blanks = current.map(|q| q.blanks).unwrap_or(1);
+        let ranking_options = match current.map(|q| &q.kind) {
+            Some(QuestionKind::Ranking { options }) => Some(options.clone()),
+            _ => None,
+        };
+        let picture_options = match current.map(|q| &q.kind) {
+            Some(QuestionKind::PictureChoice { options }) => Some(
+                options
+                    .iter()
+                    .map(|o| {
+                        json!({
+                            "id": o.id,
+                            "label": o.label,
+                            "url": assets.url(&o.image),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+        let choice_options = match current.map(|q| &q.kind) {
+            Some(QuestionKind::MultipleChoice { options }) => Some(options.clone()),
+            _ => None,
+        };
+        let scale = match current.map(|q| &q.kind) {
+            Some(QuestionKind::Scale { min, max }) => Some(json!({ "min": min, "max": max })),
+            _ => None,
+        };
+        let answered = player
+            .as_ref()
+            .and_then(|id| {
+                room.answers
+                    .get(room.current_question_index)
+                    .map(|a| a.contains_key(id))
+            })
+            .unwrap_or(false);
+        // only computed when the room allows peeking
+        let running_score = room.show_score.then(|| room.compatibility());
+        // hosts hear about passphrase guessing while the lock holds
+        let join_locked_mins = room
+            .join_guard
+            .locked_until
+            .checked_sub(now_secs())
+            .filter(|left| is_host && *left > 0)
+            .map(|left| left.div_ceil(60));
+
+        let page = Negotiated::render(
+            "play",
+            context! {
+                code: room.code.clone(),
+                players,
+                player,
+                question,
+                question_number: room.current_question_index + 1,
+                // for the answer forms and the live-update check; see TabStamp
+                question_index: room.current_question_index,
+                submission: Uuid::new_v4().simple().to_string(),
+                version: room.version().to_string(),
+                blanks,
+                ranking_options,
+                picture_options,
+                choice_options,
+                scale,
+                total: if room.endless { None } else { Some(room.questions.len()) },
+                endless: room.endless,
+                answered,
+                waiting_for_partner: !room.in_play(),
+                notice: room.notice.clone(),
+                join_locked_mins,
+                running_score,
+                goal_target: room.goal.as_ref().map(|g| g.target),
+                wagers: room.wagers,
+                balance,
+                seconds_left: room.timer.remaining(now_secs()),
+                timer_paused: room.timer.is_paused(),
+                can_extend: player.as_deref().is_some_and(|id| room.can_extend(id, now_secs())),
+                extension_secs: EXTENSION_SECS,
+                reveal: room.last_reveal(),
+                reveal_level: room.last_reveal().and_then(|e| match e.event {
+                    RoomEvent::QuestionRevealed { credit_pct, .. } => Some(Level::hint(credit_pct)),
+                    _ => None,
+                }),
+                translated,
+                // sent since this question came up, oldest first
+                reactions: room.reactions(room.current_question_index).collect::<Vec<_>>(),
+                reaction_choices: REACTIONS,
+                is_host,
+                moved_away,
+                // host tools need ids to target players; scores only where
+                // the room shows them
+                members: room
+                    .players
+                    .iter()
+                    .filter(|_| is_host)
+                    .map(|p| json!({
+                        "id": p.id,
+                        "name": p.name.expose(),
+                        "score": room.show_score.then_some(p.score),
+                    }))
+                    .collect::<Vec<_>>(),
+                pending: room
+                    .pending
+                    .iter()
+                    .filter(|_| is_host)
+                    .map(|p| json!({ "id": p.id, "name": p.name.expose() }))
+                    .collect::<Vec<_>>(),
+                queue: room.queue.iter().map(|p| p.name.expose().clone()).collect::<Vec<_>>(),
+                can_leave,
+                can_start,
+                custom_questions,
+                custom_slots,
+                custom_max_chars: custom::MAX_CHARS,
+                locale,
+                languages: i18n::SUPPORTED,
+                question_placeholder: ""
+            },
+        );
+        Ok(Fresh::Render(page, validator))
+    } else if state.is_expired(&code) {
+        let page = Negotiated::render("expired", context! { code, expired: true });
+        Ok(Fresh::Render(page, validator))
+    } else {
+        let page = Negotiated::render(
+            "play",
+            context! {
+                code,
+                players: Vec::<String>::new(),
+                question_placeholder: "Room not found."
+            },
+        );
+        Ok(Fresh::Render(page, validator))
+    }
+}
+
+// (cache key, author id, answer, from, to) for every other player's answer in
+// the last reveal, when it was a free-text question.
+fn pending_translations(
+    code: &str,
+    viewer: Option<&str>,
+    state: &AppState,
+    bank: &QuestionBank,
+    accept: &AcceptLanguage,
+) -> Vec<(String, String, String, String, String)> {
+    let map = state.rooms.read();
+    let Some(room) = map.get(code) else { return Vec::new() };
+    let Some(me) = viewer.and_then(|id| room.players.iter().find(|p| p.id == id)) else {
+        return Vec::new();
+    };
+    let Some(RoomEvent::QuestionRevealed {
+        question_index,
+        answers,
+        ..
+    }) = room.last_reveal().map(|e| &e.event)
+    else {
+        return Vec::new();
+    };
+    if !room.seen(*question_index, bank).is_some_and(|q| q.kind == QuestionKind::FreeText) {
+        return Vec::new();
+    }
+    let to = accept.resolve(me.locale.as_deref());
+    answers
+        .iter()
+        .filter(|a| a.player_id != me.id)
+        .filter_map(|a| {
+            let author = room.players.iter().find(|p| p.id == a.player_id)?;
+            let from = author.locale.clone().unwrap_or_else(|| i18n::DEFAULT_LOCALE.to_string());
+            let key = format!("{}:{}:{}:{}", room.code, question_index, a.player_id, to);
+            Some((key, a.player_id.clone(), a.answer.clone(), from, to.clone()))
+        })
+        .collect()
+}
+
+// The form's `player` is the cookie's, from the browser that joined as
+// them, and still seated. Anything a player posts goes through this.
+fn acts_as(room: &Room, me: &CurrentPlayer, player: &str) -> bool {
+    me.id == player && room.bound(me) && room.has_player(player)
+}
+
+// Running score for rooms that allow it; hidden rooms don't leak it here either.
+#[get("/play/<code>/score")]
+fn score_get(code: String, state: &State<AppState>) -> Result<Json<ScorePeek>, Status> {
+    let map = state.rooms.read();
+    let room = map.get(&code).ok_or(Status::NotFound)?;
+    if !room.show_score {
+        return Err(Status::Forbidden);
+    }
+    Ok(Json(ScorePeek {
+        score: room.compatibility(),
+        answered: room.completed_answers().count(),
+    }))
+}
+
+// The player's own notification settings; they hold an email address.
+#[get("/play/<code>/settings?<player>")]
+fn settings_get(
+    code: String,
+    player: String,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+) -> Result<Negotiated, Status> {
+    let map = state.rooms.read();
+    let room = map.get(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &player) {
+        return Err(Status::Forbidden);
+    }
+    let seat = room.players.iter().find(|p| p.id == player).ok_or(Status::Forbidden)?;
+    Ok(Negotiated::render(
+        "settings",
+        context! {
+            code: room.code.clone(),
+            player: seat.id.clone(),
+            name: seat.name.clone(),
+            prefs: seat.notify.clone(),
+        },
+    ))
+}
+
+#[post("/play/<code>/settings", data = "<form>")]
+fn settings_post(
+    code: String,
+    form: Form<SettingsForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+) -> Result<Redirect, Status> {
+    let email = form
+        .email
+        .as_deref()
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(str::to_string);
+    if email.as_deref().is_some_and(|e| !e.contains('@')) || (form.email_summary && email.is_none()) {
+        return Err(Status::UnprocessableEntity);
+    }
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) {
+        return Err(Status::Forbidden);
+    }
+    let player = room
+        .players
+        .iter_mut()
+        .find(|p| p.id == form.player)
+        .ok_or(Status::Forbidden)?;
+    player.notify = NotifyPrefs {
+        partner_answered: form.partner_answered,
+        email_summary: form.email_summary,
+        email: email.map(Sensitive::new),
+    };
+    Ok(Redirect::to(uri!(play_get(
+        code = code,
+        player = Some(form.player.clone())
+    ))))
+}
+
+#[post("/play/<code>/locale", data = "<form>")]
+fn locale_post(
+    code: String,
+    form: Form<LocaleForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+) -> Result<Redirect, Status> {
+    let lang = i18n::normalize(&form.lang).ok_or(Status::UnprocessableEntity)?;
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) {
+        return Err(Status::Forbidden);
+    }
+    let player = room
+        .players
+        .iter_mut()
+        .find(|p| p.id == form.player)
+        .ok_or(Status::Forbidden)?;
+    player.locale = Some(lang);
+    Ok(Redirect::to(uri!(play_get(
+        code = code,
+        player = Some(form.player.clone())
+    ))))
+}
+
+// 30 more seconds on the current question, once per player; everyone's
+// clock moves with the `time_extended` event.
+#[post("/play/<code>/extend")]
+fn extend_post(code: String, me: CurrentPlayer, state: &State<AppState>) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) || !room.has_player(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    room.extend_time(&me.id, now_secs())?;
+    Ok(Redirect::to(uri!(play_get(code = code, player = Some(me.id)))))
+}
+
+// The page sends these in the background and ignores the redirect; it's
+// there for browsers without scripts.
+#[post("/play/<code>/react", data = "<form>")]
+fn react_post(
+    _limit: FormLimit<scope::React>,
+    code: String,
+    form: Form<ReactForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) || !room.has_player(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    room.react(&me.id, &form.emoji)?;
+    Ok(Redirect::to(uri!(play_get(code = code, player = Some(me.id)))))
+}
+
+// Keeps the player marked online while the play page is open.
+#[post("/play/<code>/heartbeat?<player>")]
+fn heartbeat_post(code: String, player: String, me: CurrentPlayer, state: &State<AppState>) -> Status {
+    let mut map = state.rooms.write();
+    match map.get_mut(&code) {
+        Some(room) if acts_as(room, &me, &player) => {
+            room.touch(&player, now_secs());
+            Status::NoContent
+        }
+        Some(_) => Status::Forbidden,
+        None => Status::NotFound,
+    }
+}
+
+// Host-only: hand out bonus or penalty points ("+5, you cooked dinner").
+#[post("/room/<code>/adjust", data = "<form>")]
+fn adjust_post(
+    code: String,
+    form: Form<AdjustForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    audit: &State<AuditLog>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) || !room.is_host(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    if form.reason.trim().is_empty() || !room.adjust_points(&form.target, form.delta, &form.reason) {
+        return Err(Status::BadRequest);
+    }
+    audit.record(
+        &format!("host:{}", room.players[0].name.expose()),
+        "points_adjusted",
+        Some(&code),
+        json!({ "player_id": form.target, "delta": form.delta, "reason": form.reason.trim() }),
+    );
+    Ok(Redirect::to(uri!(play_get(
+        code = code,
+        player = Some(form.player.clone())
+    ))))
+}
+
+// Endless rooms only: either player can call it a day.
+#[post("/play/<code>/finish", data = "<form>")]
+fn finish_post(
+    code: String,
+    form: Form<FinishForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) {
+        return Err(Status::Forbidden);
+    }
+    if !room.endless || room.in_lightning() {
+        return Err(Status::BadRequest);
+    }
+    room.end_main_round(now_secs());
+    if room.finished {
+        Ok(Redirect::to(uri!(result_get(code = code))))
+    } else {
+        Ok(Redirect::to(uri!(lightning_get(
+            code = code,
+            player = Some(form.player.clone())
+        ))))
+    }
+}
+
+#[get("/play/<code>/lightning?<player>")]
+#[allow(clippy::result_large_err)]
+fn lightning_get(
+    code: String,
+    player: Option<String>,
+    current: Option<CurrentPlayer>,
+    state: &State<AppState>,
+) -> Result<Negotiated, Redirect> {
+    let mut map = state.rooms.write();
+    let Some(room) = map.get_mut(&code) else {
+        return Err(Redirect::to(uri!(play_get(code = code, player = player))));
+    };
+    let now = now_secs();
+    room.settle_lightning(now);
+    if room.finished {
+        return Err(Redirect::to(uri!(result_get(code = code))));
+    }
+    let Some(round) = room.lightning.as_ref() else {
+        return Err(Redirect::to(uri!(play_get(code = code, player = player))));
+    };
+    let player = player.filter(|id| current.as_ref().is_some_and(|me| acts_as(room, me, id)));
+    let current = player.as_deref().and_then(|id| round.next_pair(id));
+
+    Ok(Negotiated::render(
+        "lightning",
+        context! {
+            code: room.code.clone(),
+            player: player.clone(),
+            seconds_left: round.remaining(now),
+            index: current.map(|(i, _)| i),
+            this: current.map(|(_, p)| p.0.clone()),
+            that: current.map(|(_, p)| p.1.clone()),
+            answered: player
+                .as_deref()
+                .and_then(|id| round.picks.get(id))
+                .map(Vec::len)
+                .unwrap_or(0),
+            bonus_per_match: lightning::BONUS_PER_MATCH,
+        },
+    ))
+}
+
+#[post("/play/<code>/lightning", data = "<form>")]
+fn lightning_post(
+    code: String,
+    form: Form<LightningForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) {
+        return Err(Status::Forbidden);
+    }
+    let now = now_secs();
+    room.touch(&form.player, now);
+    if let Some(round) = room.lightning.as_mut() {
+        // late or duplicate picks are simply dropped
+        round.pick(&form.player, form.index, form.pick, now);
+    }
+    room.settle_lightning(now);
+    if room.finished {
+        return Ok(Redirect::to(uri!(result_get(code = code))));
+    }
+    Ok(Redirect::to(uri!(lightning_get(
+        code = code,
+        player = Some(form.player.clone())
+    ))))
+}
+
+#[get("/play/<code>/calibrate?<player>")]
+#[allow(clippy::result_large_err)]
+fn calibrate_get(
+    code: String,
+    player: Option<String>,
+    current: Option<CurrentPlayer>,
+    state: &State<AppState>,
+) -> Result<Negotiated, Redirect> {
+    let map = state.rooms.read();
+    let Some(room) = map.get(&code) else {
+        return Err(Redirect::to(uri!(play_get(code = code, player = player))));
+    };
+    let Some(calibration) = room.calibration.as_ref().filter(|_| room.calibrating()) else {
+        return Err(Redirect::to(uri!(play_get(code = code, player = player))));
+    };
+    let player = player.filter(|id| current.as_ref().is_some_and(|me| acts_as(room, me, id)));
+    let ids: Vec<&str> = room.players.iter().map(|p| p.id.as_str()).collect();
+    let step = player.as_deref().map(|id| calibration.step(id, &ids));
+    let (index, prompt, answers, judging) = match &step {
+        Some(calibration::Step::Answer { index, prompt }) => (Some(*index), Some(*prompt), None, false),
+        Some(calibration::Step::Judge { index, prompt, answers }) => {
+            let named: Vec<Value> = room
+                .players
+                .iter()
+                .filter_map(|p| Some(json!({ "name": p.name.expose(), "answer": answers.get(&p.id)? })))
+                .collect();
+            (Some(*index), Some(*prompt), Some(named), true)
+        }
+        _ => (None, None, None, false),
+    };
+    let is_host = player.as_deref().is_some_and(|id| room.is_host(id));
+
+    Ok(Negotiated::render(
+        "calibrate",
+        context! {
+            code: room.code.clone(),
+            player: player.clone(),
+            number: index.map(|i| i + 1),
+            total: calibration.prompts.len(),
+            index,
+            prompt,
+            answers,
+            judging,
+            waiting_for_partner: !room.in_play(),
+            is_host,
+        },
+    ))
+}
+
+#[post("/play/<code>/calibrate", data = "<form>")]
+fn calibrate_post(
+    code: String,
+    form: Form<CalibrateForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) {
+        return Err(Status::Forbidden);
+    }
+    if form.skip && !room.is_host(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    let now = now_secs();
+    room.touch(&form.player, now);
+    let ids: Vec<String> = room.players.iter().map(|p| p.id.clone()).collect();
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+    let full = room.in_play();
+    if let Some(calibration) = room.calibration.as_mut().filter(|c| !c.done) {
+        // late or duplicate submits are simply dropped
+        if form.skip {
+            calibration.done = true;
+        } else if let (Some(same), true) = (form.same, full) {
+            calibration.judge(&form.player, form.index, same, &ids);
+        } else if let (Some(answer), true) = (form.answer.as_deref(), full) {
+            calibration.answer(&form.player, form.index, answer, &ids);
+        }
+    }
+    room.finish_calibration(now);
+    Ok(Redirect::to(uri!(calibrate_get(
+        code = code,
+        player = Some(form.player.clone())
+    ))))
+}
+
+#[get("/play/<code>/story?<player>")]
+#[allow(clippy::result_large_err)]
+fn story_get(
+    code: String,
+    player: Option<String>,
+    current: Option<CurrentPlayer>,
+    state: &State<AppState>,
+    story: &State<Story>,
+) -> Result<Negotiated, Redirect> {
+    let map = state.rooms.read();
+    let Some(room) = map.get(&code) else {
+        return Err(Redirect::to(uri!(play_get(code = code, player = player))));
+    };
+    if room.finished {
+        return Err(Redirect::to(uri!(result_get(code = code))));
+    }
+    let Some(progress) = room.story.as_ref() else {
+        return Err(Redirect::to(uri!(play_get(code = code, player = player))));
+    };
+    let player = player.filter(|id| current.as_ref().is_some_and(|me| acts_as(room, me, id)));
+    let scene = story.scene(&progress.scene);
+    let picked = player.as_deref().is_some_and(|id| progress.picks.contains_key(id));
+
+    Ok(Negotiated::render(
+        "story",
+        context! {
+            code: room.code.clone(),
+            player: player.clone(),
+            title: story.title.clone(),
+            text: scene.map(|s| s.text.clone()),
+            choices: scene.map(|s| s.choices.iter().map(|c| c.label.clone()).collect::<Vec<_>>()),
+            picked,
+            waiting_for_partner: !room.in_play(),
+            last_step: progress.path.last(),
+        },
+    ))
+}
+
+#[post("/play/<code>/story", data = "<form>")]
+fn story_post(
+    code: String,
+    form: Form<StoryForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    story: &State<Story>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if !acts_as(room, &me, &form.player) {
+        return Err(Status::Forbidden);
+    }
+    room.touch(&form.player, now_secs());
+    if !room.story_pick(story, &form.player, form.choice) {
+        return Err(Status::BadRequest);
+    }
+    if room.finished {
+        return Ok(Redirect::to(uri!(result_get(code = code))));
+    }
+    Ok(Redirect::to(uri!(story_get(
+        code = code,
+        player = Some(form.player.clone())
+    ))))
+}
+
+#[get("/result/<code>")]
+#[allow(clippy::too_many_arguments)]
+fn result_get(
+    code: String,
+    state: &State<AppState>,
+    bank: Bank,
+    verdicts: &State<Verdicts>,
+    story: &State<Story>,
+    key: &State<MessageKey>,
+    snapshots: &State<SnapshotStore>,
+    site: &State<Site>,
+    conditional: Conditional,
+) -> Fresh<Negotiated> {
+    let now = now_secs();
+    let mut map = state.rooms.write();
+    // the game before this one in its series, to compare against
+    let previous_snapshot = map
+        .get(&code)
+        .and_then(|room| room.series.as_ref())
+        .and_then(|id| state.series.read().get(id).cloned())
+        .and_then(|s| {
+            let at = s.rooms.iter().position(|c| *c == code)?;
+            s.rooms[..at].last().cloned()
+        })
+        .and_then(|prev| map.get(&prev)?.snapshot.clone());
+    let series_progress = map
+        .get(&code)
+        .and_then(|room| room.series.as_ref())
+        .and_then(|id| state.series.read().get(id).cloned())
+        .map(|s| {
+            let total = s.rooms.iter().filter_map(|c| map.get(c)).map(Room::series_points).sum();
+            context! { id: s.id, games: s.rooms.len(), progress: series::progress(total) }
+        });
+    if let Some(room) = map.get_mut(&code) {
+        room.reveal_surprise(key, now);
+        if room.finished && room.snapshot.is_none() {
+            room.snapshot = Some(snapshots.save(room.transcript(&bank), room.public_result));
+        }
+        // series progress comes from the other games, the review link from the year
+        let version = (
+            room.version(),
+            previous_snapshot.clone(),
+            json::to_string(&series_progress).unwrap_or_default(),
+            review::year_of(now),
+        );
+        let modified = room.stamp.since(hash_of(&version), now);
+        let validator = match conditional.check(conditional.validator(version, modified)) {
+            Ok(validator) => validator,
+            Err(fresh) => return fresh,
+        };
+        let timing = analytics::timing_report(room.timings(&bank));
+        let score = room.compatibility();
+        let answered = room.completed_answers().count();
+        let breakdown = room.breakdown(&bank);
+        let pairs = if room.game_mode == GameMode::Friends { room.pair_scores(&bank) } else { Vec::new() };
+        // parties are a race; everyone else shares the score
+        let standings = (room.game_mode == GameMode::Party).then(|| {
+            let mut players = room.players.clone();
+            players.sort_by(|a, b| b.score.cmp(&a.score));
+            players
+        });
+        let adjustments: Vec<&LoggedEvent> = room
+            .events
+            .iter()
+            .filter(|e| matches!(e.event, RoomEvent::PointsAdjusted { .. }))
+            .collect();
+        let lightning = room
+            .events
+            .iter()
+            .find(|e| matches!(e.event, RoomEvent::LightningFinished { .. }));
+        let message = verdicts.for_score(score).label();
+        let goal = room.goal.as_ref().map(|g| {
+            let reached = room.finished && score >= g.target;
+            context! {
+                target: g.target,
+                reached,
+                has_message: g.message.is_some(),
+                // stays sealed unless you made it
+                message: g.message.as_ref().filter(|_| reached).and_then(|m| key.open(m)),
+            }
+        });
+        let surprise = room
+            .surprise
+            .as_ref()
+            .filter(|s| s.revealed)
+            .map(|s| s.open(key))
+            .map(|o| context! { text: o.text, has_photo: o.has_photo });
+        let surprise_pending = room.surprise.as_ref().is_some_and(|s| !s.revealed);
+        let story_path = room.story.as_ref().map(|p| {
+            context! {
+                title: story.title.clone(),
+                steps: p.path.iter().map(|step| context! {
+                    text: story.scene(&step.scene).map(|s| s.text.clone()),
+                    picks: step.picks.clone(),
+                    agreed: step.agreed,
+                }).collect::<Vec<_>>(),
+                ending: story.scene(&p.scene).map(|s| s.text.clone()),
+            }
+        });
+        let cue = room.events.iter().rev().find_map(|e| match &e.event {
+            RoomEvent::GameFinished { cue, .. } => *cue,
+            _ => None,
+        });
+        let score_level = Level::hint(score);
+        // only games their hosts made public are for crawlers; the symbol
+        // carries the level into link previews, which show no colors
+        let meta = site.meta(
+            &format!("/result/{}", code),
+            &format!("{} {}% in sync · Room {}", score_level.symbol, score, code),
+            Some(&message),
+            room.finished && room.public_result,
+        );
+        let page = Negotiated::render(
+            "result",
+            context! {
+                meta,
+                code,
+                score,
+                score_level,
+                message,
+                cue,
+                answered,
+                breakdown,
+                game_mode: room.game_mode,
+                pairs,
+                standings,
+                players: room
+                    .players
+                    .iter()
+                    .map(|p| json!({ "id": p.id, "name": p.name.expose(), "score": p.score }))
+                    .collect::<Vec<_>>(),
+                adjustments,
+                lightning,
+                timing,
+                story: story_path,
+                goal,
+                surprise,
+                surprise_pending,
+                series: series_progress,
+                can_rematch: room.finished && room.is_full(),
+                can_replay: room.finished,
+                // a re-test only makes sense with a fixed deck
+                can_retest: !room.endless && room.story.is_none(),
+                rematch: room.rematch.clone(),
+                snapshot: room.snapshot.clone(),
+                previous_snapshot,
+                review_year: review::year_of(now),
+            },
+        );
+        Fresh::Render(page, validator)
+    } else {
+        let validator = match conditional.check(conditional.validator(&code, now)) {
+            Ok(validator) => validator,
+            Err(fresh) => return fresh,
+        };
+        let page = if state.is_expired(&code) {
+            Negotiated::render("expired", context! { code, expired: true })
+        } else {
+            Negotiated::render(
+                "result",
+                context! { code, score: 0, message: "Room not found.", answered: 0 },
+            )
+        };
+        Fresh::Render(page, validator)
+    }
+          }
+
+// Two finished games of the same couple, side by side. Tokens come from the
+// result page; `a` is the earlier game by convention.
+#[get("/compare?<a>&<b>")]
+fn compare_get(a: Option<String>, b: Option<String>, snapshots: &State<SnapshotStore>) -> Template {
+    let (a, b) = (a.unwrap_or_default(), b.unwrap_or_default());
+    let found = (snapshots.get(&a), snapshots.get(&b));
+    let (comparison, error) = match found {
+        _ if a.trim().is_empty() || b.trim().is_empty() => (None, None),
+        (Some(sa), Some(sb)) => match snapshots::compare(&sa, &sb) {
+            Ok(c) => (Some(c), None),
+            Err(CompareError::NotSameCouple) => (None, Some("Those two games weren't played by the same couple.")),
+        },
+        _ => (None, Some("We couldn't find a game for one of those tokens.")),
+    };
+    Template::render("compare", context! { a, b, comparison, error })
+}
+
+#[get("/api/v1/compare?<a>&<b>")]
+fn compare_api(
+    _limit: ApiLimit<scope::Read>,
+    a: String,
+    b: String,
+    snapshots: &State<SnapshotStore>,
+) -> Result<Json<snapshots::Comparison>, ApiError> {
+    let missing = |field| ApiError::new(Status::NotFound, "not_found", Some(field), "no result with that token");
+    let sa = snapshots.get(&a).ok_or_else(|| missing("a"))?;
+    let sb = snapshots.get(&b).ok_or_else(|| missing("b"))?;
+    match snapshots::compare(&sa, &sb) {
+        Ok(c) => Ok(Json(c)),
+        Err(CompareError::NotSameCouple) => Err(ApiError::invalid(
+            "b",
+            "not_same_couple",
+            "both results must be from the same couple",
+        )),
+    }
+}
+
+// What everyone answers, from public games; see wordcloud.rs.
+#[get("/words")]
+fn words_get(word_cloud: &State<WordCloudService>) -> Template {
+    let cloud = word_cloud.current();
+    Template::render("words", context! { cloud: &*cloud })
+}
+
+#[get("/api/v1/words")]
+fn words_api(_limit: ApiLimit<scope::Read>, word_cloud: &State<WordCloudService>) -> Json<Value> {
+    Json(json::to_value(&*word_cloud.current()).unwrap_or_default())
+}
+
+// Best public games, rated across modes; see leaderboard.rs.
+#[get("/api/v1/leaderboard")]
+fn leaderboard_api(_limit: ApiLimit<scope::Read>, leaderboard: &State<LeaderboardService>) -> Json<Value> {
+    Json(json::to_value(&*leaderboard.current()).unwrap_or_default())
+}
+
+// Year of Us: any of the couple's snapshot tokens unlocks their review.
+#[get("/review/<year>?<token>")]
+fn review_get(year: i64, token: String, reviews: &State<ReviewService>) -> Option<Template> {
+    let review = reviews.for_token(&token, year)?;
+    Some(Template::render("review", context! { review: &*review, token }))
+}
+
+// The transcript holds every answer, so only the room's own players get
+// it, and only once the game is over, like the result page.
+#[get("/result/<code>/export?<format>")]
+fn export_get(
+    code: String,
+    format: Option<String>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    bank: Bank,
+) -> Result<Download, Status> {
+    let format = export::Format::parse(format.as_deref().unwrap_or("json")).ok_or(Status::BadRequest)?;
+    let map = state.rooms.read();
+    let room = map.get(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) {
+        return Err(Status::Forbidden);
+    }
+    if !room.finished {
+        return Err(Status::BadRequest);
+    }
+    Ok(room.transcript(&bank).download(format))
+}
+
+#[get("/result/<code>/surprise")]
+fn surprise_photo_get(
+    code: String,
+    state: &State<AppState>,
+    key: &State<MessageKey>,
+) -> Option<(ContentType, Vec<u8>)> {
+    let map = state.rooms.read();
+    let surprise = map.get(&code)?.surprise.as_ref().filter(|s| s.revealed)?;
+    let (bytes, content_type) = surprise.open_photo(key)?;
+    Some((ContentType::parse_flexible(&content_type)?, bytes))
+}
+
+#[post("/result/<code>/rematch")]
+fn rematch_post(
+    code: String,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    bank: Bank,
+    story: &State<Story>,
+    key: &State<MessageKey>,
+    scheme: &State<CodeScheme>,
+    recommender: &State<Recommender>,
+    features: &State<Features>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    if !features.room_fits(map.len()) {
+        return Err(Status::ServiceUnavailable);
+    }
+    let played = map.get(&code).ok_or(Status::NotFound)?;
+    // only the room's players start the next game
+    if !played.bound(&me) {
+        return Err(Status::Forbidden);
+    }
+    let host_locale = played.players.first().and_then(|p| p.locale.clone());
+    let next_code = RoomCode::generate_unique(**scheme, host_locale.as_deref(), |c| state.code_taken(&map, c))
+        .ok_or(Status::ServiceUnavailable)?
+        .into_string();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if room.rematch.is_some() {
+        // partner beat us to it
+        return Ok(Redirect::to(uri!(result_get(code = code))));
+    }
+    if !room.finished || !room.is_full() {
+        return Err(Status::BadRequest);
+    }
+
+    let mut series = state.series.write();
+    let series_id = series_of(room, &mut series)?;
+    // lead with what they've agreed on least so far
+    let ids: Vec<&str> = room.players.iter().map(|p| p.id.as_str()).collect();
+    let focus = recommender.for_players(&ids, bank.drawable(room.tenant.as_deref())).question_ids();
+    let next = room.rematch(next_code.clone(), focus, &bank, story, key);
+    room.rematch = Some(next_code.clone());
+    if let Some(s) = series.get_mut(&series_id) {
+        s.rooms.push(next_code.clone());
+    }
+    map.insert(next_code, next);
+    Ok(Redirect::to(uri!(result_get(code = code))))
+}
+
+// "Continue" into the rematch: the browser that played this room as a
+// player takes their seat in the new one. The only way into a rematch seat,
+// since nobody joined it.
+#[post("/result/<code>/continue")]
+fn continue_post(
+    code: String,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) || !room.has_player(&me.id) {
+        return Err(Status::Forbidden);
+    }
+    let next_code = room.rematch.clone().ok_or(Status::BadRequest)?;
+    let next = map.get_mut(&next_code).ok_or(Status::NotFound)?;
+    // sent twice, or from a second tab: already seated
+    let seated = identity::recall(cookies, cookie_keys, &next_code).is_some_and(|c| c.id == me.id && next.bound(&c));
+    if !seated {
+        let session = next.claim_seat(&me.id).ok_or(Status::Conflict)?;
+        identity::remember(cookies, cookie_keys, &next_code, &me.id, Some(&session));
+    }
+    Ok(Redirect::to(uri!(play_get(code = next_code, player = Some(me.id)))))
+}
+
+// The series `room` belongs to, starting one with it if it has none yet.
+fn series_of(room: &mut Room, series: &mut HashMap<String, Series>) -> Result<String, Status> {
+    if let Some(id) = room.series.clone() {
+        return Ok(id);
+    }
+    // series ids never need reading out, so always the short form
+    let id = RoomCode::generate_unique(CodeScheme::Alphanumeric, None, |c| series.contains_key(c))
+        .ok_or(Status::ServiceUnavailable)?
+        .into_string();
+    series.insert(id.clone(), Series::new(id.clone(), room.code.clone()));
+    room.series = Some(id.clone());
+    Ok(id)
+}
+
+#[derive(FromForm)]
+struct ReplayForm {
+    // checkbox: the same questions again, for a re-test
+    same_questions: bool,
+}
+
+// "Play this setup again": a fresh room with this game's settings, hosted
+// by the player who asked and linked to this one in a series. Unlike a
+// rematch nobody else is seated; the new code goes to whoever plays next.
+#[post("/result/<code>/again", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
+fn replay_post(
+    code: String,
+    form: Form<ReplayForm>,
+    me: CurrentPlayer,
+    state: &State<AppState>,
+    bank: Bank,
+    story: &State<Story>,
+    key: &State<MessageKey>,
+    scheme: &State<CodeScheme>,
+    features: &State<Features>,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    if !features.room_fits(map.len()) {
+        return Err(Status::ServiceUnavailable);
+    }
+    let room = map.get(&code).ok_or(Status::NotFound)?;
+    if !room.bound(&me) {
+        return Err(Status::Forbidden);
+    }
+    if !room.finished {
+        return Err(Status::BadRequest);
+    }
+    let me = room.players.iter().find(|p| p.id == me.id).ok_or(Status::Forbidden)?;
+    let mut host = new_player(me.name.expose().clone(), me.locale.clone(), me.referral.clone());
+    // they agreed to the 18+ questions for the last game
+    host.consented_at = me.consented_at.filter(|_| room.mature);
+    let next_code = RoomCode::generate_unique(**scheme, host.locale.as_deref(), |c| state.code_taken(&map, c))
+        .ok_or(Status::ServiceUnavailable)?
+        .into_string();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    let mut series = state.series.write();
+    let series_id = series_of(room, &mut series)?;
+    let host_id = host.id.clone();
+    identity::remember(cookies, cookie_keys, &next_code, &host_id, host.session.as_deref());
+    let next = room.replay(next_code.clone(), host, form.same_questions, &bank, story, key);
+    if let Some(s) = series.get_mut(&series_id) {
+        s.rooms.push(next_code.clone());
+    }
+    map.insert(next_code.clone(), next);
+    Ok(Redirect::to(uri!(play_get(code = next_code, player = Some(host_id)))))
+}
+
+#[get("/series/<id>")]
+fn series_get(id: String, state: &State<AppState>) -> Option<Template> {
+    let map = state.rooms.read();
+    let series = state.series.read().get(&id).cloned()?;
+    let games: Vec<_> = series
+        .rooms
+        .iter()
+        .filter_map(|code| map.get(code))
+        .map(|room| {
+            context! {
+                code: room.code.clone(),
+                finished: room.finished,
+                score: room.compatibility(),
+                points: room.series_points(),
+            }
+        })
+        .collect();
+    let total = series.rooms.iter().filter_map(|c| map.get(c)).map(Room::series_points).sum();
+    Some(Template::render(
+        "series",
+        context! {
+            id: series.id,
+            games,
+            progress: series::progress(total),
+        },
+    ))
+}
+
+// --- JSON API ---
+// The game for apps: create, join, answer and result as JSON under /api/v1,
+// the same flow as the pages. Players are recognised by the same sealed
+// `player_<CODE>` cookie the pages set, so clients keep a cookie jar.
+// Failures use the api.rs envelope. Rooms and results also come as protobuf
+// (proto/now.proto), MessagePack or CBOR; see negotiate::Encoding.
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct CreateRoomBody {
+    host_name: String,
+    game_mode: Option<GameMode>,
+    capacity: Option<usize>,
+    #[serde(default)]
+    wait_for_host: bool,
+    #[serde(default)]
+    endless: bool,
+    #[serde(default)]
+    show_score: bool,
+    #[serde(default)]
+    wagers: bool,
+    time_limit: Option<u64>,
+    question_count: Option<usize>,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    lightning: bool,
+    #[serde(default)]
+    calibration: bool,
+    #[serde(default)]
+    story: bool,
+    goal_target: Option<u32>,
+    secret_message: Option<String>,
+    // photos need the form upload; text surprises work here too
+    surprise_text: Option<String>,
+    surprise_min_score: Option<u32>,
+    surprise_date: Option<String>,
+    lang: Option<String>,
+    passphrase: Option<String>,
+    #[serde(default)]
+    approve_joins: bool,
+    #[serde(default)]
+    queue_when_full: bool,
+    #[serde(default)]
+    public_result: bool,
+    #[serde(default)]
+    mature: bool,
+}
+
+impl Validate for CreateRoomBody {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.host_name.trim().is_empty() {
+            return Err(ApiError::invalid("host_name", "required", "a name is required"));
+        }
+        if self.question_count.is_some_and(|n| !QUESTION_COUNTS.contains(&n)) {
+            return Err(ApiError::invalid("question_count", "invalid_count", "question_count is 10, 25 or 50"));
+        }
+        let seats = self.game_mode.unwrap_or_default().capacities();
+        if self.capacity.is_some_and(|n| !seats.contains(&n)) {
+            let message = format!("capacity is {} to {} for this game_mode", seats.start(), seats.end());
+            return Err(ApiError::invalid("capacity", "invalid_capacity", message));
+        }
+        let date = self.surprise_date.as_deref().filter(|d| !d.is_empty());
+        if date.is_some_and(|d| surprise::parse_day(d).is_none()) {
+            return Err(ApiError::invalid("surprise_date", "invalid_date", "dates are YYYY-MM-DD"));
+        }
+        Ok(())
+    }
+}
+
+impl CreateRoomBody {
+    // As the create form would have posted it.
+    fn to_form(&self) -> CreateRoomForm<'static> {
+        CreateRoomForm {
+            host_name: self.host_name.trim().to_string(),
+            game_mode: self.game_mode,
+            capacity: self.capacity,
+            wait_for_host: self.wait_for_host,
+            endless: self.endless,
+            show_score: self.show_score,
+            wagers: self.wagers,
+            time_limit: self.time_limit,
+            question_count: self.question_count,
+            categories: self.categories.clone(),
+            focus: Vec::new(),
+            lightning: self.lightning,
+            calibration: self.calibration,
+            story: self.story,
+            goal_target: self.goal_target,
+            secret_message: self.secret_message.clone(),
+            surprise_text: self.surprise_text.clone(),
+            surprise_photo: None,
+            surprise_min_score: self.surprise_min_score,
+            surprise_date: self.surprise_date.clone(),
+            lang: self.lang.clone(),
+            passphrase: self.passphrase.clone(),
+            approve_joins: self.approve_joins,
+            queue_when_full: self.queue_when_full,
+            public_result: self.public_result,
+            mature: self.mature,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct JoinRoomBody {
+    name: String,
+    lang: Option<String>,
+    passphrase: Option<String>,
+}
+
+impl Validate for JoinRoomBody {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.name.trim().is_empty() {
+            return Err(ApiError::invalid("name", "required", "a name is required"));
+        }
+        Ok(())
+    }
+}
+
+// One of `answer` (free text, one per blank), `order` (ranking, option
+// indexes best first) or `option` (picture id), matching the question.
+// `question` and `submission` work like the play page's TabStamp.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct AnswerBody {
+    #[serde(default)]
+    answer: Vec<String>,
+    #[serde(default)]
+    order: Vec<usize>,
+    option: Option<String>,
+    // scale questions
+    value: Option<i32>,
+    wager: Option<u32>,
+    question: Option<usize>,
+    submission: Option<String>,
+}
+
+impl Validate for AnswerBody {
+    fn validate(&self) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
+
+impl AnswerBody {
+    // What to record for `question`, checked against its kind.
+    fn items(&self, question: Option<&Question>) -> Result<Vec<String>, ApiError> {
+        match question.map(|q| &q.kind) {
+            None | Some(QuestionKind::FreeText) => {
+                let blanks = question.map(|q| q.blanks).unwrap_or(1);
+                let items: Vec<String> = self
+                    .answer
+                    .iter()
+                    .map(|a| a.trim().to_string())
+                    .filter(|a| !a.is_empty())
+                    .collect();
+                if items.is_empty() {
+                    return Err(ApiError::invalid("answer", "required", "an answer is required"));
+                }
+                if items.len() > blanks {
+                    let message = format!("this question has {} blank(s)", blanks);
+                    return Err(ApiError::invalid("answer", "too_many", message));
+                }
+                Ok(items)
+            }
+            Some(QuestionKind::Ranking { options }) => ranking(&self.order, options)
+                .ok_or_else(|| ApiError::invalid("order", "not_a_ranking", "order must list every option index once")),
+            Some(QuestionKind::PictureChoice { options }) => match &self.option {
+                Some(id) if options.iter().any(|o| o.id == *id) => Ok(vec![id.clone()]),
+                _ => Err(ApiError::invalid("option", "unknown_option", "option must be one of the question's ids")),
+            },
+            Some(QuestionKind::MultipleChoice { options }) => match &self.option {
+                Some(option) if options.contains(option) => Ok(vec![option.clone()]),
+                _ => Err(ApiError::invalid("option", "unknown_option", "option must be one of the question's options")),
+            },
+            Some(QuestionKind::Scale { min, max }) => match self.value {
+                Some(value) if (*min..=*max).contains(&value) => Ok(vec![value.to_string()]),
+                _ => {
+                    let message = format!("value must be a whole number from {} to {}", min, max);
+                    Err(ApiError::invalid("value", "out_of_range", message))
+                }
+            },
+        }
+    }
+
+    fn tab(&self) -> TabStamp {
+        TabStamp {
+            question: self.question,
+            submission: self.submission.clone(),
+        }
+    }
+}
+
+// The room as the play page shows it to `me` (None = not in the room).
+fn api_room(room: &Room, me: Option<&str>, bank: &QuestionBank, assets: &AssetStore, locale: &str) -> Value {
+    let current = room.current_question(bank);
+    let question = current.filter(|_| !room.finished).map(|q| {
+        let options = match &q.kind {
+            QuestionKind::FreeText => Value::Null,
+            QuestionKind::Ranking { options } | QuestionKind::MultipleChoice { options } => json!(options),
+            QuestionKind::Scale { min, max } => json!({ "min": min, "max": max }),
+            QuestionKind::PictureChoice { options } => options
+                .iter()
+                .map(|o| json!({ "id": o.id, "label": o.label, "url": assets.url(&o.image) }))
+                .collect(),
+        };
+        json!({
+            "index": room.current_question_index,
+            "number": room.current_question_index + 1,
+            "text": q.text_for(locale),
+            "kind": q.kind,
+            "blanks": q.blanks,
+            "options": options,
+        })
+    });
+    let answered = me.is_some_and(|id| {
+        room.answers
+            .get(room.current_question_index)
+            .is_some_and(|a| a.contains_key(id))
+    });
+    let seat = me.map(|id| {
+        if room.needs_consent(id) {
+            "consent"
+        } else if room.has_player(id) {
+            "playing"
+        } else if room.pending.iter().any(|p| p.id == id) {
+            "pending"
+        } else {
+            "queued"
+        }
+    });
+    json!({
+        "code": room.code,
+        "player_id": me,
+        "seat": seat,
+        "is_host": me.is_some_and(|id| room.is_host(id)),
+        "game_mode": room.game_mode,
+        "capacity": room.capacity,
+        "players": api_players(room),
+        "waiting_for_partner": !room.in_play(),
+        "finished": room.finished,
+        "total": if room.endless { None } else { Some(room.questions.len()) },
+        "question": question,
+        "answered": answered,
+        "seconds_left": room.timer.remaining(now_secs()),
+        "running_score": room.show_score.then(|| room.compatibility()),
+    })
+}
+
+fn api_players(room: &Room) -> Vec<Value> {
+    room.players
+        .iter()
+        .map(|p| json!({ "name": p.name.expose(), "score": p.score }))
+        .collect()
+}
+
+// A missing room: gone for good if it expired, else never there.
+fn api_missing(state: &AppState, code: &str) -> ApiError {
+    if state.is_expired(code) {
+        return ApiError::new(Status::Gone, "expired", None, "this room has expired");
+    }
+    ApiError::new(Status::NotFound, "not_found", None, "no room with that code")
+}
+
+#[post("/api/v1/rooms", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
+fn api_create_room(
+    _limit: ApiLimit<scope::Create>,
+    body: Validated<CreateRoomBody>,
+    state: &State<AppState>,
+    bank: Bank,
+    story: &State<Story>,
+    key: &State<MessageKey>,
+    outbox: &State<Outbox>,
+    tenant: Tenant,
+    variants: Variants,
+    referral: Referral,
+    age: AgeGate,
+    restrictions: Restrictions,
+    scheme: &State<CodeScheme>,
+    weights: &State<CategoryWeights>,
+    assets: &State<AssetStore>,
+    features: &State<Features>,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<status::Created<ApiBody>, ApiError> {
+    if !age.passed() {
+        return Err(ApiError::new(Status::Forbidden, "age_gate", None, "confirm your age at /age first"));
+    }
+    let form = body.to_form();
+    let locale = form.lang.as_deref().and_then(i18n::normalize);
+    let mut map = state.rooms.write();
+    if !features.room_fits(map.len()) {
+        return Err(ApiError::new(
+            Status::ServiceUnavailable,
+            "at_capacity",
+            None,
+            "no room for more games; try again later",
+        ));
+    }
+    let code = RoomCode::generate_unique(**scheme, locale.as_deref(), |c| state.code_taken(&map, c))
+        .ok_or_else(|| ApiError::new(Status::ServiceUnavailable, "no_free_code", None, "no free room code; try again"))?
+        .into_string();
+    let mut host = new_player(form.host_name.clone(), locale.clone(), referral.0.clone());
+    // ticking the box is the host's own consent
+    host.consented_at = (form.mature && restrictions.mature).then(now_secs);
+    let host_id = host.id.clone();
+    identity::remember(cookies, cookie_keys, &code, &host_id, host.session.as_deref());
+    let mut room = Room::new(code.clone(), host, &form, &bank, story, key, tenant.0);
+    room.restrict(restrictions.blocked, restrictions.mature, &bank);
+    room.category_weights = weights.inner().clone();
+    room.referral = referral.0;
+    let unlock_day = form.surprise_date.as_deref().and_then(surprise::parse_day);
+    room.surprise = Surprise::seal(key, form.surprise_text.as_deref(), None, form.surprise_min_score, unlock_day);
+    room.recount();
+
+    let view = api_room(&room, Some(&host_id), &bank, assets, locale.as_deref().unwrap_or(i18n::DEFAULT_LOCALE));
+    map.insert(code.clone(), room);
+    drop(map);
+    outbox.notify_webhook(json!({ "event": "room_created", "code": code }));
+    variants.convert("room_created");
+    Ok(status::Created::new(format!("/api/v1/rooms/{}", code)).body(ApiBody::room(view)))
+}
+
+// Carries a `version`; with `?since=` set to an earlier one, JSON clients
+// get only what changed since, as a merge patch (see delta.rs).
+#[get("/api/v1/rooms/<code>?<since>")]
+#[allow(clippy::too_many_arguments)]
+fn api_room_get(
+    _limit: ApiLimit<scope::Read>,
+    code: String,
+    since: Option<String>,
+    state: &State<AppState>,
+    bank: Bank,
+    assets: &State<AssetStore>,
+    history: &State<ViewHistory>,
+    accept: AcceptLanguage,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<Either<ApiBody, MergePatch>, ApiError> {
+    let map = state.rooms.read();
+    let room = map.get(&code).ok_or_else(|| api_missing(state, &code))?;
+    let me = identity::recall(cookies, cookie_keys, &code).filter(|me| room.bound(me));
+    let me = me.as_ref().map(|me| me.id.as_str());
+    let preferred = me.and_then(|id| room.players.iter().find(|p| p.id == id)).and_then(|p| p.locale.as_deref());
+    let locale = accept.resolve(preferred);
+    let mut view = api_room(room, me, &bank, assets, &locale);
+    let viewer = format!("{}/{}/{}", code, me.unwrap_or("-"), locale);
+    match history.record(&viewer, &mut view, since.as_deref()) {
+        Some(patch) => Ok(Either::Right(MergePatch(patch))),
+        None => Ok(Either::Left(ApiBody::room(view))),
+    }
+}
+
+// 201 with the new seat; 200 with the existing one when this client has
+// already joined.
+#[post("/api/v1/rooms/<code>/join", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
+fn api_join_room(
+    _limit: ApiLimit<scope::Join>,
+    code: String,
+    body: Validated<JoinRoomBody>,
+    state: &State<AppState>,
+    bank: Bank,
+    key: &State<MessageKey>,
+    outbox: &State<Outbox>,
+    audit: &State<AuditLog>,
+    assets: &State<AssetStore>,
+    referral: Referral,
+    age: AgeGate,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<status::Custom<ApiBody>, ApiError> {
+    if !age.passed() {
+        return Err(ApiError::new(Status::Forbidden, "age_gate", None, "confirm your age at /age first"));
+    }
+    let code = RoomCode::parse(&code).map(RoomCode::into_string).unwrap_or_default();
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or_else(|| api_missing(state, &code))?;
+    let locale = body.lang.as_deref().and_then(i18n::normalize);
+    let shown_in = locale.clone().unwrap_or_else(|| i18n::DEFAULT_LOCALE.to_string());
+    if let Some(me) = identity::recall(cookies, cookie_keys, &code).filter(|me| room.bound(me)) {
+        return Ok(status::Custom(Status::Ok, ApiBody::room(api_room(room, Some(&me.id), &bank, assets, &shown_in))));
+    }
+    let p = new_player(body.name.trim().to_string(), locale, referral.0);
+    let (player_id, session) = (p.id.clone(), p.session.clone());
+    admit(room, p, body.passphrase.as_deref(), key, outbox, audit).map_err(JoinRefused::api_error)?;
+    identity::remember(cookies, cookie_keys, &code, &player_id, session.as_deref());
+    Ok(status::Custom(Status::Created, ApiBody::room(api_room(room, Some(&player_id), &bank, assets, &shown_in))))
+}
+
+#[post("/api/v1/rooms/<code>/answer", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
+fn api_answer(
+    _limit: ApiLimit<scope::Answer>,
+    code: String,
+    body: Validated<AnswerBody>,
+    state: &State<AppState>,
+    bank: Bank,
+    notifier: &State<Notifier>,
+    assets: &State<AssetStore>,
+    accept: AcceptLanguage,
+    cookies: &CookieJar<'_>,
+    cookie_keys: &State<CookieKeys>,
+) -> Result<ApiBody, ApiError> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or_else(|| api_missing(state, &code))?;
+    let me = identity::recall(cookies, cookie_keys, &code)
+        .filter(|me| acts_as(room, me, &me.id))
+        .ok_or_else(|| ApiError::new(Status::Forbidden, "not_a_player", None, "join this room to answer"))?;
+    if room.finished {
+        return Err(ApiError::new(Status::Conflict, "finished", None, "this game is over"));
+    }
+    let tab = body.tab();
+    let stale = tab.question.is_some_and(|q| q != room.current_question_index);
+    if stale {
+        let message = "the game has moved on; fetch the room again";
+        return Err(ApiError::new(Status::Conflict, "stale", Some("question"), message));
+    }
+    // the same submission again changes nothing
+    if flow::superseded(room, &me.id, &tab).is_none() {
+        let items = body.items(room.current_question(&bank))?;
+        flow::record_answer(room, &me.id, items, body.wager, &tab, &bank, notifier).map_err(|status| match status.code {
+            422 => ApiError::invalid("wager", "over_balance", "you can't wager more points than you have"),
+            400 => ApiError::new(Status::Conflict, "not_started", None, "the game hasn't started yet"),
+            409 => ApiError::new(Status::Conflict, "timed_out", None, "time ran out on this question"),
+            507 => ApiError::new(status, "room_full", None, "this room can't hold any more answers"),
+            _ => ApiError::from(status),
+        })?;
+    }
+    let locale = accept.resolve(room.players.iter().find(|p| p.id == me.id).and_then(|p| p.locale.as_deref()));
+    Ok(ApiBody::room(api_room(room, Some(&me.id), &bank, assets, &locale)))
+}
+
+#[get("/api/v1/rooms/<code>/result")]
+fn api_result(
+    _limit: ApiLimit<scope::Read>,
+    code: String,
+    state: &State<AppState>,
+    bank: Bank,
+    verdicts: &State<Verdicts>,
+    snapshots: &State<SnapshotStore>,
+) -> Result<ApiBody, ApiError> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or_else(|| api_missing(state, &code))?;
+    if room.finished && room.snapshot.is_none() {
+        room.snapshot = Some(snapshots.save(room.transcript(&bank), room.public_result));
+    }
+    let score = room.compatibility();
+    Ok(ApiBody::result(json!({
+        "code": room.code,
+        "finished": room.finished,
+        "score": score,
+        "level": Level::hint(score),
+        "verdict": verdicts.for_score(score).label(),
+        "answered": room.completed_answers().count(),
+        "players": api_players(room),
+        "questions": room.breakdown(&bank),
+        // for /compare and the yearly review
+        "snapshot": room.snapshot,
+    })))
+}
+
+// What this deployment offers, so apps can hide what isn't there instead of
+// hardcoding it, plus any announcement from the admins. Mature content
+// follows the caller's region, as the create form does.
+#[get("/api/v1/capabilities")]
+fn api_capabilities(
+    _limit: ApiLimit<scope::Read>,
+    restrictions: Restrictions,
+    story: &State<Story>,
+    translations: &State<Translations>,
+    rules: &State<ContentRules>,
+    outbox: &State<Outbox>,
+    announcements: &State<Announcements>,
+    features: &State<Features>,
+) -> Json<Value> {
+    let game_modes: Vec<Value> = GameMode::ALL
+        .iter()
+        .map(|mode| {
+            let seats = mode.capacities();
+            json!({
+                "mode": mode,
+                "min_players": seats.start(),
+                "max_players": seats.end(),
+                "default_players": mode.default_capacity(),
+            })
+        })
+        .collect();
+    let locales: Vec<Value> = i18n::SUPPORTED
+        .iter()
+        .map(|(code, name)| json!({ "code": code, "name": name }))
+        .collect();
+    Json(json!({
+        "api_version": "v1",
+        "game_modes": game_modes,
+        "max_players": scoring::MAX_GROUP_SIZE,
+        "locales": locales,
+        "default_locale": i18n::DEFAULT_LOCALE,
+        "question_counts": QUESTION_COUNTS,
+        "reactions": REACTIONS,
+        "question_kinds": ["free_text", "ranking", "picture_choice", "multiple_choice", "scale"],
+        "encodings": ["application/json", "application/x-protobuf", "application/msgpack", "application/cbor"],
+        "features": {
+            "story": !story.scenes.is_empty(),
+            "translation": translations.enabled(),
+            "mature": restrictions.mature,
+            "age_gate": rules.min_age(),
+            "webhooks": outbox.has_webhook(),
+            "endless": true,
+            "wagers": true,
+            "lightning": true,
+            "calibration": true,
+            "graphql": true,
+            "merge_patch": true,
+            "events": true,
+            "invite_qr": features.qr_codes,
+            "surprise_photos": features.surprise_photos,
+        },
+        // the admins' notice, if one is up
+        "announcement": announcements.current(),
+    }))
+}
+
+// --- GraphQL ---
+// What graphql.rs reads rooms through: the same rooms, bank and verdicts as
+// the pages, as of this request.
+struct GameService {
+    state: AppState,
+    bank: Bank,
+    verdicts: Verdicts,
+}
+
+impl GameService {
+    fn view(&self, room: &Room) -> RoomView {
+        let question = room.current_question(&self.bank).filter(|_| !room.finished).map(|q| QuestionView {
+            number: room.current_question_index as u32 + 1,
+            text: q.text.clone(),
+            category: q.category.clone(),
+            kind: json::to_value(&q.kind).unwrap_or_default()["type"].as_str().unwrap_or_default().to_string(),
+        });
+        let result = room.finished.then(|| {
+            let score = room.compatibility();
+            ResultView {
+                score,
+                level: Level::of(score).as_str().to_string(),
+                verdict: self.verdicts.for_score(score).label(),
+                answered: room.completed_answers().count() as u32,
+                questions: room
+                    .credits
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, credit)| {
+                        let question = room.seen(idx, &self.bank);
+                        let credit_pct = (credit * 100.0).round() as u32;
+                        QuestionResultView {
+                            number: idx as u32 + 1,
+                            question: question.map(|q| q.text.clone()),
+                            category: question.map(|q| q.category.clone()),
+                            credit_pct,
+                            level: Level::of(credit_pct).as_str().to_string(),
+                            matched: *credit >= 1.0,
+                        }
+                    })
+                    .collect(),
+            }
+        });
+        RoomView {
+            code: room.code.clone(),
+            game_mode: json::to_value(room.game_mode).unwrap_or_default().as_str().unwrap_or_default().to_string(),
+            capacity: room.capacity as u32,
+            players: room
+                .players
+                .iter()
+                .map(|p| PlayerView {
+                    name: p.name.expose().clone(),
+                    score: p.score,
+                })
+                .collect(),
+            finished: room.finished,
+            total: (!room.endless).then_some(room.questions.len() as u32),
+            question,
+            result,
+        }
+    }
+}
+
+impl Games for GameService {
+    fn room(&self, code: &str) -> Option<RoomView> {
+        self.state.rooms.read().get(code).map(|room| self.view(room))
+    }
+
+    fn public_results(&self) -> Vec<RoomView> {
+        let map = self.state.rooms.read();
+        let mut rooms: Vec<RoomView> = map
+            .values()
+            .filter(|room| room.finished && room.public_result)
+            .map(|room| self.view(room))
+            .collect();
+        rooms.sort_by(|a, b| a.code.cmp(&b.code));
+        rooms
+    }
+
+    fn subscribe(&self, code: &str) -> Option<broadcast::Receiver<LoggedEvent>> {
+        self.state.rooms.write().get_mut(code).map(Room::subscribe)
+    }
+}
+
+fn game_service(state: &AppState, bank: Bank, verdicts: &Verdicts) -> GamesData {
+    Arc::new(GameService {
+        state: state.clone(),
+        bank,
+        verdicts: verdicts.clone(),
+    })
+}
+
+#[post("/graphql", data = "<request>")]
+async fn graphql_post(
+    request: GraphQLRequest,
+    schema: &State<GameSchema>,
+    state: &State<AppState>,
+    bank: Bank,
+    verdicts: &State<Verdicts>,
+) -> GraphQLResponse {
+    request.data(game_service(state, bank, verdicts)).execute(schema.inner()).await
+}
+
+// Subscriptions, one response per server-sent event. Plain queries work
+// too and end after their single response.
+#[post("/graphql/stream", data = "<request>")]
+fn graphql_stream(
+    request: GraphQLRequest,
+    schema: &State<GameSchema>,
+    state: &State<AppState>,
+    bank: Bank,
+    verdicts: &State<Verdicts>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], Status> {
+    let request = request.0.into_single().map_err(|_| Status::BadRequest)?;
+    let mut responses = schema.execute_stream(request.data(game_service(state, bank, verdicts)));
+    Ok(EventStream! {
+        loop {
+            let response = select! {
+                next = responses.next() => match next {
+                    Some(response) => response,
+                    None => break,
+                },
+                _ = &mut shutdown => break,
+            };
+            yield Event::json(&response);
+        }
+    })
+}
@@ -0,0 +1,404 @@
+use super::*;
+
+// The question loop: each question kind's answer route, and how a room moves
+// from one question to the next until everyone's sent to the result page.
+
+// --- Forms ---
+#[derive(FromForm)]
+struct AnswerForm {
+    player: String,
+    tab: TabStamp,
+    // repeated once per blank
+    answer: Vec<String>,
+    // only read in wager rooms; blank means no bet
+    wager: Option<u32>,
+}
+
+#[derive(FromForm)]
+struct RankForm {
+    player: String,
+    tab: TabStamp,
+    // option indexes, first = ranked highest
+    order: Vec<usize>,
+    wager: Option<u32>,
+}
+
+impl RankForm {
+    fn to_ranking(&self, options: &[String]) -> Option<Vec<String>> {
+        ranking(&self.order, options)
+    }
+}
+
+#[derive(FromForm)]
+struct PickForm {
+    player: String,
+    // picture option id, or the multiple-choice answer itself
+    option: String,
+    wager: Option<u32>,
+    tab: TabStamp,
+}
+
+#[derive(FromForm)]
+struct RateForm {
+    player: String,
+    value: i32,
+    wager: Option<u32>,
+    tab: TabStamp,
+}
+
+// --- Game flow ---
+impl Room {
+    // Current question is now live for everyone: stamp it and start the clock.
+    // Held back until the calibration round is over.
+    pub(super) fn start_question(&mut self, now: u64) {
+        if self.calibrating() {
+            return;
+        }
+        self.asked_at.insert(self.current_question_index, now);
+        self.timer.start(now);
+    }
+
+    // The question at `idx` as it was dealt, falling back to the bank for
+    // rooms from before snapshots.
+    pub(super) fn seen<'a>(&'a self, idx: usize, bank: &'a QuestionBank) -> Option<&'a Question> {
+        let id = self.questions.get(idx)?;
+        self.deck.get(id).or_else(|| bank.get(id))
+    }
+
+    pub(super) fn current_question<'a>(&'a self, bank: &'a QuestionBank) -> Option<&'a Question> {
+        self.seen(self.current_question_index, bank)
+    }
+
+    pub(super) fn submit_answer(&mut self, player_id: &str, answer: Answer, bank: &QuestionBank) {
+        let idx = self.current_question_index;
+        let Some(answers) = self.answers.get_mut(idx) else {
+            return;
+        };
+        let submission = answer.submission.clone();
+        answers.insert(player_id.to_string(), answer);
+        let player_name = self.players.iter().find(|p| p.id == player_id).map(|p| p.name.expose().clone());
+        self.log_from(
+            RoomEvent::AnswerSubmitted {
+                player_id: player_id.to_string(),
+                player_name: player_name.unwrap_or_default(),
+                question_index: idx,
+            },
+            submission,
+        );
+        let Some(answers) = self.answers.get(idx) else {
+            return;
+        };
+
+        // everyone in the room has answered -> move on
+        if answers.len() >= self.players.len() && self.in_play() {
+            self.resolve(idx, bank, now_secs());
+        }
+    }
+
+    // Reveals question `idx` and puts the next one up, or ends the round.
+    pub(super) fn resolve(&mut self, idx: usize, bank: &QuestionBank, now: u64) {
+        let question = self.seen(idx, bank).cloned();
+        self.reveal(idx, question.as_ref());
+        self.current_question_index += 1;
+        self.notice = None;
+        if self.queue_follow_up(idx, bank) {
+            self.start_question(now);
+        } else if self.endless {
+            self.draw_next(bank);
+            self.start_question(now);
+        } else if self.current_question_index >= self.questions.len() {
+            self.end_main_round(now);
+        } else {
+            self.start_question(now);
+        }
+    }
+
+    // Time's up on the current question: whoever hasn't answered gets a
+    // timed-out answer, which matches nothing, and the question resolves.
+    // False if there's time left (or no clock at all).
+    pub(super) fn expire_question(&mut self, now: u64, bank: &QuestionBank) -> bool {
+        if self.finished || self.in_lightning() || !self.timer.is_running() || self.timer.remaining(now) != Some(0) {
+            return false;
+        }
+        let idx = self.current_question_index;
+        let ids: Vec<String> = self.players.iter().map(|p| p.id.clone()).collect();
+        let Some(answers) = self.answers.get_mut(idx) else {
+            return false;
+        };
+        for id in ids {
+            answers.entry(id).or_insert_with(|| Answer {
+                items: Vec::new().into(),
+                wager: 0,
+                answered_at: now,
+                submission: None,
+                timed_out: true,
+            });
+        }
+        self.log(RoomEvent::QuestionTimedOut { question_index: idx });
+        self.resolve(idx, bank, now);
+        true
+    }
+
+    // Slots a follow-up in right after question `idx` if its answers call for
+    // one. Each follow-up is asked at most once per room.
+    fn queue_follow_up(&mut self, idx: usize, bank: &QuestionBank) -> bool {
+        let answers = &self.answers[idx];
+        let items: Vec<&[String]> = answers.values().map(|a| a.items.as_slice()).collect();
+        let credit = self.credits.last().copied().unwrap_or(0.0);
+        let Some(next) = bank.follow_up(&self.questions[idx], &items, credit, self.tenant.as_deref()) else {
+            return false;
+        };
+        if self.questions.contains(&next.id) {
+            return false;
+        }
+        self.deck.insert(next.id.clone(), next.clone());
+        self.questions.insert(idx + 1, next.id.clone());
+        self.answers.insert(idx + 1, HashMap::new());
+        true
+    }
+
+    // Regular questions are done: either on to the lightning round or finished.
+    pub(super) fn end_main_round(&mut self, now: u64) {
+        self.timer.stop();
+        if self.lightning_enabled && self.lightning.is_none() {
+            self.lightning = Some(LightningRound::start(now));
+        } else {
+            self.finish();
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        self.finished = true;
+        let score = self.compatibility();
+        self.log(RoomEvent::GameFinished {
+            score,
+            cue: Cue::for_finish(score),
+        });
+    }
+}
+
+// --- Routes ---
+#[post("/play/<code>/answer", data = "<form>")]
+pub(super) fn answer_post(
+    code: String,
+    form: Form<AnswerForm>,
+    state: &State<AppState>,
+    bank: Bank,
+    notifier: &State<Notifier>,
+    me: CurrentPlayer,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if let Some(back) = superseded(room, &form.player, &form.tab) {
+        return Ok(back);
+    }
+    let question = room.current_question(&bank);
+    // other kinds have their own routes
+    if !matches!(question.map(|q| &q.kind), None | Some(QuestionKind::FreeText)) {
+        return Err(Status::BadRequest);
+    }
+    let blanks = question.map(|q| q.blanks).unwrap_or(1);
+    let items: Vec<String> = form
+        .answer
+        .iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect();
+    if items.is_empty() || items.len() > blanks {
+        return Err(Status::BadRequest);
+    }
+    accept_answer(room, &me, &form.player, items, form.wager, &form.tab, &bank, notifier)
+}
+
+// Ranking questions post the option indexes in the order the player dragged them.
+#[post("/play/<code>/rank", data = "<form>")]
+pub(super) fn rank_post(
+    code: String,
+    form: Form<RankForm>,
+    state: &State<AppState>,
+    bank: Bank,
+    notifier: &State<Notifier>,
+    me: CurrentPlayer,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if let Some(back) = superseded(room, &form.player, &form.tab) {
+        return Ok(back);
+    }
+    let options = match room.current_question(&bank) {
+        Some(Question {
+            kind: QuestionKind::Ranking { options },
+            ..
+        }) => options.clone(),
+        _ => return Err(Status::BadRequest),
+    };
+    let items = form.to_ranking(&options).ok_or(Status::BadRequest)?;
+    accept_answer(room, &me, &form.player, items, form.wager, &form.tab, &bank, notifier)
+}
+
+// Picture questions post the chosen option's id, multiple choice the option.
+#[post("/play/<code>/pick", data = "<form>")]
+pub(super) fn pick_post(
+    code: String,
+    form: Form<PickForm>,
+    state: &State<AppState>,
+    bank: Bank,
+    notifier: &State<Notifier>,
+    me: CurrentPlayer,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if let Some(back) = superseded(room, &form.player, &form.tab) {
+        return Ok(back);
+    }
+    let valid = match room.current_question(&bank) {
+        Some(Question {
+            kind: QuestionKind::PictureChoice { options },
+            ..
+        }) => options.iter().any(|o| o.id == form.option),
+        Some(Question {
+            kind: QuestionKind::MultipleChoice { options },
+            ..
+        }) => options.contains(&form.option),
+        _ => false,
+    };
+    if !valid {
+        return Err(Status::BadRequest);
+    }
+    accept_answer(room, &me, &form.player, vec![form.option.clone()], form.wager, &form.tab, &bank, notifier)
+}
+
+// Scale questions post a number within the question's range.
+#[post("/play/<code>/rate", data = "<form>")]
+pub(super) fn rate_post(
+    code: String,
+    form: Form<RateForm>,
+    state: &State<AppState>,
+    bank: Bank,
+    notifier: &State<Notifier>,
+    me: CurrentPlayer,
+) -> Result<Redirect, Status> {
+    let mut map = state.rooms.write();
+    let room = map.get_mut(&code).ok_or(Status::NotFound)?;
+    if let Some(back) = superseded(room, &form.player, &form.tab) {
+        return Ok(back);
+    }
+    let valid = match room.current_question(&bank) {
+        Some(Question {
+            kind: QuestionKind::Scale { min, max },
+            ..
+        }) => (*min..=*max).contains(&form.value),
+        _ => false,
+    };
+    if !valid {
+        return Err(Status::BadRequest);
+    }
+    accept_answer(room, &me, &form.player, vec![form.value.to_string()], form.wager, &form.tab, &bank, notifier)
+}
+
+// Shared tail of the answer routes: identity, then record.
+#[allow(clippy::too_many_arguments)]
+fn accept_answer(
+    room: &mut Room,
+    me: &CurrentPlayer,
+    player: &str,
+    items: Vec<String>,
+    wager: Option<u32>,
+    tab: &TabStamp,
+    bank: &QuestionBank,
+    notifier: &Notifier,
+) -> Result<Redirect, Status> {
+    if !acts_as(room, me, player) {
+        return Err(Status::Forbidden);
+    }
+    if !room.finished {
+        match record_answer(room, player, items, wager, tab, bank, notifier) {
+            // out of time; the play page shows how the question went
+            Err(status) if status == Status::Conflict => {}
+            result => result?,
+        }
+    }
+    let code = room.code.clone();
+    if room.finished {
+        Ok(Redirect::to(uri!(super::result_get(code = code))))
+    } else {
+        Ok(Redirect::to(uri!(super::play_get(
+            code = code,
+            player = Some(player.to_string())
+        ))))
+    }
+}
+
+// Wager check, then the answer goes in; for a room still being played.
+pub(super) fn record_answer(
+    room: &mut Room,
+    player: &str,
+    items: Vec<String>,
+    wager: Option<u32>,
+    tab: &TabStamp,
+    bank: &QuestionBank,
+    notifier: &Notifier,
+) -> Result<(), Status> {
+    // nobody plays while someone is still at the consent gate or warming up
+    if !room.in_play() || room.players.iter().any(|p| room.needs_consent(&p.id)) || room.calibrating() {
+        return Err(Status::BadRequest);
+    }
+    if room.is_full_up() {
+        return Err(Status::InsufficientStorage);
+    }
+
+    let wager = if room.wagers { wager.unwrap_or(0) } else { 0 };
+    let balance = room
+        .players
+        .iter()
+        .find(|p| p.id == player)
+        .map(|p| p.score)
+        .unwrap_or(0);
+    if wager > balance {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    // too late: the question resolved without them
+    if room.expire_question(now_secs(), bank) {
+        return Err(Status::Conflict);
+    }
+
+    room.touch(player, now_secs());
+    let answer = Answer {
+        items: items.into(),
+        wager,
+        answered_at: now_secs(),
+        submission: tab.submission.clone(),
+        timed_out: false,
+    };
+    let question_before = room.current_question_index;
+    room.submit_answer(player, answer, bank);
+    // still on the same question = the partner hasn't answered yet, nudge them
+    if !room.finished && room.current_question_index == question_before {
+        let name = room.players.iter().find(|p| p.id == player).map(|p| p.name.clone()).unwrap_or_default();
+        for p in room.players.iter().filter(|p| p.id != player) {
+            notifier.partner_answered(&room.code, &p.id, &p.notify, &name);
+        }
+    }
+    Ok(())
+}
+
+// An answer form that no longer applies: sent from a tab left on an earlier
+// question, or the same form sent twice. Checked before the question kind,
+// which may have changed since, and answered by showing the tab where the
+// game is now.
+pub(super) fn superseded(room: &Room, player: &str, tab: &TabStamp) -> Option<Redirect> {
+    let idx = room.current_question_index;
+    let behind = tab.question.is_some_and(|q| q != idx);
+    let resent = room
+        .answers
+        .get(idx)
+        .and_then(|a| a.get(player))
+        .is_some_and(|a| a.submission.is_some() && a.submission == tab.submission);
+    (behind || resent).then(|| {
+        Redirect::to(uri!(super::play_get(
+            code = room.code.clone(),
+            player = Some(player.to_string())
+        )))
+    })
+}
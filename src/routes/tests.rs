@@ -0,0 +1,295 @@
+use rocket::http::Cookie;
+use rocket::local::blocking::{Client, LocalResponse};
+use rocket::serde::de::DeserializeOwned;
+
+use super::*;
+
+// --- Validated bodies ---
+// Every rule a body is checked against, broken on its own in an otherwise
+// good body: the request fails with that rule's field and code.
+
+fn body<T: DeserializeOwned>(value: Value) -> T {
+    json::from_value(value).expect("the body should parse")
+}
+
+// (field, code) of a 422.
+fn refused(result: Result<impl std::fmt::Debug, ApiError>) -> (String, String) {
+    let e = result.expect_err("the body should be refused");
+    assert_eq!(e.status, Status::UnprocessableEntity);
+    (e.field.unwrap_or_default(), e.code)
+}
+
+fn create(value: Value) -> (String, String) {
+    refused(body::<CreateRoomBody>(value).validate())
+}
+
+fn question(kind: Value) -> Question {
+    body(json!({ "id": "q", "text": "Which one?", "category": "fun", "kind": kind }))
+}
+
+fn answer(value: Value, question: Option<&Question>) -> (String, String) {
+    refused(body::<AnswerBody>(value).items(question))
+}
+
+fn pair(field: &str, code: &str) -> (String, String) {
+    (field.to_string(), code.to_string())
+}
+
+#[test]
+fn a_good_create_body_passes() {
+    let good = json!({ "host_name": "Ada", "question_count": 25, "capacity": 2, "surprise_date": "2026-02-14" });
+    assert!(body::<CreateRoomBody>(good).validate().is_ok());
+}
+
+#[test]
+fn create_needs_a_host_name() {
+    assert_eq!(create(json!({ "host_name": "  " })), pair("host_name", "required"));
+}
+
+#[test]
+fn create_takes_only_the_offered_question_counts() {
+    assert_eq!(create(json!({ "host_name": "Ada", "question_count": 7 })), pair("question_count", "invalid_count"));
+}
+
+#[test]
+fn create_capacity_fits_the_game_mode() {
+    assert_eq!(create(json!({ "host_name": "Ada", "capacity": 3 })), pair("capacity", "invalid_capacity"));
+    let party = json!({ "host_name": "Ada", "game_mode": "party", "capacity": 9 });
+    assert_eq!(create(party), pair("capacity", "invalid_capacity"));
+}
+
+#[test]
+fn create_surprise_date_is_a_day() {
+    let vague = json!({ "host_name": "Ada", "surprise_date": "next friday" });
+    assert_eq!(create(vague), pair("surprise_date", "invalid_date"));
+}
+
+#[test]
+fn join_needs_a_name() {
+    assert!(body::<JoinRoomBody>(json!({ "name": "Ben" })).validate().is_ok());
+    assert_eq!(refused(body::<JoinRoomBody>(json!({ "name": "" })).validate()), pair("name", "required"));
+}
+
+#[test]
+fn free_text_needs_an_answer() {
+    assert_eq!(answer(json!({ "answer": [" ", ""] }), None), pair("answer", "required"));
+}
+
+#[test]
+fn free_text_fits_the_blanks() {
+    let one_blank = question(json!({ "type": "free_text" }));
+    let two = json!({ "answer": ["Rome", "Oslo"] });
+    assert_eq!(answer(two, Some(&one_blank)), pair("answer", "too_many"));
+}
+
+#[test]
+fn ranking_lists_every_option_once() {
+    let q = question(json!({ "type": "ranking", "options": ["a", "b", "c"] }));
+    assert!(body::<AnswerBody>(json!({ "order": [2, 0, 1] })).items(Some(&q)).is_ok());
+    for order in [json!([0, 1]), json!([0, 0, 1]), json!([0, 1, 3])] {
+        assert_eq!(answer(json!({ "order": order }), Some(&q)), pair("order", "not_a_ranking"));
+    }
+}
+
+#[test]
+fn picture_choice_takes_one_of_its_ids() {
+    let options = json!([{ "id": "beach", "label": "Beach", "image": "img/beach.svg" }]);
+    let q = question(json!({ "type": "picture_choice", "options": options }));
+    assert!(body::<AnswerBody>(json!({ "option": "beach" })).items(Some(&q)).is_ok());
+    assert_eq!(answer(json!({ "option": "Beach" }), Some(&q)), pair("option", "unknown_option"));
+    assert_eq!(answer(json!({}), Some(&q)), pair("option", "unknown_option"));
+}
+
+#[test]
+fn multiple_choice_takes_one_of_its_options() {
+    let q = question(json!({ "type": "multiple_choice", "options": ["Coffee", "Tea"] }));
+    assert!(body::<AnswerBody>(json!({ "option": "Tea" })).items(Some(&q)).is_ok());
+    assert_eq!(answer(json!({ "option": "Cocoa" }), Some(&q)), pair("option", "unknown_option"));
+}
+
+#[test]
+fn scale_stays_in_range() {
+    let q = question(json!({ "type": "scale", "min": 1, "max": 5 }));
+    assert!(body::<AnswerBody>(json!({ "value": 5 })).items(Some(&q)).is_ok());
+    assert_eq!(answer(json!({ "value": 6 }), Some(&q)), pair("value", "out_of_range"));
+    assert_eq!(answer(json!({}), Some(&q)), pair("value", "out_of_range"));
+}
+
+// --- Joining twice ---
+// A refresh or a double click sends the join form again, and the back
+// button goes back to it, from a browser that's already in the room: both
+// end up at the seat it has, and nobody is seated twice.
+
+// every *_path setting, so tests leave the real data alone
+const DATA_KEYS: &[&str] = &[
+    "outbox_path",
+    "question_store_path",
+    "snapshot_path",
+    "history_path",
+    "announcement_path",
+    "audit_path",
+];
+
+fn client() -> Client {
+    let rocket = build_rocket();
+    // every test comes from the same address
+    let off = json!({ "burst": 0, "per_minute": 0 });
+    let scratch = std::env::temp_dir().join(format!("now-tests-{}", Uuid::new_v4().simple()));
+    let mut figment = rocket
+        .figment()
+        .clone()
+        .merge(("log_level", "critical"))
+        .merge(("room_storage", "memory"))
+        .merge(("shutdown_snapshot", false))
+        .merge(("form_rate_limits", json!({ "create": off, "join": off })));
+    for key in DATA_KEYS {
+        figment = figment.merge((*key, scratch.join(format!("{}.json", key))));
+    }
+    Client::untracked(rocket.configure(figment)).expect("the app starts")
+}
+
+// One browser: its own cookies, past the age gate.
+struct Browser(Vec<Cookie<'static>>);
+
+impl Browser {
+    fn new() -> Browser {
+        Browser(vec![Cookie::new("age_ok", "99")])
+    }
+
+    fn post(&mut self, client: &Client, uri: &str, form: &str) -> String {
+        let response = client
+            .post(uri.to_string())
+            .header(ContentType::Form)
+            .cookies(self.0.clone())
+            .body(form)
+            .dispatch();
+        self.follow(response)
+    }
+
+    fn get(&mut self, client: &Client, uri: &str) -> String {
+        let response = client.get(uri.to_string()).cookies(self.0.clone()).dispatch();
+        self.follow(response)
+    }
+
+    // Keeps what the response set and returns where it redirects.
+    fn follow(&mut self, response: LocalResponse<'_>) -> String {
+        for cookie in response.cookies().iter() {
+            self.0.retain(|c| c.name() != cookie.name());
+            self.0.push(cookie.clone());
+        }
+        assert_eq!(response.status(), Status::SeeOther);
+        response.headers().get_one("Location").expect("a redirect").to_string()
+    }
+}
+
+// A room hosted from its own browser, by its form settings.
+fn host(client: &Client, settings: &str) -> String {
+    let location = Browser::new().post(client, "/create", &format!("host_name=Ada&{}", settings));
+    let code = location.strip_prefix("/play/").and_then(|rest| rest.split('?').next());
+    code.expect("the host is sent to the room").to_string()
+}
+
+// (seated, waiting) in room `code`.
+fn seated(client: &Client, code: &str) -> (usize, usize) {
+    let state = client.rocket().state::<AppState>().expect("app state");
+    let map = state.rooms.read();
+    let room = &map[code];
+    (room.players.len(), room.pending.len() + room.queue.len())
+}
+
+#[test]
+fn a_refreshed_join_goes_back_to_the_seat() {
+    let client = client();
+    let code = host(&client, "game_mode=friends");
+    let mut guest = Browser::new();
+    let join = format!("code={}&name=Ben", code);
+    let first = guest.post(&client, "/join", &join);
+    assert!(first.starts_with(&format!("/play/{}?player=", code)));
+    assert_eq!(guest.post(&client, "/join", &join), first);
+    assert_eq!(seated(&client, &code), (2, 0));
+}
+
+#[test]
+fn back_to_the_join_page_goes_back_to_the_seat() {
+    let client = client();
+    let code = host(&client, "game_mode=friends");
+    let mut guest = Browser::new();
+    let seat = guest.post(&client, "/join", &format!("code={}&name=Ben", code));
+    assert_eq!(guest.get(&client, &format!("/join?code={}", code)), seat);
+    assert_eq!(seated(&client, &code), (2, 0));
+}
+
+#[test]
+fn another_browser_still_gets_its_own_seat() {
+    let client = client();
+    let code = host(&client, "game_mode=friends");
+    let ben = Browser::new().post(&client, "/join", &format!("code={}&name=Ben", code));
+    let cy = Browser::new().post(&client, "/join", &format!("code={}&name=Cy", code));
+    assert_ne!(ben, cy);
+    assert_eq!(seated(&client, &code), (3, 0));
+}
+
+#[test]
+fn a_refreshed_join_waits_in_the_same_place() {
+    let client = client();
+    let code = host(&client, "game_mode=friends&approve_joins=true");
+    let mut guest = Browser::new();
+    let join = format!("code={}&name=Ben", code);
+    let waiting = guest.post(&client, "/join", &join);
+    assert!(waiting.starts_with(&format!("/play/{}/pending?player=", code)));
+    assert_eq!(guest.post(&client, "/join", &join), waiting);
+    assert_eq!(guest.get(&client, &format!("/join?code={}", code)), waiting);
+    assert_eq!(seated(&client, &code), (1, 1));
+}
+
+// --- Someone else's player id ---
+// Player ids show up in links and the event stream; a browser that posts
+// another player's id is turned away.
+
+// Ben's id in a friends room, and a third browser seated next to him.
+fn neighbours(client: &Client) -> (String, String, Browser) {
+    let code = host(client, "game_mode=friends");
+    let ben = Browser::new().post(client, "/join", &format!("code={}&name=Ben", code));
+    let ben = ben.split_once("?player=").expect("Ben is seated").1.to_string();
+    let mut cy = Browser::new();
+    cy.post(client, "/join", &format!("code={}&name=Cy", code));
+    (code, ben, cy)
+}
+
+#[test]
+fn posting_as_another_player_is_forbidden() {
+    let client = client();
+    let (code, ben, cy) = neighbours(&client);
+    let forms = [
+        ("consent", format!("player={}&agree=true", ben)),
+        ("story", format!("player={}&choice=0", ben)),
+        ("settings", format!("player={}&partner_answered=true", ben)),
+    ];
+    for (route, form) in forms {
+        let response = client
+            .post(format!("/play/{}/{}", code, route))
+            .header(ContentType::Form)
+            .cookies(cy.0.clone())
+            .body(form)
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden, "{}", route);
+    }
+    let heartbeat = format!("/play/{}/heartbeat?player={}", code, ben);
+    assert_eq!(client.post(heartbeat).cookies(cy.0.clone()).dispatch().status(), Status::Forbidden);
+    let settings = format!("/play/{}/settings?player={}", code, ben);
+    assert_eq!(client.get(settings).cookies(cy.0).dispatch().status(), Status::Forbidden);
+}
+
+#[test]
+fn a_browser_without_a_seat_is_forbidden() {
+    let client = client();
+    let (code, ben, _) = neighbours(&client);
+    let heartbeat = format!("/play/{}/heartbeat?player={}", code, ben);
+    assert_eq!(client.post(heartbeat).dispatch().status(), Status::Forbidden);
+    let story = client
+        .post(format!("/play/{}/story", code))
+        .header(ContentType::Form)
+        .body(format!("player={}&choice=0", ben))
+        .dispatch();
+    assert_eq!(story.status(), Status::Forbidden);
+}
@@ -0,0 +1,165 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::futures::future::BoxFuture;
+use rocket::serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Small periodic-job runner. Jobs are registered while building rocket and
+// start ticking once the server has lifted off.
+
+const TICK: Duration = Duration::from_secs(1);
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+// --- Schedules ---
+#[derive(Clone, Debug)]
+pub enum Schedule {
+    // run every N, first run one interval after liftoff
+    Every(Duration),
+    // run once a day at HH:MM (UTC), cron-style "M H * * *"
+    DailyAt { hour: u32, minute: u32 },
+}
+
+impl Schedule {
+    fn next_after(&self, now: u64) -> u64 {
+        match self {
+            Schedule::Every(d) => now + d.as_secs().max(1),
+            Schedule::DailyAt { hour, minute } => {
+                let day = 24 * 60 * 60;
+                let offset = (*hour as u64 % 24) * 3600 + (*minute as u64 % 60) * 60;
+                let today = now - now % day + offset;
+                if today > now {
+                    today
+                } else {
+                    today + day
+                }
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Schedule::Every(d) => format!("every {}s", d.as_secs()),
+            Schedule::DailyAt { hour, minute } => format!("daily at {:02}:{:02} UTC", hour, minute),
+        }
+    }
+}
+
+// --- Status ---
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct JobStatus {
+    pub name: String,
+    pub schedule: String,
+    pub last_run: Option<u64>,
+    pub next_run: u64,
+    pub runs: u64,
+    pub running: bool,
+    pub last_error: Option<String>,
+}
+
+struct Job {
+    schedule: Schedule,
+    task: JobFn,
+    status: JobStatus,
+}
+
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    jobs: Arc<RwLock<Vec<Job>>>,
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Scheduler {
+    pub fn register<F, Fut>(&self, name: &str, schedule: Schedule, f: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let status = JobStatus {
+            name: name.to_string(),
+            schedule: schedule.describe(),
+            last_run: None,
+            next_run: schedule.next_after(now_secs()),
+            runs: 0,
+            running: false,
+            last_error: None,
+        };
+        let task: JobFn = Arc::new(move || Box::pin(f()));
+        self.jobs.write().push(Job { schedule, task, status });
+    }
+
+    pub fn status(&self) -> Vec<JobStatus> {
+        self.jobs.read().iter().map(|j| j.status.clone()).collect()
+    }
+
+    // Marks due jobs as running and hands back what needs to be spawned.
+    fn take_due(&self, now: u64) -> Vec<(usize, JobFn)> {
+        let mut jobs = self.jobs.write();
+        jobs.iter_mut()
+            .enumerate()
+            .filter(|(_, j)| !j.status.running && j.status.next_run <= now)
+            .map(|(i, j)| {
+                j.status.running = true;
+                (i, j.task.clone())
+            })
+            .collect()
+    }
+
+    fn finish(&self, idx: usize, result: Result<(), String>) {
+        let now = now_secs();
+        let mut jobs = self.jobs.write();
+        if let Some(job) = jobs.get_mut(idx) {
+            job.status.running = false;
+            job.status.runs += 1;
+            job.status.last_run = Some(now);
+            job.status.next_run = job.schedule.next_after(now);
+            job.status.last_error = result.err();
+        }
+    }
+
+    pub fn fairing(&self) -> AdHoc {
+        let scheduler = self.clone();
+        AdHoc::on_liftoff("Job Scheduler", move |_| {
+            Box::pin(async move {
+                rocket::tokio::spawn(async move {
+                    let mut ticker = rocket::tokio::time::interval(TICK);
+                    loop {
+                        ticker.tick().await;
+                        for (idx, task) in scheduler.take_due(now_secs()) {
+                            let scheduler = scheduler.clone();
+                            // a job that panics is recorded as failed and
+                            // scheduled again, rather than left running
+                            let run = rocket::tokio::spawn(task());
+                            rocket::tokio::spawn(async move {
+                                let result = match run.await {
+                                    Ok(result) => result,
+                                    Err(e) if e.is_panic() => Err(panic_message(e.into_panic())),
+                                    Err(e) => Err(e.to_string()),
+                                };
+                                scheduler.finish(idx, result);
+                            });
+                        }
+                    }
+                });
+            })
+        })
+    }
+}
+
+// What a job's panic said, for its `last_error`.
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or("no message", |m| *m).to_string(),
+    };
+    format!("panicked: {}", message)
+}
@@ -0,0 +1,490 @@
+use rocket::FromFormField;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use crate::questions::{Question, QuestionKind};
+
+// Free-text items match when at least this similar (see `similarity`);
+// exact unless a calibration round tuned it for the couple.
+pub const DEFAULT_MATCH_THRESHOLD: f64 = 1.0;
+
+// Picks the strategy for the question's kind. `threshold` applies to
+// free text only; choices must match exactly.
+pub fn question_credit(question: &Question, answers: &[&[String]], threshold: f64) -> f64 {
+    match &question.kind {
+        QuestionKind::FreeText => credit(answers, question.blanks, threshold),
+        QuestionKind::Ranking { .. } => ranking_credit(answers),
+        QuestionKind::PictureChoice { .. } | QuestionKind::MultipleChoice { .. } => {
+            credit(answers, 1, DEFAULT_MATCH_THRESHOLD)
+        }
+        QuestionKind::Scale { min, max } => scale_credit(answers, *min, *max),
+    }
+}
+
+// How much of a question the players agreed on, from 0.0 to 1.0.
+//
+// Each player's answer is a list of items ("name 3 things we both love").
+// Items are compared case-insensitively and in any order; every item that
+// all players gave earns 1/blanks of the credit. Below a threshold of 1,
+// an item counts as given when it's at least that similar to one the
+// others gave, each of their items matching once at most.
+pub fn credit(answers: &[&[String]], blanks: usize, threshold: f64) -> f64 {
+    let mut sets = answers.iter().map(|items| {
+        let mut set: Vec<String> = items.iter().map(|i| normalize(i)).filter(|i| !i.is_empty()).collect();
+        set.sort();
+        set.dedup();
+        set
+    });
+    let Some(first) = sets.next() else {
+        return 0.0;
+    };
+    let mut others: Vec<Vec<String>> = sets.collect();
+    let mut common = 0;
+    for item in &first {
+        // closest unmatched item in every other answer
+        let hits: Option<Vec<usize>> = others
+            .iter()
+            .map(|set| {
+                set.iter()
+                    .enumerate()
+                    .map(|(i, other)| (i, similarity(item, other)))
+                    .filter(|(_, s)| *s >= threshold)
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, _)| i)
+            })
+            .collect();
+        if let Some(hits) = hits {
+            for (set, i) in others.iter_mut().zip(hits) {
+                set.swap_remove(i);
+            }
+            common += 1;
+        }
+    }
+    (common as f64 / blanks.max(1) as f64).min(1.0)
+}
+
+fn normalize(item: &str) -> String {
+    item.trim().to_lowercase()
+}
+
+// 1.0 for the same text (ignoring case and surrounding space), falling with
+// the edit distance relative to the longer text.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = normalize(a).chars().collect();
+    let b: Vec<char> = normalize(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+// Levenshtein distance, one row at a time.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb { diagonal } else { 1 + diagonal.min(above).min(row[j]) };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+// Ranking answers are full orderings of the same options. Agreement is the
+// Kendall rank correlation (tau, -1..1) rescaled to 0..1 and averaged over
+// every pair of players, so swapping two neighbours still scores high.
+pub fn ranking_credit(answers: &[&[String]]) -> f64 {
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for (i, a) in answers.iter().enumerate() {
+        for b in &answers[i + 1..] {
+            total += (kendall_tau(a, b) + 1.0) / 2.0;
+            pairs += 1;
+        }
+    }
+    if pairs == 0 {
+        0.0
+    } else {
+        total / pairs as f64
+    }
+}
+
+// Scale answers are numbers; two players earn less the further apart they
+// are, nothing at opposite ends. Averaged over every pair of players.
+pub fn scale_credit(answers: &[&[String]], min: i32, max: i32) -> f64 {
+    let span = (max - min).max(1) as f64;
+    let values: Vec<Option<i32>> = answers
+        .iter()
+        .map(|a| a.first().and_then(|v| v.trim().parse().ok()))
+        .collect();
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for (i, a) in values.iter().enumerate() {
+        for b in &values[i + 1..] {
+            if let (Some(a), Some(b)) = (a, b) {
+                total += 1.0 - (a - b).abs() as f64 / span;
+            }
+            pairs += 1;
+        }
+    }
+    if pairs == 0 {
+        0.0
+    } else {
+        (total / pairs as f64).clamp(0.0, 1.0)
+    }
+}
+
+pub fn kendall_tau(a: &[String], b: &[String]) -> f64 {
+    let pos_b: HashMap<&str, usize> = b.iter().enumerate().map(|(i, o)| (o.as_str(), i)).collect();
+    // b's positions listed in a's order; only options both ranked count
+    let ranks: Vec<usize> = a.iter().filter_map(|o| pos_b.get(o.as_str()).copied()).collect();
+    let n = ranks.len();
+    if n < 2 {
+        return if n == 1 { 1.0 } else { 0.0 };
+    }
+    let mut concordant = 0i64;
+    let mut discordant = 0i64;
+    for i in 0..n {
+        for j in i + 1..n {
+            if ranks[i] < ranks[j] {
+                concordant += 1;
+            } else {
+                discordant += 1;
+            }
+        }
+    }
+    (concordant - discordant) as f64 / (n * (n - 1) / 2) as f64
+}
+
+// --- Game modes ---
+// Couples score as one. Friends groups score every pair of players and
+// see who's most in sync; parties rank players by how well each matched
+// the rest of the room.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromFormField)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum GameMode {
+    #[default]
+    Couple,
+    Friends,
+    Party,
+}
+
+pub const COUPLE_SIZE: usize = 2;
+pub const MAX_GROUP_SIZE: usize = 8;
+// seats a group room gets when the host doesn't say
+pub const DEFAULT_GROUP_SIZE: usize = 4;
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Couple, GameMode::Friends, GameMode::Party];
+
+    pub fn is_group(self) -> bool {
+        self != GameMode::Couple
+    }
+
+    // Room sizes the mode allows.
+    pub fn capacities(self) -> RangeInclusive<usize> {
+        match self {
+            GameMode::Couple => COUPLE_SIZE..=COUPLE_SIZE,
+            _ => COUPLE_SIZE..=MAX_GROUP_SIZE,
+        }
+    }
+
+    pub fn default_capacity(self) -> usize {
+        match self {
+            GameMode::Couple => COUPLE_SIZE,
+            _ => DEFAULT_GROUP_SIZE,
+        }
+    }
+}
+
+// Agreement between every two players on one question, players by
+// position. A group's credit is the average over its pairs, so a room
+// where everyone agrees still earns 1.0.
+pub struct Pairwise {
+    pairs: Vec<(usize, usize, f64)>,
+}
+
+impl Pairwise {
+    pub fn score(players: usize, credit: impl Fn(usize, usize) -> f64) -> Pairwise {
+        let mut pairs = Vec::new();
+        for a in 0..players {
+            for b in a + 1..players {
+                pairs.push((a, b, credit(a, b)));
+            }
+        }
+        Pairwise { pairs }
+    }
+
+    pub fn pairs(&self) -> &[(usize, usize, f64)] {
+        &self.pairs
+    }
+
+    pub fn overall(&self) -> f64 {
+        mean(self.pairs.iter().map(|p| p.2))
+    }
+
+    // How well one player matched everyone else.
+    pub fn member(&self, player: usize) -> f64 {
+        mean(self.pairs.iter().filter(|p| p.0 == player || p.1 == player).map(|p| p.2))
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, n) = values.fold((0.0, 0), |(sum, n), v| (sum + v, n + 1));
+    if n == 0 {
+        0.0
+    } else {
+        sum / n as f64
+    }
+}
+
+// --- Levels ---
+// Scores as words, for pages that would otherwise say it only in color.
+// Each level also has a symbol and a fill pattern to draw alongside the
+// color, so red/green is never the only difference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Level {
+    Low,
+    Medium,
+    High,
+    Perfect,
+}
+
+impl Level {
+    // From a 0-100 percentage.
+    pub fn of(pct: u32) -> Level {
+        match pct {
+            100.. => Level::Perfect,
+            70.. => Level::High,
+            40.. => Level::Medium,
+            _ => Level::Low,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Low => "low",
+            Level::Medium => "medium",
+            Level::High => "high",
+            Level::Perfect => "perfect",
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Level::Low => "○",
+            Level::Medium => "◐",
+            Level::High => "●",
+            Level::Perfect => "★",
+        }
+    }
+
+    // Named fills the pages define in CSS.
+    pub fn pattern(self) -> &'static str {
+        match self {
+            Level::Low => "dots",
+            Level::Medium => "stripes",
+            Level::High => "crosshatch",
+            Level::Perfect => "solid",
+        }
+    }
+
+    // What templates and the API get: `{ level, symbol, pattern }`.
+    pub fn hint(pct: u32) -> LevelHint {
+        let level = Level::of(pct);
+        LevelHint {
+            level,
+            symbol: level.symbol(),
+            pattern: level.pattern(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LevelHint {
+    pub level: Level,
+    pub symbol: &'static str,
+    pub pattern: &'static str,
+}
+
+// --- Category weights ---
+// `category_weights = { deep = 2.0, funny = 0.5 }` makes agreeing on some
+// categories count for more of the compatibility score. Unlisted categories
+// weigh 1. Rooms keep the weights they were created with.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", transparent)]
+pub struct CategoryWeights(HashMap<String, f64>);
+
+impl CategoryWeights {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        if figment.find_value("category_weights").is_err() {
+            return Ok(CategoryWeights::default());
+        }
+        let raw: HashMap<String, f64> = figment
+            .extract_inner("category_weights")
+            .map_err(|e| format!("invalid `category_weights` config: {}", e))?;
+        let mut weights = HashMap::new();
+        for (category, weight) in raw {
+            if !weight.is_finite() || weight <= 0.0 {
+                return Err(format!("category weight for `{}` must be above 0", category));
+            }
+            weights.insert(normalize(&category), weight);
+        }
+        Ok(CategoryWeights(weights))
+    }
+
+    pub fn weight(&self, category: &str) -> f64 {
+        self.0.get(&normalize(category)).copied().unwrap_or(1.0)
+    }
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Category Weights", |rocket| async move {
+        let weights = CategoryWeights::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(weights)
+    })
+}
+
+// --- Leaderboard normalization ---
+// A 90% story game and a 90% guess-mode game with 50 questions aren't the
+// same feat, so the leaderboard ranks by rating rather than raw score: how
+// many standard deviations a game sits from the others played the same way
+// (same mode and length band), as 50 plus 15 per deviation, kept to 0-100.
+// A group with too few games to say much is measured against every game.
+pub const NORMALIZATION_METHOD: &str = "z-score within mode and length band";
+pub const NORMALIZATION_DETAIL: &str = "rating = 50 + 15 * (score - group mean) / group standard deviation, \
+     clamped to 0-100; a group is games of the same mode and length band (short: up to 10 questions, medium: \
+     up to 25, long: more), and groups with fewer than 10 games use the mean and deviation of all games";
+pub const MIN_GROUP_GAMES: usize = 10;
+const RATING_CENTER: f64 = 50.0;
+const RATING_PER_DEVIATION: f64 = 15.0;
+
+pub fn length_band(questions: usize) -> &'static str {
+    match questions {
+        0..=10 => "short",
+        11..=25 => "medium",
+        _ => "long",
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Spread {
+    mean: f64,
+    deviation: f64,
+    games: usize,
+}
+
+impl Spread {
+    fn of(scores: &[f64]) -> Self {
+        if scores.is_empty() {
+            return Spread::default();
+        }
+        let n = scores.len() as f64;
+        let mean = scores.iter().sum::<f64>() / n;
+        let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+        Spread {
+            mean,
+            deviation: variance.sqrt(),
+            games: scores.len(),
+        }
+    }
+
+    fn rating(&self, score: f64) -> f64 {
+        // everyone scored the same: that's the middle of the pack
+        let z = if self.deviation > 0.0 { (score - self.mean) / self.deviation } else { 0.0 };
+        (RATING_CENTER + RATING_PER_DEVIATION * z).clamp(0.0, 100.0)
+    }
+}
+
+// Fitted on the games being ranked, so ratings move as more games come in.
+#[derive(Debug, Default)]
+pub struct Normalizer {
+    groups: HashMap<String, Spread>,
+    overall: Spread,
+}
+
+impl Normalizer {
+    // `games` is (mode, questions asked, raw score) per game.
+    pub fn fit<'a>(games: impl IntoIterator<Item = (&'a str, usize, u32)>) -> Self {
+        let mut by_group: HashMap<String, Vec<f64>> = HashMap::new();
+        let mut all = Vec::new();
+        for (mode, questions, score) in games {
+            by_group.entry(group(mode, questions)).or_default().push(score as f64);
+            all.push(score as f64);
+        }
+        Normalizer {
+            groups: by_group.into_iter().map(|(g, scores)| (g, Spread::of(&scores))).collect(),
+            overall: Spread::of(&all),
+        }
+    }
+
+    // The group a game was measured against, and its rating there.
+    pub fn rate(&self, mode: &str, questions: usize, score: u32) -> (String, f64) {
+        let key = group(mode, questions);
+        match self.groups.get(&key).filter(|s| s.games >= MIN_GROUP_GAMES) {
+            Some(spread) => (key, spread.rating(score as f64)),
+            None => ("all".to_string(), self.overall.rating(score as f64)),
+        }
+    }
+}
+
+fn group(mode: &str, questions: usize) -> String {
+    format!("{}/{}", mode, length_band(questions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn identical_orders_agree_fully() {
+        let a = order(&["Board games", "Cinema", "Long walk", "Dinner"]);
+        assert_eq!(kendall_tau(&a, &a), 1.0);
+        assert_eq!(ranking_credit(&[&a[..], &a[..]]), 1.0);
+    }
+
+    #[test]
+    fn reversed_orders_disagree_fully() {
+        let a = order(&["Board games", "Cinema", "Long walk", "Dinner"]);
+        let b = order(&["Dinner", "Long walk", "Cinema", "Board games"]);
+        assert_eq!(kendall_tau(&a, &b), -1.0);
+        assert_eq!(ranking_credit(&[&a[..], &b[..]]), 0.0);
+    }
+
+    #[test]
+    fn one_swap_of_neighbours_still_scores_high() {
+        let a = order(&["Board games", "Cinema", "Long walk", "Dinner"]);
+        let b = order(&["Cinema", "Board games", "Long walk", "Dinner"]);
+        // five of six pairs agree
+        assert!((kendall_tau(&a, &b) - 4.0 / 6.0).abs() < 1e-9);
+        assert!((ranking_credit(&[&a[..], &b[..]]) - 5.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_single_item_always_agrees() {
+        let a = order(&["Cinema"]);
+        assert_eq!(kendall_tau(&a, &a), 1.0);
+        assert_eq!(ranking_credit(&[&a[..], &a[..]]), 1.0);
+        // nothing in common ranks nothing
+        assert_eq!(kendall_tau(&a, &order(&["Dinner"])), 0.0);
+    }
+
+    #[test]
+    fn one_player_alone_earns_nothing() {
+        let a = order(&["Board games", "Cinema"]);
+        assert_eq!(ranking_credit(&[&a[..]]), 0.0);
+    }
+}
@@ -0,0 +1,266 @@
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::Rng;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::serde::{Deserialize, Serialize};
+
+// Host-provided secrets kept on a room (co-op goal message, surprises). They are
+// sealed with ChaCha20-Poly1305 under `message_key` (64 hex chars); without
+// one a random key is made at launch, so sealed messages die with the process.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Sealed {
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl Sealed {
+    // bytes held, for footprint.rs
+    pub fn stored_len(&self) -> usize {
+        self.nonce.len() + self.ciphertext.len()
+    }
+}
+
+#[derive(Clone)]
+pub struct MessageKey {
+    cipher: ChaCha20Poly1305,
+}
+
+impl MessageKey {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let key = match figment.extract_inner::<String>("message_key") {
+            Ok(hex) => parse_hex_key(&hex).ok_or("`message_key` must be 64 hex characters")?,
+            Err(_) => rand::thread_rng().gen::<[u8; 32]>(),
+        };
+        Ok(MessageKey {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        })
+    }
+
+    pub fn seal(&self, message: &str) -> Option<Sealed> {
+        self.seal_bytes(message.as_bytes())
+    }
+
+    pub fn seal_bytes(&self, bytes: &[u8]) -> Option<Sealed> {
+        let nonce: [u8; 12] = rand::thread_rng().gen();
+        let ciphertext = self.cipher.encrypt(Nonce::from_slice(&nonce), bytes).ok()?;
+        Some(Sealed {
+            nonce: nonce.to_vec(),
+            ciphertext,
+        })
+    }
+
+    // None if the key changed or the box was tampered with.
+    pub fn open(&self, sealed: &Sealed) -> Option<String> {
+        String::from_utf8(self.open_bytes(sealed)?).ok()
+    }
+
+    pub fn open_bytes(&self, sealed: &Sealed) -> Option<Vec<u8>> {
+        if sealed.nonce.len() != 12 {
+            return None;
+        }
+        self.cipher
+            .decrypt(Nonce::from_slice(&sealed.nonce), sealed.ciphertext.as_slice())
+            .ok()
+    }
+}
+
+// Compares two secrets without stopping at the first byte that differs, so
+// how long a wrong guess takes doesn't tell how much of it was right.
+pub fn same_secret(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut diff = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let (x, y) = (a.get(i).copied().unwrap_or(0), b.get(i).copied().unwrap_or(0));
+        diff |= usize::from(x ^ y);
+    }
+    diff == 0
+}
+
+// --- Cookie keys ---
+// Session cookies (the admin login) are sealed the same way, under
+// `cookie_key`. Rotating it would sign everyone out, so the keys it replaced
+// go in `previous_cookie_keys`: still accepted, and cookies sealed under them
+// are re-issued under the current key on their next use.
+#[derive(Clone)]
+pub struct CookieKeys {
+    current: MessageKey,
+    previous: Vec<MessageKey>,
+}
+
+// What a cookie turned out to be sealed with.
+#[derive(Debug, PartialEq)]
+pub enum Verified {
+    Current(String),
+    // good, but under a retired key; re-seal it
+    Previous(String),
+}
+
+impl CookieKeys {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let key = |hex: &str, name: &str| {
+            parse_hex_key(hex)
+                .map(|k| MessageKey {
+                    cipher: ChaCha20Poly1305::new(Key::from_slice(&k)),
+                })
+                .ok_or(format!("{} must be 64 hex characters", name))
+        };
+        let current = match figment.extract_inner::<String>("cookie_key") {
+            Ok(hex) => key(&hex, "`cookie_key`")?,
+            Err(_) => MessageKey {
+                cipher: ChaCha20Poly1305::new(Key::from_slice(&rand::thread_rng().gen::<[u8; 32]>())),
+            },
+        };
+        let previous = match figment.find_value("previous_cookie_keys") {
+            Ok(_) => figment
+                .extract_inner::<Vec<String>>("previous_cookie_keys")
+                .map_err(|e| format!("invalid `previous_cookie_keys`: {}", e))?
+                .iter()
+                .map(|hex| key(hex, "each of `previous_cookie_keys`"))
+                .collect::<Result<_, _>>()?,
+            Err(_) => Vec::new(),
+        };
+        Ok(CookieKeys { current, previous })
+    }
+
+    // nonce and ciphertext, hex-encoded to stay cookie-safe
+    pub fn seal(&self, value: &str) -> String {
+        match self.current.seal(value) {
+            Some(sealed) => format!("{}.{}", to_hex(&sealed.nonce), to_hex(&sealed.ciphertext)),
+            None => String::new(),
+        }
+    }
+
+    pub fn open(&self, cookie: &str) -> Option<Verified> {
+        let (nonce, ciphertext) = cookie.split_once('.')?;
+        let sealed = Sealed {
+            nonce: from_hex(nonce)?,
+            ciphertext: from_hex(ciphertext)?,
+        };
+        if let Some(value) = self.current.open(&sealed) {
+            return Some(Verified::Current(value));
+        }
+        self.previous.iter().find_map(|k| k.open(&sealed)).map(Verified::Previous)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn parse_hex_key(hex: &str) -> Option<[u8; 32]> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(key)
+}
+
+// A malformed key is reported by the startup checks; this only installs it.
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Message Key", |rocket| async move {
+        match MessageKey::load(rocket.figment()) {
+            Ok(key) => Ok(rocket.manage(key)),
+            Err(_) => Err(rocket),
+        }
+    })
+}
+
+pub fn cookie_fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Cookie Keys", |rocket| async move {
+        match CookieKeys::load(rocket.figment()) {
+            Ok(keys) => Ok(rocket.manage(keys)),
+            Err(_) => Err(rocket),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "0f1e2d3c4b5a69788796a5b4c3d2e1f00f1e2d3c4b5a69788796a5b4c3d2e1f0";
+    const NEW: &str = "a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90";
+    const STRANGER: &str = "5555555555555555555555555555555555555555555555555555555555555555";
+    const VALUE: &str = "ROSE42:player-1:session-1";
+
+    fn keys(current: &str, previous: &[&str]) -> CookieKeys {
+        let figment = Figment::new()
+            .merge(("cookie_key", current))
+            .merge(("previous_cookie_keys", previous));
+        CookieKeys::load(&figment).expect("valid keys")
+    }
+
+    #[test]
+    fn current_key_opens_what_it_sealed() {
+        let keys = keys(NEW, &[]);
+        assert_eq!(keys.open(&keys.seal(VALUE)), Some(Verified::Current(VALUE.to_string())));
+    }
+
+    #[test]
+    fn previous_key_still_opens_and_asks_for_a_reseal() {
+        let sealed = keys(OLD, &[]).seal(VALUE);
+        let rotated = keys(NEW, &[OLD]);
+        assert_eq!(rotated.open(&sealed), Some(Verified::Previous(VALUE.to_string())));
+        // re-sealed, it opens under the current key
+        assert_eq!(rotated.open(&rotated.seal(VALUE)), Some(Verified::Current(VALUE.to_string())));
+    }
+
+    #[test]
+    fn unknown_key_is_rejected() {
+        let sealed = keys(STRANGER, &[]).seal(VALUE);
+        assert_eq!(keys(NEW, &[OLD]).open(&sealed), None);
+    }
+
+    #[test]
+    fn key_dropped_from_previous_is_rejected() {
+        let sealed = keys(OLD, &[]).seal(VALUE);
+        assert_eq!(keys(NEW, &[]).open(&sealed), None);
+    }
+
+    #[test]
+    fn tampered_or_malformed_cookies_are_rejected() {
+        let keys = keys(NEW, &[]);
+        let sealed = keys.seal(VALUE);
+        let last = sealed.chars().last().unwrap();
+        let flipped = format!("{}{}", &sealed[..sealed.len() - 1], if last == '0' { '1' } else { '0' });
+        assert_eq!(keys.open(&flipped), None);
+        assert_eq!(keys.open("not-a-cookie"), None);
+        assert_eq!(keys.open("zz.zz"), None);
+    }
+
+    #[test]
+    fn malformed_keys_fail_to_load() {
+        let figment = Figment::new().merge(("cookie_key", "too-short"));
+        assert!(CookieKeys::load(&figment).is_err());
+        let figment = Figment::new()
+            .merge(("cookie_key", NEW))
+            .merge(("previous_cookie_keys", ["not hex"]));
+        assert!(CookieKeys::load(&figment).is_err());
+    }
+
+    #[test]
+    fn same_secret_needs_every_byte() {
+        assert!(same_secret("open sesame", "open sesame"));
+        assert!(!same_secret("open sesame", "open sesamE"));
+        assert!(!same_secret("open sesame", "open"));
+        assert!(!same_secret("", "x"));
+        assert!(same_secret("", ""));
+    }
+}
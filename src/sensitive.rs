@@ -0,0 +1,82 @@
+use rocket::serde::json::Value;
+use rocket::serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+// Text players wrote about themselves: names, answers, email addresses. It
+// serializes as the plain value, since pages and the room store need it,
+// but prints as "[redacted]" through both {:?} and {}, so a log line or an
+// error message can't carry it by accident. `expose()` when the text itself
+// is what's wanted.
+
+pub const REDACTED: &str = "[redacted]";
+
+#[derive(Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", transparent)]
+pub struct Sensitive<T>(T);
+
+impl<T> Sensitive<T> {
+    pub fn new(value: T) -> Self {
+        Sensitive(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Sensitive(value)
+    }
+}
+
+impl<T> Deref for Sensitive<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+// --- Admin exports ---
+// JSON keys whose values came from players: outbox payloads carry partner
+// names and email addresses, audit details the hosts' reasons.
+const KEYS: &[&str] = &["name", "names", "message", "to", "email", "reason", "answers", "items"];
+
+// Blanks every player-written value in `value`, at any depth.
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if KEYS.contains(&key.as_str()) && !v.is_null() {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+// "host:Kamzy" -> "host:[redacted]"; admin and system actors are kept.
+pub fn redact_actor(actor: &str) -> String {
+    match actor.split_once(':') {
+        Some((kind @ ("host" | "player"), _)) => format!("{}:{}", kind, REDACTED),
+        _ => actor.to_string(),
+    }
+}
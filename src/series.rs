@@ -0,0 +1,70 @@
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::scheduler::now_secs;
+
+// A series links consecutive rooms (rematches) so long-distance couples can
+// build up points across sessions and unlock badges along the way.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Series {
+    pub id: String,
+    // room codes, oldest first
+    pub rooms: Vec<String>,
+    pub created_at: u64,
+}
+
+// (cumulative couple points, badge)
+pub const MILESTONES: &[(u32, &str)] = &[
+    (250, "🌱 Budding Duo"),
+    (500, "💞 In Sync"),
+    (1000, "🔥 Unstoppable"),
+    (2500, "🏆 Legendary Couple"),
+];
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Progress {
+    pub total: u32,
+    pub badges: Vec<&'static str>,
+    pub next_target: Option<u32>,
+    pub next_badge: Option<&'static str>,
+    // towards the next milestone, 0-100
+    pub pct: u32,
+}
+
+impl Series {
+    pub fn new(id: String, first_room: String) -> Self {
+        Series {
+            id,
+            rooms: vec![first_room],
+            created_at: now_secs(),
+        }
+    }
+}
+
+pub fn progress(total: u32) -> Progress {
+    let badges = MILESTONES
+        .iter()
+        .filter(|(target, _)| total >= *target)
+        .map(|(_, badge)| *badge)
+        .collect();
+    let next = MILESTONES.iter().find(|(target, _)| total < *target);
+    let floor = MILESTONES
+        .iter()
+        .rev()
+        .find(|(target, _)| total >= *target)
+        .map(|(target, _)| *target)
+        .unwrap_or(0);
+    let pct = match next {
+        Some((target, _)) => (total - floor) * 100 / (target - floor),
+        None => 100,
+    };
+    Progress {
+        total,
+        badges,
+        next_target: next.map(|(t, _)| *t),
+        next_badge: next.map(|(_, b)| *b),
+        pct,
+    }
+}
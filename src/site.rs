@@ -0,0 +1,121 @@
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::serde::Serialize;
+
+use crate::surprise;
+
+// Search and link-preview metadata, for deployments that want the landing
+// page found. Everything hangs off `public_url`: without it there is no
+// sitemap and pages carry no canonical links. Results are only ever listed
+// when the host opted in at room creation.
+
+const DEFAULT_NAME: &str = "Now";
+const DEFAULT_DESCRIPTION: &str = "A question game for two: play from anywhere with a room code and see how in sync you are.";
+
+// --- Models ---
+#[derive(Clone, Debug, Default)]
+pub struct Site {
+    // e.g. "https://now.example.com", no trailing slash
+    base: Option<String>,
+    name: String,
+    description: String,
+    // og:image / twitter:image, relative to `base` unless absolute
+    image: Option<String>,
+}
+
+// canonical/OG/Twitter tags for one page; see the `meta` template.
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Meta {
+    pub site_name: String,
+    pub title: String,
+    pub description: String,
+    pub canonical: Option<String>,
+    pub image: Option<String>,
+    // false asks crawlers to skip the page
+    pub index: bool,
+}
+
+impl Site {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let base = match figment.extract_inner::<String>("public_url") {
+            Ok(url) => {
+                let url = url.trim().trim_end_matches('/').to_string();
+                if !(url.starts_with("https://") || url.starts_with("http://")) {
+                    return Err(format!("public_url `{}` must start with http:// or https://", url));
+                }
+                Some(url)
+            }
+            Err(_) => None,
+        };
+        Ok(Site {
+            base,
+            name: figment
+                .extract_inner("site_name")
+                .unwrap_or_else(|_| DEFAULT_NAME.to_string()),
+            description: figment
+                .extract_inner("site_description")
+                .unwrap_or_else(|_| DEFAULT_DESCRIPTION.to_string()),
+            image: figment.extract_inner("site_image").ok(),
+        })
+    }
+
+    fn url(&self, path: &str) -> Option<String> {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return Some(path.to_string());
+        }
+        self.base.as_ref().map(|base| format!("{}{}", base, path))
+    }
+
+    // `path` as a full URL; None without `public_url`.
+    pub fn absolute(&self, path: &str) -> Option<String> {
+        self.base.as_ref().map(|base| format!("{}{}", base, path))
+    }
+
+    // Meta for the page at `path`; `description` falls back to the site's.
+    pub fn meta(&self, path: &str, title: &str, description: Option<&str>, index: bool) -> Meta {
+        Meta {
+            site_name: self.name.clone(),
+            title: title.to_string(),
+            description: description.unwrap_or(&self.description).to_string(),
+            canonical: self.url(path).filter(|_| index),
+            image: self.image.as_deref().and_then(|img| self.url(img)),
+            index,
+        }
+    }
+
+    // `pages` are (path, last modified unix secs); None without `public_url`.
+    pub fn sitemap(&self, pages: &[(String, Option<u64>)]) -> Option<String> {
+        self.base.as_ref()?;
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for (path, modified) in pages {
+            let Some(loc) = self.url(path) else { continue };
+            xml.push_str("  <url><loc>");
+            xml.push_str(&escape(&loc));
+            xml.push_str("</loc>");
+            if let Some(at) = modified {
+                xml.push_str(&format!("<lastmod>{}</lastmod>", surprise::format_day(at / 86_400)));
+            }
+            xml.push_str("</url>\n");
+        }
+        xml.push_str("</urlset>\n");
+        Some(xml)
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Site Metadata", |rocket| async move {
+        let site = Site::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(site)
+    })
+}
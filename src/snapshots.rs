@@ -0,0 +1,410 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::export::Transcript;
+use crate::jsonfile;
+use crate::scheduler::now_secs;
+
+// Finished games, frozen when the result page is first shown. Each snapshot
+// gets an unguessable token the couple can keep and later compare against
+// another of their games. Persisted like the outbox: one JSON file.
+
+// --- Models ---
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Snapshot {
+    pub token: String,
+    pub taken_at: u64,
+    pub transcript: Transcript,
+    // came from another deployment's export; its room code means nothing here
+    #[serde(default)]
+    pub imported: bool,
+    // the host let the result be public; only these feed the word cloud
+    #[serde(default)]
+    pub public: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Comparison {
+    pub a: SnapshotSummary,
+    pub b: SnapshotSummary,
+    // b minus a
+    pub score_delta: i32,
+    pub dimensions: Vec<DimensionDelta>,
+    // questions both games asked, with the answers that moved
+    pub changed: Vec<QuestionChange>,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SnapshotSummary {
+    pub token: String,
+    pub code: String,
+    pub score: u32,
+    pub taken_at: u64,
+    pub imported: bool,
+}
+
+// Per-category agreement in each game; a side is None when that game asked
+// nothing from the category.
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DimensionDelta {
+    pub category: String,
+    pub a_pct: Option<u32>,
+    pub b_pct: Option<u32>,
+    pub delta: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct QuestionChange {
+    pub question_id: String,
+    pub question: String,
+    pub answers: Vec<AnswerChange>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AnswerChange {
+    pub player_name: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CompareError {
+    // different players; comparing strangers' games says nothing
+    NotSameCouple,
+}
+
+// --- Comparison ---
+pub fn compare(a: &Snapshot, b: &Snapshot) -> Result<Comparison, CompareError> {
+    let (ta, tb) = (&a.transcript, &b.transcript);
+    if couple(ta) != couple(tb) {
+        return Err(CompareError::NotSameCouple);
+    }
+
+    let mut changed = Vec::new();
+    let mut unchanged = 0;
+    for qa in &ta.questions {
+        let Some(qb) = tb.questions.iter().find(|q| q.question_id == qa.question_id) else {
+            continue;
+        };
+        let answers: Vec<AnswerChange> = qa
+            .answers
+            .iter()
+            .filter_map(|before| {
+                let after = qb.answers.iter().find(|x| x.player_id == before.player_id)?;
+                let same = before.answer.trim().eq_ignore_ascii_case(after.answer.trim());
+                (!same).then(|| AnswerChange {
+                    player_name: after.player_name.clone(),
+                    before: before.answer.clone(),
+                    after: after.answer.clone(),
+                })
+            })
+            .collect();
+        if answers.is_empty() {
+            unchanged += 1;
+        } else {
+            changed.push(QuestionChange {
+                question_id: qb.question_id.clone(),
+                question: qb.question.clone(),
+                answers,
+            });
+        }
+    }
+
+    let (da, db) = (by_category(ta), by_category(tb));
+    let mut categories: Vec<&String> = da.keys().chain(db.keys()).collect();
+    categories.sort();
+    categories.dedup();
+    let dimensions = categories
+        .into_iter()
+        .map(|c| {
+            let (a_pct, b_pct) = (da.get(c).copied(), db.get(c).copied());
+            DimensionDelta {
+                category: c.clone(),
+                a_pct,
+                b_pct,
+                delta: a_pct.zip(b_pct).map(|(x, y)| y as i32 - x as i32),
+            }
+        })
+        .collect();
+
+    Ok(Comparison {
+        a: summary(a),
+        b: summary(b),
+        score_delta: tb.score as i32 - ta.score as i32,
+        dimensions,
+        changed,
+        unchanged,
+    })
+}
+
+// Player ids, order-free; rematches keep them, so they identify the couple.
+pub fn couple(t: &Transcript) -> Vec<&str> {
+    let mut ids: Vec<&str> = t.players.iter().map(|p| p.id.as_str()).collect();
+    ids.sort_unstable();
+    ids
+}
+
+fn summary(s: &Snapshot) -> SnapshotSummary {
+    SnapshotSummary {
+        token: s.token.clone(),
+        code: s.transcript.code.clone(),
+        score: s.transcript.score,
+        taken_at: s.taken_at,
+        imported: s.imported,
+    }
+}
+
+// Average credit per category over the questions both players answered.
+fn by_category(t: &Transcript) -> BTreeMap<String, u32> {
+    let mut sums: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    for q in &t.questions {
+        if let Some(pct) = q.credit_pct {
+            let e = sums.entry(q.category.clone()).or_default();
+            e.0 += pct;
+            e.1 += 1;
+        }
+    }
+    sums.into_iter().map(|(c, (sum, n))| (c, sum / n)).collect()
+}
+
+// --- Import ---
+#[derive(Default)]
+pub struct ImportOutcome {
+    // (source room code, new snapshot token)
+    pub imported: Vec<(String, String)>,
+    // (source room code, why it was skipped)
+    pub failed: Vec<(String, &'static str)>,
+}
+
+// Every answer belongs to a listed player, and scores are percentages.
+fn is_well_formed(t: &Transcript) -> bool {
+    let known = |id: &str| t.players.iter().any(|p| p.id == id);
+    !t.players.is_empty()
+        && t.score <= 100
+        && t.questions.iter().all(|q| q.credit_pct.is_none_or(|c| c <= 100))
+        && t.questions.iter().flat_map(|q| &q.answers).all(|a| known(&a.player_id))
+}
+
+// Source player id -> the target couple's id: same name first, leftovers
+// paired up by seat order.
+fn seat_map(from: &Transcript, to: &Transcript) -> Option<HashMap<String, String>> {
+    if from.players.len() != to.players.len() {
+        return None;
+    }
+    let mut ids = HashMap::new();
+    let mut free: Vec<&str> = to.players.iter().map(|p| p.id.as_str()).collect();
+    for p in &from.players {
+        if let Some(m) = to.players.iter().find(|m| m.name.eq_ignore_ascii_case(&p.name) && free.contains(&m.id.as_str())) {
+            free.retain(|id| *id != m.id);
+            ids.insert(p.id.clone(), m.id.clone());
+        }
+    }
+    for p in &from.players {
+        if !ids.contains_key(&p.id) {
+            ids.insert(p.id.clone(), free.remove(0).to_string());
+        }
+    }
+    Some(ids)
+}
+
+// --- Store ---
+#[derive(Default)]
+struct StoreInner {
+    path: Option<PathBuf>,
+    snapshots: HashMap<String, Snapshot>,
+    // bumped on every save, so derived reports know when to recompute
+    generation: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct SnapshotStore {
+    inner: Arc<RwLock<StoreInner>>,
+}
+
+impl SnapshotStore {
+    // Freezes a finished game; returns its token.
+    pub fn save(&self, transcript: Transcript, public: bool) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        let mut inner = self.inner.write();
+        inner.snapshots.insert(
+            token.clone(),
+            Snapshot {
+                token: token.clone(),
+                taken_at: transcript.finished_at.unwrap_or_else(now_secs),
+                transcript,
+                imported: false,
+                public,
+            },
+        );
+        inner.generation += 1;
+        persist(&inner);
+        token
+    }
+
+    // Merges exports from another deployment. Player ids are remapped onto
+    // `into`'s couple when given (matched by name, then by seat), otherwise
+    // onto fresh ids shared across the batch so the games stay one history.
+    pub fn import(&self, transcripts: Vec<Transcript>, into: Option<&Snapshot>) -> ImportOutcome {
+        let mut outcome = ImportOutcome::default();
+        let mut fresh: HashMap<String, String> = HashMap::new();
+        let mut inner = self.inner.write();
+        for mut t in transcripts {
+            let source = t.code.clone();
+            if !is_well_formed(&t) {
+                outcome.failed.push((source, "invalid"));
+                continue;
+            }
+            let ids: HashMap<String, String> = match into {
+                Some(target) => match seat_map(&t, &target.transcript) {
+                    Some(ids) => ids,
+                    None => {
+                        outcome.failed.push((source, "players_mismatch"));
+                        continue;
+                    }
+                },
+                None => t
+                    .players
+                    .iter()
+                    .map(|p| {
+                        let id = fresh.entry(p.id.clone()).or_insert_with(|| Uuid::new_v4().to_string());
+                        (p.id.clone(), id.clone())
+                    })
+                    .collect(),
+            };
+            for p in &mut t.players {
+                p.id = ids[&p.id].clone();
+            }
+            for a in t.questions.iter_mut().flat_map(|q| q.answers.iter_mut()) {
+                a.player_id = ids[&a.player_id].clone();
+            }
+            // the same game already here, imported earlier or played locally
+            let duplicate = inner.snapshots.values().any(|s| {
+                s.transcript.code == t.code
+                    && s.transcript.finished_at == t.finished_at
+                    && couple(&s.transcript) == couple(&t)
+            });
+            if duplicate {
+                outcome.failed.push((source, "duplicate"));
+                continue;
+            }
+            let token = Uuid::new_v4().simple().to_string();
+            inner.snapshots.insert(
+                token.clone(),
+                Snapshot {
+                    token: token.clone(),
+                    taken_at: t.finished_at.unwrap_or_else(now_secs),
+                    transcript: t,
+                    imported: true,
+                    public: false,
+                },
+            );
+            outcome.imported.push((source, token));
+        }
+        if !outcome.imported.is_empty() {
+            inner.generation += 1;
+            persist(&inner);
+        }
+        outcome
+    }
+
+    // Games whose hosts made the result public, played here.
+    pub fn public(&self) -> Vec<Snapshot> {
+        self.inner
+            .read()
+            .snapshots
+            .values()
+            .filter(|s| s.public && !s.imported)
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, token: &str) -> Option<Snapshot> {
+        self.inner.read().snapshots.get(token.trim()).cloned()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.inner.read().generation
+    }
+
+    // Drops snapshots taken before `cutoff` (unix secs); returns their
+    // tokens, or the ones that would go on a dry run.
+    pub fn purge(&self, cutoff: u64, dry_run: bool) -> Vec<String> {
+        let expired = |inner: &StoreInner| -> Vec<String> {
+            inner
+                .snapshots
+                .values()
+                .filter(|s| s.taken_at < cutoff)
+                .map(|s| s.token.clone())
+                .collect()
+        };
+        if dry_run {
+            return expired(&self.inner.read());
+        }
+        let mut inner = self.inner.write();
+        let tokens = expired(&inner);
+        if !tokens.is_empty() {
+            for token in &tokens {
+                inner.snapshots.remove(token);
+            }
+            inner.generation += 1;
+            persist(&inner);
+        }
+        tokens
+    }
+
+    // Every game this couple has on record, oldest first.
+    pub fn for_couple(&self, players: &[&str]) -> Vec<Snapshot> {
+        let mut wanted = players.to_vec();
+        wanted.sort_unstable();
+        let inner = self.inner.read();
+        let mut found: Vec<Snapshot> = inner
+            .snapshots
+            .values()
+            .filter(|s| couple(&s.transcript) == wanted)
+            .cloned()
+            .collect();
+        found.sort_by_key(|s| s.taken_at);
+        found
+    }
+
+    // Reads `snapshot_path` and reloads what was saved there.
+    pub fn fairing(&self) -> AdHoc {
+        let store = self.clone();
+        AdHoc::on_ignite("Result Snapshots", |rocket| async move {
+            let path: Option<PathBuf> = rocket.figment().extract_inner("snapshot_path").ok();
+            let mut inner = store.inner.write();
+            if let Some(path) = &path {
+                match jsonfile::read(path) {
+                    Ok(Some(snapshots)) => {
+                        inner.snapshots = snapshots;
+                        inner.generation += 1;
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("snapshots: ignoring {}", e),
+                }
+            }
+            inner.path = path;
+            drop(inner);
+            rocket
+        })
+    }
+}
+
+fn persist(inner: &StoreInner) {
+    let Some(path) = &inner.path else { return };
+    if let Err(e) = jsonfile::write(path, &inner.snapshots) {
+        tracing::error!("snapshots: {}", e);
+    }
+}
@@ -0,0 +1,301 @@
+use parking_lot::{Mutex, RwLock};
+use rocket::figment::Figment;
+use rocket::serde::json::{self, Value};
+use rocket::{Build, Rocket};
+use rocket_db_pools::{sqlx, Database};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::jsonfile;
+
+// Where open rooms are kept between restarts. The default keeps them in
+// memory only, as before; `room_storage = "file"` writes them to
+// `room_store_path`, and `room_storage = "sqlite"` to the `rooms` database,
+// so a deploy doesn't end every game in progress. Rooms reach the backend
+// as JSON documents, one per room with its players and answers, so a
+// backend never needs to know the room model.
+
+// --- Backends ---
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    // for the startup log
+    fn describe(&self) -> String;
+    // every saved room; empty when nothing was saved yet
+    async fn load(&self) -> Result<Vec<Value>, String>;
+    // replaces what was saved with `rooms`
+    async fn save(&self, rooms: &[Value]) -> Result<(), String>;
+    // whether a save would get through, for /readyz; cheap, no load
+    async fn ping(&self) -> Result<(), String> {
+        Ok(())
+    }
+    // false when nothing outlives the process
+    fn is_persistent(&self) -> bool {
+        true
+    }
+}
+
+pub struct MemoryStorage;
+
+#[rocket::async_trait]
+impl Storage for MemoryStorage {
+    fn describe(&self) -> String {
+        "memory".to_string()
+    }
+
+    async fn load(&self) -> Result<Vec<Value>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn save(&self, _rooms: &[Value]) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+}
+
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+#[rocket::async_trait]
+impl Storage for FileStorage {
+    fn describe(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    async fn load(&self) -> Result<Vec<Value>, String> {
+        Ok(jsonfile::read(&self.path)?.unwrap_or_default())
+    }
+
+    async fn save(&self, rooms: &[Value]) -> Result<(), String> {
+        jsonfile::write(&self.path, rooms)
+    }
+
+    // The directory must be there and writable; the file needn't exist yet.
+    async fn ping(&self) -> Result<(), String> {
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => std::path::Path::new("."),
+        };
+        let meta = std::fs::metadata(dir).map_err(|e| format!("can't reach {}: {}", dir.display(), e))?;
+        if meta.permissions().readonly() {
+            return Err(format!("{} is read-only", dir.display()));
+        }
+        Ok(())
+    }
+}
+
+// The pool rocket_db_pools keeps for `[default.databases.rooms]`, e.g.
+// `url = "sqlite://data/rooms.db"`. Only attached for `room_storage = "sqlite"`.
+#[derive(Database)]
+#[database("rooms")]
+pub struct RoomsDb(sqlx::SqlitePool);
+
+const SQLITE_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS rooms (code TEXT PRIMARY KEY NOT NULL, room TEXT NOT NULL)";
+
+// One row per room, its document as JSON text. A save rewrites the table in
+// one transaction, so a crash mid-save leaves the previous rooms in place.
+pub struct SqliteStorage {
+    pool: sqlx::SqlitePool,
+}
+
+impl SqliteStorage {
+    async fn open(pool: sqlx::SqlitePool) -> Result<Self, String> {
+        sqlx::query(SQLITE_SCHEMA)
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("can't create the rooms table: {}", e))?;
+        Ok(SqliteStorage { pool })
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for SqliteStorage {
+    fn describe(&self) -> String {
+        "sqlite database `rooms`".to_string()
+    }
+
+    async fn load(&self) -> Result<Vec<Value>, String> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT code, room FROM rooms ORDER BY code")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("can't read rooms: {}", e))?;
+        rows.into_iter()
+            .map(|(code, raw)| json::from_str(&raw).map_err(|e| format!("room {} is malformed: {}", code, e)))
+            .collect()
+    }
+
+    async fn save(&self, rooms: &[Value]) -> Result<(), String> {
+        let failed = |e: sqlx::Error| format!("can't write rooms: {}", e);
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+        sqlx::query("DELETE FROM rooms").execute(&mut *tx).await.map_err(failed)?;
+        for room in rooms {
+            sqlx::query("INSERT INTO rooms (code, room) VALUES (?, ?)")
+                .bind(room["code"].as_str().unwrap_or_default())
+                .bind(room.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(failed)?;
+        }
+        tx.commit().await.map_err(failed)
+    }
+
+    async fn ping(&self) -> Result<(), String> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("can't reach the rooms database: {}", e))
+    }
+}
+
+// --- Config ---
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum StorageKind {
+    #[default]
+    Memory,
+    File(PathBuf),
+    Sqlite,
+}
+
+const DEFAULT_ROOM_STORE_PATH: &str = "data/rooms.json";
+
+impl StorageKind {
+    // `room_storage = "memory"` (default), `"file"` with an optional
+    // `room_store_path`, or `"sqlite"` with `[default.databases.rooms]`.
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        if figment.find_value("room_storage").is_err() {
+            return Ok(StorageKind::default());
+        }
+        let choice: String = figment
+            .extract_inner("room_storage")
+            .map_err(|e| format!("invalid `room_storage` config: {}", e))?;
+        match choice.as_str() {
+            "memory" => Ok(StorageKind::Memory),
+            "file" => {
+                let path = figment
+                    .extract_inner::<PathBuf>("room_store_path")
+                    .unwrap_or_else(|_| PathBuf::from(DEFAULT_ROOM_STORE_PATH));
+                Ok(StorageKind::File(path))
+            }
+            "sqlite" => Ok(StorageKind::Sqlite),
+            other => Err(format!("room_storage `{}` must be \"memory\", \"file\" or \"sqlite\"", other)),
+        }
+    }
+
+    // Sqlite needs RoomsDb's fairing to have run on `rocket` already.
+    pub async fn open(&self, rocket: &Rocket<Build>) -> Result<Arc<dyn Storage>, String> {
+        match self {
+            StorageKind::Memory => Ok(Arc::new(MemoryStorage)),
+            StorageKind::File(path) => Ok(Arc::new(FileStorage { path: path.clone() })),
+            StorageKind::Sqlite => {
+                let db = RoomsDb::fetch(rocket).ok_or("the `rooms` database isn't set up")?;
+                Ok(Arc::new(SqliteStorage::open(db.0.clone()).await?))
+            }
+        }
+    }
+}
+
+// --- Shutdown snapshot ---
+// With memory storage, open rooms still ride out a graceful restart: the
+// way down writes them to `shutdown_snapshot_path` and the next launch
+// reads them back and deletes the file, so a snapshot is never replayed
+// after a crash that skipped the next one. `shutdown_snapshot = false`
+// turns it off; file storage saves on the way down already.
+const DEFAULT_SHUTDOWN_SNAPSHOT_PATH: &str = "data/shutdown-rooms.json";
+
+pub struct ShutdownSnapshot {
+    path: PathBuf,
+}
+
+impl ShutdownSnapshot {
+    pub fn load(figment: &Figment, kind: &StorageKind) -> Option<Self> {
+        let enabled = figment.extract_inner::<bool>("shutdown_snapshot").unwrap_or(true);
+        if !enabled || *kind != StorageKind::Memory {
+            return None;
+        }
+        let path = figment
+            .extract_inner::<PathBuf>("shutdown_snapshot_path")
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_SHUTDOWN_SNAPSHOT_PATH));
+        Some(ShutdownSnapshot { path })
+    }
+
+    pub fn describe(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    // What the last shutdown left, removing it.
+    pub fn take(&self) -> Result<Vec<Value>, String> {
+        let rooms = jsonfile::read(&self.path)?.unwrap_or_default();
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => Ok(rooms),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(rooms),
+            Err(e) => Err(format!("can't remove {}: {}", self.path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, rooms: &[Value]) -> Result<(), String> {
+        if rooms.is_empty() {
+            return Ok(());
+        }
+        jsonfile::write(&self.path, rooms)
+    }
+}
+
+// --- Handle ---
+// The backend in use, swapped in at ignite. Saving skips the write when
+// nothing changed since the last one, so the flush job can run often.
+#[derive(Clone)]
+pub struct RoomStorage {
+    backend: Arc<RwLock<Arc<dyn Storage>>>,
+    last_saved: Arc<Mutex<Option<Vec<Value>>>>,
+}
+
+impl Default for RoomStorage {
+    fn default() -> Self {
+        RoomStorage {
+            backend: Arc::new(RwLock::new(Arc::new(MemoryStorage))),
+            last_saved: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl RoomStorage {
+    pub fn set_backend(&self, backend: Arc<dyn Storage>) {
+        *self.backend.write() = backend;
+        *self.last_saved.lock() = None;
+    }
+
+    pub fn describe(&self) -> String {
+        self.backend.read().describe()
+    }
+
+    pub async fn ping(&self) -> Result<(), String> {
+        let backend = self.backend.read().clone();
+        backend.ping().await
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.backend.read().is_persistent()
+    }
+
+    pub async fn load(&self) -> Result<Vec<Value>, String> {
+        let backend = self.backend.read().clone();
+        let rooms = backend.load().await?;
+        *self.last_saved.lock() = Some(rooms.clone());
+        Ok(rooms)
+    }
+
+    // The locks aren't held over the write, which may wait on the database.
+    pub async fn save(&self, rooms: Vec<Value>) -> Result<(), String> {
+        if self.last_saved.lock().as_ref() == Some(&rooms) {
+            return Ok(());
+        }
+        let backend = self.backend.read().clone();
+        backend.save(&rooms).await?;
+        *self.last_saved.lock() = Some(rooms);
+        Ok(())
+    }
+}
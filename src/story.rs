@@ -0,0 +1,168 @@
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+// Story mode: a scripted "date story" as a DAG of scenes loaded from
+// `story_path`. Both players pick a choice at every scene; agreeing takes that
+// branch, disagreeing takes the scene's `split` branch (or the first pick).
+
+// --- Models ---
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Story {
+    pub title: String,
+    pub start: String,
+    pub scenes: HashMap<String, Scene>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Scene {
+    pub text: String,
+    // no choices = an ending
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+    // where to go when the two of you pick differently
+    #[serde(default)]
+    pub split: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Choice {
+    pub label: String,
+    pub next: String,
+}
+
+// Where a room is in the story, and how it got there.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct StoryProgress {
+    pub scene: String,
+    // player id -> choice index for the current scene
+    pub picks: HashMap<String, usize>,
+    pub path: Vec<StoryStep>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct StoryStep {
+    pub scene: String,
+    pub picks: Vec<String>,
+    pub agreed: bool,
+    pub next: String,
+}
+
+const DEFAULT_STORY_PATH: &str = "stories/date-night.json";
+
+impl Story {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let path: PathBuf = figment
+            .extract_inner("story_path")
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_STORY_PATH));
+        let raw = std::fs::read_to_string(&path)
+            .map_err(|e| format!("story file `{}` unreadable: {}", path.display(), e))?;
+        let story: Story = rocket::serde::json::from_str(&raw)
+            .map_err(|e| format!("story file `{}` is invalid: {}", path.display(), e))?;
+        story.validate()?;
+        Ok(story)
+    }
+
+    // Every branch must lead somewhere real, and no scene may loop back.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.scenes.contains_key(&self.start) {
+            return Err(format!("story start scene `{}` does not exist", self.start));
+        }
+        for (id, scene) in &self.scenes {
+            let targets = scene.choices.iter().map(|c| &c.next).chain(scene.split.iter());
+            for next in targets {
+                if !self.scenes.contains_key(next) {
+                    return Err(format!("story scene `{}` points at missing scene `{}`", id, next));
+                }
+            }
+        }
+        let mut done = HashSet::new();
+        for id in self.scenes.keys() {
+            self.check_acyclic(id, &mut Vec::new(), &mut done)?;
+        }
+        Ok(())
+    }
+
+    fn check_acyclic<'a>(
+        &'a self,
+        id: &'a str,
+        trail: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> Result<(), String> {
+        if done.contains(id) {
+            return Ok(());
+        }
+        if trail.contains(&id) {
+            return Err(format!("story loops back to scene `{}`", id));
+        }
+        trail.push(id);
+        let scene = &self.scenes[id];
+        for next in scene.choices.iter().map(|c| c.next.as_str()).chain(scene.split.as_deref()) {
+            self.check_acyclic(next, trail, done)?;
+        }
+        trail.pop();
+        done.insert(id);
+        Ok(())
+    }
+
+    pub fn scene(&self, id: &str) -> Option<&Scene> {
+        self.scenes.get(id)
+    }
+}
+
+impl StoryProgress {
+    pub fn start(story: &Story) -> Self {
+        StoryProgress {
+            scene: story.start.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_over(&self, story: &Story) -> bool {
+        story.scene(&self.scene).map(|s| s.choices.is_empty()).unwrap_or(true)
+    }
+
+    // Records a pick; once everyone has picked, moves to the next scene.
+    // Returns false for out-of-range or repeated picks.
+    pub fn pick(&mut self, story: &Story, player: &str, choice: usize, players: &[&str]) -> bool {
+        let Some(scene) = story.scene(&self.scene) else { return false };
+        if choice >= scene.choices.len() || self.picks.contains_key(player) {
+            return false;
+        }
+        self.picks.insert(player.to_string(), choice);
+        if players.iter().any(|p| !self.picks.contains_key(*p)) {
+            return true;
+        }
+
+        let chosen: Vec<usize> = players.iter().map(|p| self.picks[*p]).collect();
+        let agreed = chosen.iter().all(|c| *c == chosen[0]);
+        let next = match (&scene.split, agreed) {
+            (Some(split), false) => split.clone(),
+            _ => scene.choices[chosen[0]].next.clone(),
+        };
+        self.path.push(StoryStep {
+            scene: self.scene.clone(),
+            picks: chosen.iter().map(|c| scene.choices[*c].label.clone()).collect(),
+            agreed,
+            next: next.clone(),
+        });
+        self.scene = next;
+        self.picks.clear();
+        true
+    }
+}
+
+// A broken story file is reported by the startup checks; this only installs it.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Story", |rocket| async move {
+        let story = Story::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(story)
+    })
+}
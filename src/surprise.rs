@@ -0,0 +1,118 @@
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::sealed::{MessageKey, Sealed};
+
+// A host's hidden surprise (a note and/or a photo) for the result page.
+// Both parts are sealed at creation and only opened once the game is over
+// and every condition the host set is met.
+
+pub const MAX_PHOTO_BYTES: usize = 2 * 1024 * 1024;
+const MAX_TEXT_CHARS: usize = 1000;
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Surprise {
+    text: Option<Sealed>,
+    photo: Option<Sealed>,
+    photo_type: Option<String>,
+    // unlock conditions on top of the game being finished
+    pub min_score: Option<u32>,
+    // days since the unix epoch (UTC)
+    pub unlock_day: Option<u64>,
+    pub revealed: bool,
+}
+
+pub struct Opened {
+    pub text: Option<String>,
+    pub has_photo: bool,
+}
+
+impl Surprise {
+    // None when the host left both parts empty.
+    pub fn seal(
+        key: &MessageKey,
+        text: Option<&str>,
+        photo: Option<(Vec<u8>, String)>,
+        min_score: Option<u32>,
+        unlock_day: Option<u64>,
+    ) -> Option<Surprise> {
+        let text = text
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| t.chars().take(MAX_TEXT_CHARS).collect::<String>());
+        let photo = photo.filter(|(bytes, _)| !bytes.is_empty() && bytes.len() <= MAX_PHOTO_BYTES);
+        if text.is_none() && photo.is_none() {
+            return None;
+        }
+        Some(Surprise {
+            text: text.and_then(|t| key.seal(&t)),
+            photo_type: photo.as_ref().map(|(_, ty)| ty.clone()),
+            photo: photo.and_then(|(bytes, _)| key.seal_bytes(&bytes)),
+            min_score: min_score.filter(|s| *s <= 100),
+            unlock_day,
+            revealed: false,
+        })
+    }
+
+    pub fn is_unlocked(&self, finished: bool, score: u32, now: u64) -> bool {
+        finished
+            && self.min_score.is_none_or(|min| score >= min)
+            && self.unlock_day.is_none_or(|day| now / DAY_SECS >= day)
+    }
+
+    pub fn open(&self, key: &MessageKey) -> Opened {
+        Opened {
+            text: self.text.as_ref().and_then(|t| key.open(t)),
+            has_photo: self.photo.is_some(),
+        }
+    }
+
+    // Sealed text and photo, in bytes.
+    pub fn stored_len(&self) -> usize {
+        [&self.text, &self.photo].into_iter().flatten().map(Sealed::stored_len).sum()
+    }
+
+    pub fn open_photo(&self, key: &MessageKey) -> Option<(Vec<u8>, String)> {
+        let bytes = key.open_bytes(self.photo.as_ref()?)?;
+        Some((bytes, self.photo_type.clone()?))
+    }
+}
+
+// "YYYY-MM-DD" (as sent by <input type="date">) -> days since the epoch.
+pub fn parse_day(date: &str) -> Option<u64> {
+    let mut parts = date.trim().splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    // days-from-civil (Howard Hinnant)
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    u64::try_from(era * 146_097 + doe - 719_468).ok()
+}
+
+// Days since the epoch -> "YYYY-MM-DD".
+pub fn format_day(days: u64) -> String {
+    let (y, m, d) = civil_from_days(days as i64);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+// Days since the epoch -> (year, month, day); civil-from-days (Howard Hinnant).
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
@@ -0,0 +1,101 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::figment::Figment;
+use rocket::{Data, Request, Response};
+use std::time::Instant;
+use tracing::level_filters::LevelFilter;
+
+// Structured logs through `tracing`: one line per request (method, path,
+// room code when the route has one, status, latency) from `RequestLog`, and
+// the game's own milestones under the `game` target (see Room::new and
+// Room::log_from). `log_format = "json"` writes one JSON object per line for
+// log shippers; the default, "pretty", is for reading in a terminal.
+// `trace_level` picks how much: error, warn, info (default), debug, trace.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Pretty,
+    Json,
+}
+
+pub struct Settings {
+    format: Format,
+    level: LevelFilter,
+}
+
+impl Settings {
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let format = match figment.extract_inner::<String>("log_format").ok().as_deref() {
+            None | Some("pretty") => Format::Pretty,
+            Some("json") => Format::Json,
+            Some(other) => return Err(format!("log_format `{}` must be \"pretty\" or \"json\"", other)),
+        };
+        let level = match figment.extract_inner::<String>("trace_level") {
+            Ok(level) => level
+                .parse()
+                .map_err(|_| format!("trace_level `{}` must be error, warn, info, debug or trace", level))?,
+            Err(_) => LevelFilter::INFO,
+        };
+        Ok(Settings { format, level })
+    }
+}
+
+// Installs the subscriber. A bad setting is reported by the startup checks;
+// this falls back to the defaults.
+pub fn init(figment: &Figment) {
+    let settings = Settings::load(figment).unwrap_or(Settings {
+        format: Format::Pretty,
+        level: LevelFilter::INFO,
+    });
+    let builder = tracing_subscriber::fmt().with_max_level(settings.level);
+    let installed = match settings.format {
+        Format::Pretty => builder.try_init(),
+        Format::Json => builder.json().flatten_event(true).try_init(),
+    };
+    if let Err(e) = installed {
+        eprintln!("telemetry: logging not set up: {}", e);
+    }
+}
+
+// --- Request log ---
+// When the request came in; on the request for the response side.
+#[derive(Clone, Copy)]
+struct Started(Instant);
+
+pub struct RequestLog;
+
+#[rocket::async_trait]
+impl Fairing for RequestLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request Log",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        req.local_cache(|| Some(Started(Instant::now())));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let latency_ms = req
+            .local_cache(|| None::<Started>)
+            .map(|started| started.0.elapsed().as_secs_f64() * 1000.0)
+            .unwrap_or_default();
+        let status = res.status().code;
+        let code = room_code(req);
+        let (method, path) = (req.method().as_str(), req.uri().path().as_str());
+        if status >= 500 {
+            tracing::error!(target: "request", method, path, room = code, status, latency_ms, "request failed");
+        } else {
+            tracing::info!(target: "request", method, path, room = code, status, latency_ms, "request");
+        }
+    }
+}
+
+// The `<code>` segment of the matched route, if it has one.
+fn room_code<'r>(req: &'r Request<'_>) -> Option<&'r str> {
+    let route = req.route()?;
+    let at = route.uri.path().segments().position(|s| s == "<code>")?;
+    req.routed_segment(at)
+}
@@ -0,0 +1,111 @@
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::Deserialize;
+use std::collections::HashMap;
+
+// Optional multi-tenancy for hosting several couples' deployments on one
+// server. Each tenant is reached on its own host names and gets its own
+// admin token. Questions and rooms remember their tenant; questions without
+// one are the shared pack every tenant draws from. No `tenants` config =
+// single-tenant, and everything behaves as before.
+
+// --- Models ---
+#[derive(Clone, Debug, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TenantConfig {
+    pub hosts: Vec<String>,
+    // lets this tenant's admin edit only their own questions and rooms
+    #[serde(default)]
+    pub admin_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Tenants {
+    by_name: HashMap<String, TenantConfig>,
+}
+
+impl Tenants {
+    pub fn new(by_name: HashMap<String, TenantConfig>) -> Result<Self, String> {
+        let mut hosts: Vec<String> = Vec::new();
+        let mut tokens: Vec<&str> = Vec::new();
+        for (name, tenant) in &by_name {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+                return Err(format!("tenant name `{}` must be lowercase letters, digits and dashes", name));
+            }
+            if tenant.hosts.is_empty() {
+                return Err(format!("tenant `{}` has no hosts", name));
+            }
+            for host in &tenant.hosts {
+                let host = host.trim().to_ascii_lowercase();
+                if hosts.contains(&host) {
+                    return Err(format!("host `{}` is claimed by more than one tenant", host));
+                }
+                hosts.push(host);
+            }
+            if let Some(token) = tenant.admin_token.as_deref() {
+                if token.is_empty() || tokens.contains(&token) {
+                    return Err(format!("tenant `{}` needs its own non-empty admin_token", name));
+                }
+                tokens.push(token);
+            }
+        }
+        Ok(Tenants { by_name })
+    }
+
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        if figment.find_value("tenants").is_err() {
+            return Ok(Tenants::default());
+        }
+        let by_name = figment
+            .extract_inner("tenants")
+            .map_err(|e| format!("invalid `tenants` config: {}", e))?;
+        Tenants::new(by_name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    pub fn has_admins(&self) -> bool {
+        self.by_name.values().any(|t| t.admin_token.is_some())
+    }
+
+    pub fn for_host(&self, host: &str) -> Option<&str> {
+        self.by_name
+            .iter()
+            .find(|(_, t)| t.hosts.iter().any(|h| h.trim().eq_ignore_ascii_case(host)))
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn for_admin_token(&self, token: &str) -> Option<&str> {
+        self.by_name
+            .iter()
+            .find(|(_, t)| t.admin_token.as_deref() == Some(token))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Tenants", |rocket| async move {
+        let tenants = Tenants::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(tenants)
+    })
+}
+
+// --- Guard ---
+// The tenant this request's Host belongs to; None on the shared host.
+pub struct Tenant(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Tenant {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let tenant = req.rocket().state::<Tenants>().and_then(|tenants| {
+            let host = req.host()?;
+            tenants.for_host(host.domain().as_str()).map(str::to_string)
+        });
+        request::Outcome::Success(Tenant(tenant))
+    }
+}
@@ -0,0 +1,79 @@
+use rocket::serde::{Deserialize, Serialize};
+
+// Per-question countdown. Times are unix seconds; a paused timer keeps the
+// remaining seconds instead of a deadline.
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct QuestionTimer {
+    // None = untimed room
+    pub limit_secs: Option<u64>,
+    pub deadline: Option<u64>,
+    pub paused_remaining: Option<u64>,
+}
+
+impl QuestionTimer {
+    pub fn new(limit_secs: Option<u64>) -> Self {
+        QuestionTimer {
+            limit_secs: limit_secs.filter(|s| *s > 0),
+            deadline: None,
+            paused_remaining: None,
+        }
+    }
+
+    // (Re)starts the countdown for a fresh question.
+    pub fn start(&mut self, now: u64) {
+        self.paused_remaining = None;
+        self.deadline = self.limit_secs.map(|l| now + l);
+    }
+
+    pub fn stop(&mut self) {
+        self.deadline = None;
+        self.paused_remaining = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_remaining.is_some()
+    }
+
+    pub fn pause(&mut self, now: u64) -> bool {
+        match self.deadline.take() {
+            Some(deadline) => {
+                self.paused_remaining = Some(deadline.saturating_sub(now));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn resume(&mut self, now: u64) -> bool {
+        match self.paused_remaining.take() {
+            Some(left) => {
+                self.deadline = Some(now + left);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Adds `secs` to a running or paused countdown; false if there is none.
+    pub fn extend(&mut self, secs: u64) -> bool {
+        if let Some(deadline) = self.deadline.as_mut() {
+            *deadline += secs;
+        } else if let Some(left) = self.paused_remaining.as_mut() {
+            *left += secs;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    pub fn remaining(&self, now: u64) -> Option<u64> {
+        self.paused_remaining
+            .or_else(|| self.deadline.map(|d| d.saturating_sub(now)))
+    }
+}
@@ -0,0 +1,159 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Translating a partner's free-text answer into the viewer's language at
+// reveal time. Providers are pluggable (`translator` in config); the built-in
+// one is an offline word list. Results are cached per answer and locale.
+
+#[rocket::async_trait]
+pub trait Translator: Send + Sync {
+    // None = nothing worth showing (unknown words, same text, provider down)
+    async fn translate(&self, text: &str, from: &str, to: &str) -> Option<String>;
+}
+
+#[derive(Clone, Default)]
+pub struct Translations {
+    provider: Arc<RwLock<Option<Arc<dyn Translator>>>>,
+    // "<room>:<question>:<player>:<locale>" -> translation
+    cache: Arc<RwLock<HashMap<String, Option<String>>>>,
+}
+
+impl Translations {
+    pub fn set_provider(&self, provider: impl Translator + 'static) {
+        *self.provider.write() = Some(Arc::new(provider));
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.provider.read().is_some()
+    }
+
+    pub async fn translate_answer(&self, key: String, text: &str, from: &str, to: &str) -> Option<String> {
+        if from == to {
+            return None;
+        }
+        if let Some(hit) = self.cache.read().get(&key) {
+            return hit.clone();
+        }
+        let provider = self.provider.read().clone()?;
+        let result = provider.translate(text, from, to).await;
+        self.cache.write().insert(key, result.clone());
+        result
+    }
+
+    // `translator = "dictionary"` (default) or `"off"`.
+    pub fn fairing(&self) -> AdHoc {
+        let translations = self.clone();
+        AdHoc::on_ignite("Answer Translation", |rocket| async move {
+            let choice: String = rocket
+                .figment()
+                .extract_inner("translator")
+                .unwrap_or_else(|_| "dictionary".to_string());
+            match choice.as_str() {
+                "off" => {}
+                "dictionary" => translations.set_provider(DictionaryTranslator),
+                other => tracing::warn!("translation: unknown translator `{}`, answers stay as written", other),
+            }
+            rocket
+        })
+    }
+}
+
+// --- Offline fallback ---
+// Word-for-word through a small shared vocabulary. Crude, but it gets
+// "playa" across to someone who only reads English.
+pub struct DictionaryTranslator;
+
+// (en, fr, es)
+const WORDS: &[(&str, &str, &str)] = &[
+    ("beach", "plage", "playa"),
+    ("mountains", "montagnes", "montañas"),
+    ("city", "ville", "ciudad"),
+    ("cabin", "chalet", "cabaña"),
+    ("cat", "chat", "gato"),
+    ("cats", "chats", "gatos"),
+    ("dog", "chien", "perro"),
+    ("dogs", "chiens", "perros"),
+    ("pizza", "pizza", "pizza"),
+    ("chocolate", "chocolat", "chocolate"),
+    ("coffee", "café", "café"),
+    ("tea", "thé", "té"),
+    ("wine", "vin", "vino"),
+    ("cheese", "fromage", "queso"),
+    ("bread", "pain", "pan"),
+    ("rice", "riz", "arroz"),
+    ("chicken", "poulet", "pollo"),
+    ("fish", "poisson", "pescado"),
+    ("red", "rouge", "rojo"),
+    ("blue", "bleu", "azul"),
+    ("green", "vert", "verde"),
+    ("yellow", "jaune", "amarillo"),
+    ("black", "noir", "negro"),
+    ("white", "blanc", "blanco"),
+    ("pink", "rose", "rosa"),
+    ("purple", "violet", "morado"),
+    ("summer", "été", "verano"),
+    ("winter", "hiver", "invierno"),
+    ("spring", "printemps", "primavera"),
+    ("autumn", "automne", "otoño"),
+    ("love", "amour", "amor"),
+    ("music", "musique", "música"),
+    ("movie", "film", "película"),
+    ("book", "livre", "libro"),
+    ("sleep", "dormir", "dormir"),
+    ("travel", "voyager", "viajar"),
+    ("dance", "danser", "bailar"),
+    ("family", "famille", "familia"),
+    ("friends", "amis", "amigos"),
+    ("spiders", "araignées", "arañas"),
+    ("heights", "hauteurs", "alturas"),
+    ("yes", "oui", "sí"),
+    ("no", "non", "no"),
+    ("and", "et", "y"),
+    ("with", "avec", "con"),
+    ("sea", "mer", "mar"),
+    ("sun", "soleil", "sol"),
+    ("home", "maison", "casa"),
+    ("paris", "paris", "parís"),
+];
+
+fn column(locale: &str) -> Option<usize> {
+    match locale {
+        "en" => Some(0),
+        "fr" => Some(1),
+        "es" => Some(2),
+        _ => None,
+    }
+}
+
+fn cell<'a>(row: &'a (&'a str, &'a str, &'a str), col: usize) -> &'a str {
+    match col {
+        0 => row.0,
+        1 => row.1,
+        _ => row.2,
+    }
+}
+
+#[rocket::async_trait]
+impl Translator for DictionaryTranslator {
+    async fn translate(&self, text: &str, from: &str, to: &str) -> Option<String> {
+        let (from, to) = (column(from)?, column(to)?);
+        let mut changed = false;
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(|word| {
+                let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+                let lower = core.to_lowercase();
+                match WORDS.iter().find(|row| cell(row, from) == lower) {
+                    Some(row) if cell(row, to) != lower && !core.is_empty() => {
+                        changed = true;
+                        word.replace(core, cell(row, to))
+                    }
+                    _ => word.to_string(),
+                }
+            })
+            .collect();
+        changed.then(|| words.join(" "))
+    }
+}
@@ -0,0 +1,110 @@
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::serde::{Deserialize, Serialize};
+
+// Result-page verdicts by compatibility score. Deployments can override the
+// bands with `[[default.verdicts]]` tables in Rocket.toml; together they must
+// cover 0-100 with no gaps or overlaps.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Verdict {
+    pub min: u32,
+    pub max: u32,
+    pub message: String,
+    #[serde(default)]
+    pub emoji: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Verdicts {
+    // sorted by `min`, validated
+    bands: Vec<Verdict>,
+}
+
+const DEFAULT_BANDS: &[(u32, u32, &str, &str)] = &[
+    (0, 59, "Nice Try", "😅"),
+    (60, 84, "Good Match", "💕"),
+    (85, 100, "Perfect Match", "💍💖"),
+];
+
+impl Default for Verdicts {
+    fn default() -> Self {
+        let bands = DEFAULT_BANDS
+            .iter()
+            .map(|(min, max, message, emoji)| Verdict {
+                min: *min,
+                max: *max,
+                message: message.to_string(),
+                emoji: emoji.to_string(),
+            })
+            .collect();
+        Verdicts { bands }
+    }
+}
+
+impl Verdicts {
+    pub fn new(mut bands: Vec<Verdict>) -> Result<Self, String> {
+        bands.sort_by_key(|b| b.min);
+        let mut next = 0;
+        for b in &bands {
+            if b.message.trim().is_empty() {
+                return Err(format!("verdict band {}-{} has no message", b.min, b.max));
+            }
+            if b.min > b.max {
+                return Err(format!("verdict band {}-{} is upside down", b.min, b.max));
+            }
+            if b.min > next {
+                return Err(format!("verdict bands leave {}-{} uncovered", next, b.min - 1));
+            }
+            if b.min < next {
+                return Err(format!("verdict band {}-{} overlaps the one before it", b.min, b.max));
+            }
+            next = b.max + 1;
+        }
+        if next <= 100 {
+            return Err(format!("verdict bands leave {}-100 uncovered", next));
+        }
+        if next > 101 {
+            return Err("verdict bands go past 100".to_string());
+        }
+        Ok(Verdicts { bands })
+    }
+
+    // Bands from config, or the built-in ones when none are set.
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        if figment.find_value("verdicts").is_err() {
+            return Ok(Verdicts::default());
+        }
+        let bands: Vec<Verdict> = figment
+            .extract_inner("verdicts")
+            .map_err(|e| format!("invalid `verdicts` config: {}", e))?;
+        Verdicts::new(bands)
+    }
+
+    pub fn for_score(&self, score: u32) -> &Verdict {
+        let score = score.min(100);
+        self.bands
+            .iter()
+            .find(|b| b.min <= score && score <= b.max)
+            .unwrap_or(&self.bands[0])
+    }
+}
+
+impl Verdict {
+    pub fn label(&self) -> String {
+        if self.emoji.is_empty() {
+            self.message.clone()
+        } else {
+            format!("{} {}", self.message, self.emoji)
+        }
+    }
+}
+
+// Bad bands are reported by the startup checks; this only installs them.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Verdicts", |rocket| async move {
+        let verdicts = Verdicts::load(rocket.figment()).unwrap_or_default();
+        rocket.manage(verdicts)
+    })
+}
@@ -0,0 +1,174 @@
+use parking_lot::RwLock;
+use rocket::fairing::AdHoc;
+use rocket::serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::questions::{QuestionBank, QuestionKind, QuestionStore};
+use crate::scheduler::now_secs;
+use crate::snapshots::{Snapshot, SnapshotStore};
+
+// "What everyone says": the words people use in free-text answers, per
+// question, across every game whose host made the result public. Only
+// single words are kept, never whole answers or who wrote them, and a word
+// shows up only once enough separate games used it, so no one couple's
+// answer can be picked out. Rebuilt by a batch job, not per view.
+
+// a question needs this many public games before it gets a cloud
+const MIN_GAMES_PER_QUESTION: usize = 5;
+// and a word this many games using it
+const MIN_GAMES_PER_WORD: usize = 3;
+const MAX_WORDS: usize = 30;
+const MIN_WORD_CHARS: usize = 3;
+// font steps on the page
+const SIZES: usize = 5;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "you", "your", "our", "are", "was", "but", "not", "have", "has",
+    "just", "from", "they", "them", "his", "her", "its", "would", "could", "what", "when", "who", "how", "all",
+    "any", "some", "very", "really", "much", "more", "most", "one", "out", "into", "about", "then", "than",
+    "also", "too", "like", "les", "des", "une", "est", "pour", "avec", "que", "qui", "dans", "los", "las", "una",
+    "con", "por", "para", "del",
+];
+
+// --- Models ---
+#[derive(Debug, Default, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WordCloud {
+    pub questions: Vec<QuestionCloud>,
+    // public games looked at
+    pub games: usize,
+    pub built_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct QuestionCloud {
+    pub question_id: String,
+    pub question: String,
+    pub category: String,
+    pub games: usize,
+    // most used first
+    pub words: Vec<WordCount>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WordCount {
+    pub word: String,
+    // games that used it
+    pub count: usize,
+    // 1 (smallest) to SIZES, relative to the top word
+    pub size: usize,
+}
+
+// --- Building ---
+pub fn build(games: &[Snapshot], bank: &QuestionBank) -> WordCloud {
+    // question id -> (text, category, games asked in, word -> games using it)
+    type Tally<'a> = (&'a str, &'a str, usize, HashMap<String, usize>);
+    let mut tallies: BTreeMap<&str, Tally> = BTreeMap::new();
+    for game in games {
+        let t = &game.transcript;
+        // names of the players never count as words
+        let names: HashSet<String> = t.players.iter().flat_map(|p| words(&p.name)).collect();
+        for q in &t.questions {
+            let free_text = bank.get(&q.question_id).is_some_and(|b| b.kind == QuestionKind::FreeText);
+            if !free_text || q.answers.is_empty() {
+                continue;
+            }
+            let tally = tallies
+                .entry(&q.question_id)
+                .or_insert_with(|| (&q.question, &q.category, 0, HashMap::new()));
+            tally.2 += 1;
+            // once per game, however many times the couple said it
+            let used: HashSet<String> = q
+                .answers
+                .iter()
+                .flat_map(|a| words(&a.answer))
+                .filter(|w| !names.contains(w))
+                .collect();
+            for word in used {
+                *tally.3.entry(word).or_default() += 1;
+            }
+        }
+    }
+
+    let questions = tallies
+        .into_iter()
+        .filter(|(_, (_, _, asked, _))| *asked >= MIN_GAMES_PER_QUESTION)
+        .filter_map(|(id, (question, category, asked, counts))| {
+            let mut counts: Vec<(String, usize)> =
+                counts.into_iter().filter(|(_, n)| *n >= MIN_GAMES_PER_WORD).collect();
+            if counts.is_empty() {
+                return None;
+            }
+            counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            counts.truncate(MAX_WORDS);
+            let top = counts[0].1;
+            Some(QuestionCloud {
+                question_id: id.to_string(),
+                question: question.to_string(),
+                category: category.to_string(),
+                games: asked,
+                words: counts
+                    .into_iter()
+                    .map(|(word, count)| WordCount {
+                        word,
+                        count,
+                        size: (count * SIZES).div_ceil(top).max(1),
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+    WordCloud {
+        questions,
+        games: games.len(),
+        built_at: Some(now_secs()),
+    }
+}
+
+// Lowercased words worth counting in `text`.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphabetic())
+        .filter(|w| w.chars().count() >= MIN_WORD_CHARS)
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+}
+
+// --- Service ---
+#[derive(Clone)]
+pub struct WordCloudService {
+    snapshots: SnapshotStore,
+    questions: QuestionStore,
+    current: Arc<RwLock<Arc<WordCloud>>>,
+}
+
+impl WordCloudService {
+    pub fn new(snapshots: SnapshotStore, questions: QuestionStore) -> Self {
+        WordCloudService {
+            snapshots,
+            questions,
+            current: Arc::default(),
+        }
+    }
+
+    pub fn current(&self) -> Arc<WordCloud> {
+        self.current.read().clone()
+    }
+
+    // Recounts every public game. Run by the scheduler.
+    pub fn refresh(&self) {
+        let cloud = build(&self.snapshots.public(), &self.questions.snapshot());
+        *self.current.write() = Arc::new(cloud);
+    }
+
+    // First build once the stores are loaded, so the page isn't empty until
+    // the job's first run.
+    pub fn fairing(&self) -> AdHoc {
+        let service = self.clone();
+        AdHoc::on_liftoff("Word Cloud", move |_| {
+            Box::pin(async move { service.refresh() })
+        })
+    }
+}
@@ -0,0 +1,278 @@
+use rocket::figment::Figment;
+use rocket::http::{ContentType, Cookie, RawStr, Status};
+use rocket::local::blocking::{Client, LocalRequest, LocalResponse};
+use rocket::serde::json::{json, Value};
+
+// Plays the same couple game twice, once through the pages' forms and once
+// through /api/v1, and checks both rooms went through the same states and
+// logged the same events after every move. A feature that lands in one
+// path and not the other shows up here as the first move they disagree on.
+//
+// Games are dealt from `contract/questions.json` (one question of every
+// kind, plus a follow-up) and both players always give the same answers,
+// so scores don't depend on which question came when. Everything the app
+// would write goes to a scratch folder, removed afterwards.
+
+const QUESTIONS: &str = "contract/questions.json";
+// every *_path setting, so the run leaves the real data alone
+const DATA_KEYS: &[&str] = &[
+    "outbox_path",
+    "question_store_path",
+    "snapshot_path",
+    "history_path",
+    "announcement_path",
+    "audit_path",
+    "room_store_path",
+    "shutdown_snapshot_path",
+];
+// more than a game needs; a room that never finishes stops here
+const MAX_ROUNDS: usize = 50;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Path {
+    Form,
+    Api,
+}
+
+struct Seat {
+    id: String,
+    // the client doesn't keep cookies, so each seat carries its own
+    jar: Vec<Cookie<'static>>,
+}
+
+impl Seat {
+    fn new() -> Seat {
+        Seat {
+            id: String::new(),
+            // passes the age gate, if there is one
+            jar: vec![Cookie::new("age_ok", "99")],
+        }
+    }
+
+    fn keep(&mut self, response: &LocalResponse<'_>) {
+        for cookie in response.cookies().iter() {
+            self.jar.retain(|c| c.name() != cookie.name());
+            self.jar.push(cookie.clone());
+        }
+    }
+}
+
+// One room played through one path, with the room after every move.
+struct Game<'c> {
+    client: &'c Client,
+    path: Path,
+    code: String,
+    seats: Vec<Seat>,
+    moves: Vec<(String, Value)>,
+}
+
+#[test]
+fn forms_and_api_agree_after_every_move() {
+    let scratch = std::env::temp_dir().join(format!("now-contract-{}", std::process::id()));
+    let rocket = moyosola_gift_app::rocket();
+    let mut figment: Figment = rocket
+        .figment()
+        .clone()
+        .merge(("log_level", "critical"))
+        .merge(("questions_path", QUESTIONS))
+        .merge(("room_storage", "memory"))
+        .merge(("shutdown_snapshot", false));
+    for key in DATA_KEYS {
+        figment = figment.merge((*key, scratch.join(format!("{}.json", key))));
+    }
+    let client = Client::untracked(rocket.configure(figment)).expect("the app starts");
+
+    let (form, api) = (play(&client, Path::Form), play(&client, Path::Api));
+    let _ = std::fs::remove_dir_all(&scratch);
+    let outcome = match (form, api) {
+        (Ok(form), Ok(api)) => compare(&form.moves, &api.moves),
+        (Err(e), _) => Err(format!("form game: {}", e)),
+        (_, Err(e)) => Err(format!("API game: {}", e)),
+    };
+    if let Err(problem) = outcome {
+        panic!("forms and /api/v1 have drifted apart: {}", problem);
+    }
+}
+
+fn play(client: &Client, path: Path) -> Result<Game<'_>, String> {
+    let mut game = Game {
+        client,
+        path,
+        code: String::new(),
+        seats: Vec::new(),
+        moves: Vec::new(),
+    };
+    game.create("Ada")?;
+    game.join("Ben")?;
+    for round in 0.. {
+        if game.finished() {
+            break;
+        }
+        if round == MAX_ROUNDS {
+            return Err(format!("still not finished after {} rounds", MAX_ROUNDS));
+        }
+        for seat in 0..game.seats.len() {
+            game.answer(seat)?;
+        }
+    }
+    Ok(game)
+}
+
+// The answer route and API body for `question`, as the API shows it:
+// the last option, the top of the scale, or the same words in every blank.
+fn answer_for(question: &Value) -> (&'static str, Value) {
+    let options = question["options"].as_array().cloned().unwrap_or_default();
+    match question["kind"]["type"].as_str().unwrap_or("free_text") {
+        "ranking" => ("rank", json!({ "order": (0..options.len()).rev().collect::<Vec<_>>() })),
+        "multiple_choice" => ("pick", json!({ "option": options.last() })),
+        "picture_choice" => ("pick", json!({ "option": options.last().map(|o| o["id"].clone()) })),
+        "scale" => ("rate", json!({ "value": question["options"]["max"] })),
+        _ => {
+            let blanks = question["blanks"].as_u64().unwrap_or(1);
+            let words: Vec<String> = (1..=blanks).map(|n| format!("answer {}", n)).collect();
+            ("answer", json!({ "answer": words }))
+        }
+    }
+}
+
+// An API body as the page's form fields; lists repeat their field.
+fn fields(body: &Value) -> Vec<(&str, String)> {
+    let plain = |v: &Value| v.as_str().map_or_else(|| v.to_string(), str::to_string);
+    let mut fields = Vec::new();
+    for (key, value) in body.as_object().into_iter().flatten() {
+        match value.as_array() {
+            Some(list) => fields.extend(list.iter().map(|v| (key.as_str(), plain(v)))),
+            None => fields.push((key.as_str(), plain(value))),
+        }
+    }
+    fields
+}
+
+// The number of moves compared, or the first one the paths disagree on.
+fn compare(form: &[(String, Value)], api: &[(String, Value)]) -> Result<usize, String> {
+    for ((step, a), (_, b)) in form.iter().zip(api) {
+        if a != b {
+            let keys = a.as_object().into_iter().flatten();
+            let differing: Vec<&str> = keys.filter(|(k, v)| b.get(k) != Some(v)).map(|(k, _)| k.as_str()).collect();
+            return Err(format!("after {}, they differ in: {}", step, differing.join(", ")));
+        }
+    }
+    if form.len() != api.len() {
+        return Err(format!("the form game took {} moves, the API game {}", form.len(), api.len()));
+    }
+    Ok(form.len())
+}
+
+impl<'c> Game<'c> {
+    fn create(&mut self, name: &str) -> Result<(), String> {
+        let mut host = Seat::new();
+        let request = match self.path {
+            Path::Form => self.form("/create", &[("host_name", name.to_string())]),
+            Path::Api => self.json("/api/v1/rooms", json!({ "host_name": name })),
+        };
+        let response = request.cookies(host.jar.clone()).dispatch();
+        host.keep(&response);
+        let (code, id) = self.seated(response)?;
+        self.code = code;
+        host.id = id;
+        self.seats.push(host);
+        self.record("create")
+    }
+
+    fn join(&mut self, name: &str) -> Result<(), String> {
+        let mut guest = Seat::new();
+        let request = match self.path {
+            Path::Form => self.form("/join", &[("code", self.code.clone()), ("name", name.to_string())]),
+            Path::Api => self.json(&format!("/api/v1/rooms/{}/join", self.code), json!({ "name": name })),
+        };
+        let response = request.cookies(guest.jar.clone()).dispatch();
+        guest.keep(&response);
+        let (_, id) = self.seated(response)?;
+        guest.id = id;
+        self.seats.push(guest);
+        self.record("join")
+    }
+
+    // Answers the current question as the API describes it to `seat`;
+    // every seat gives the same answer.
+    fn answer(&mut self, seat: usize) -> Result<(), String> {
+        let room = self
+            .client
+            .get(format!("/api/v1/rooms/{}", self.code))
+            .cookies(self.seats[seat].jar.clone())
+            .dispatch()
+            .into_json::<Value>()
+            .ok_or("the room couldn't be read")?;
+        let question = &room["question"];
+        if room["finished"] == true || room["answered"] == true || !question.is_object() {
+            return Ok(());
+        }
+        let (route, body) = answer_for(question);
+        let request = match self.path {
+            Path::Form => {
+                let mut fields = fields(&body);
+                fields.push(("player", self.seats[seat].id.clone()));
+                fields.push(("tab.question", question["index"].to_string()));
+                self.form(&format!("/play/{}/{}", self.code, route), &fields)
+            }
+            Path::Api => {
+                let mut body = body;
+                body["question"] = question["index"].clone();
+                self.json(&format!("/api/v1/rooms/{}/answer", self.code), body)
+            }
+        };
+        let response = request.cookies(self.seats[seat].jar.clone()).dispatch();
+        let expected = if self.path == Path::Form { Status::SeeOther } else { Status::Ok };
+        if response.status() != expected {
+            return Err(format!("{} by seat {} came back {}", route, seat + 1, response.status()));
+        }
+        self.record(&format!("question {} {} by seat {}", question["index"], route, seat + 1))
+    }
+
+    fn form(&self, uri: &str, fields: &[(&str, String)]) -> LocalRequest<'c> {
+        let body: Vec<String> = fields
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, RawStr::new(v).percent_encode().as_str()))
+            .collect();
+        self.client.post(uri.to_string()).header(ContentType::Form).body(body.join("&"))
+    }
+
+    fn json(&self, uri: &str, body: Value) -> LocalRequest<'c> {
+        self.client.post(uri.to_string()).header(ContentType::JSON).body(body.to_string())
+    }
+
+    // (code, player id) from a create or join: the page redirects to
+    // /play/<code>?player=<id>, the API answers `created` with the room.
+    fn seated(&self, response: LocalResponse<'_>) -> Result<(String, String), String> {
+        let status = response.status();
+        match self.path {
+            Path::Form => {
+                let location = response.headers().get_one("Location").unwrap_or_default().to_string();
+                let seat = location
+                    .strip_prefix("/play/")
+                    .and_then(|rest| rest.split_once("?player="))
+                    .map(|(code, id)| (code.to_string(), id.to_string()));
+                seat.ok_or_else(|| format!("came back {} to `{}`, not a seat", status, location))
+            }
+            Path::Api => {
+                let room = response.into_json::<Value>().unwrap_or_default();
+                let code = room["code"].as_str().filter(|_| status == Status::Created);
+                match (code, room["player_id"].as_str()) {
+                    (Some(code), Some(id)) => Ok((code.to_string(), id.to_string())),
+                    _ => Err(format!("came back {}: {}", status, room)),
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, step: &str) -> Result<(), String> {
+        let room = moyosola_gift_app::transitions(self.client.rocket(), &self.code)
+            .ok_or_else(|| format!("room {} is gone", self.code))?;
+        self.moves.push((step.to_string(), room));
+        Ok(())
+    }
+
+    fn finished(&self) -> bool {
+        self.moves.last().is_some_and(|(_, room)| room["finished"] == true)
+    }
+}
@@ -0,0 +1,102 @@
+use rocket::local::blocking::Client;
+use rocket::serde::json::{self, Value};
+use rocket::serde::Deserialize;
+use rocket_dyn_templates::Template;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Renders each page from a fixed context and compares it with the HTML it
+// rendered last time, so a change to a template (or to what a route passes
+// it) can't quietly break a page. Every case is a pair in `golden/`:
+// `<case>.json` names the template and holds its context, written to match
+// what the route builds, and `<case>.html` is the render we signed off on.
+// `GOLDEN_UPDATE=1 cargo test --test golden` rewrites the HTML after an
+// intended change; review the diff before committing it.
+//
+// Only the templates are loaded, with the admins' notice left off, so what
+// is stored or configured elsewhere can't change a render.
+
+const DIR: &str = "golden";
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct Case {
+    template: String,
+    context: Value,
+}
+
+#[test]
+fn pages_match_their_recorded_html() {
+    let update = std::env::var_os("GOLDEN_UPDATE").is_some();
+    let rocket = rocket::custom(rocket::Config::figment()).attach(Template::custom(|engines| {
+        engines
+            .tera
+            .register_function("announcement", |_: &HashMap<String, tera::Value>| Ok(tera::Value::Null));
+    }));
+    let client = Client::untracked(rocket).expect("templates load");
+
+    let cases = cases();
+    assert!(!cases.is_empty(), "no cases in `{}`", DIR);
+    let mut failures = Vec::new();
+    for path in cases {
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        let outcome = render(&client, &path).and_then(|html| {
+            let golden = path.with_extension("html");
+            if update {
+                std::fs::write(&golden, html).map_err(|e| format!("cannot write {}: {}", golden.display(), e))
+            } else {
+                compare(&golden, &html)
+            }
+        });
+        if let Err(problem) = outcome {
+            failures.push(format!("{}: {}", name, problem));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "some pages differ; `GOLDEN_UPDATE=1` accepts them:\n  {}",
+        failures.join("\n  ")
+    );
+}
+
+// The cases in DIR, by name.
+fn cases() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(DIR)
+        .map(|dir| dir.filter_map(|e| e.ok().map(|e| e.path())).collect())
+        .unwrap_or_default();
+    paths.retain(|p| p.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+    paths
+}
+
+fn render(client: &Client, path: &Path) -> Result<String, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let case: Case = json::from_str(&raw).map_err(|e| format!("bad case file: {}", e))?;
+    Template::show(client.rocket(), case.template.clone(), &case.context)
+        .ok_or_else(|| format!("`{}` failed to render (see the log above)", case.template))
+}
+
+// The first line that differs is usually enough to see what moved.
+fn compare(golden: &Path, html: &str) -> Result<(), String> {
+    let expected = std::fs::read_to_string(golden)
+        .map_err(|_| format!("no {} yet; run with `GOLDEN_UPDATE=1` to record it", golden.display()))?;
+    if expected == html {
+        return Ok(());
+    }
+    let mut was = expected.lines();
+    let mut now = html.lines();
+    for line in 1.. {
+        match (was.next(), now.next()) {
+            (Some(a), Some(b)) if a == b => continue,
+            (a, b) => {
+                return Err(format!(
+                    "line {} was `{}`, now `{}`",
+                    line,
+                    a.unwrap_or("<end>").trim(),
+                    b.unwrap_or("<end>").trim()
+                ))
+            }
+        }
+    }
+    unreachable!()
+}